@@ -0,0 +1,237 @@
+//! Reading and fetching the catalog data files in the format returned by the SmartCatalog API
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use serde_json::Value;
+use vislog_core::{CourseDetails, Program};
+use vislog_parser::{parse_courses, parse_programs, ParsingError};
+
+use self::error::{Error, Result};
+
+pub mod error {
+    use std::path::PathBuf;
+
+    use thiserror::Error;
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("failed to read {path:?}: {source}")]
+        Read {
+            path: PathBuf,
+            source: std::io::Error,
+        },
+        #[error("failed to write {path:?}: {source}")]
+        Write {
+            path: PathBuf,
+            source: std::io::Error,
+        },
+        #[error("{path:?} is not valid JSON: {source}")]
+        InvalidJson {
+            path: PathBuf,
+            source: serde_json::Error,
+        },
+        #[error("expected field `{field}` to be a JSON array in {path:?}")]
+        MissingItems { path: PathBuf, field: String },
+        #[error("failed to fetch {url}: {source}")]
+        Fetch { url: String, source: reqwest::Error },
+        #[error("{url} returned no {field} array, is it the right API?")]
+        UnexpectedResponse { url: String, field: String },
+    }
+}
+
+const PROGRAMS_URL: &str = "https://iq5prod1.smartcatalogiq.com/apis/progAPI?path=/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue&format=json";
+const COURSES_URL: &str = "https://iq5prod1.smartcatalogiq.com/apis/courseAPI?path=/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue&format=json";
+
+/// The kinds of data files making up a catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CatalogKind {
+    Programs,
+    Courses,
+}
+
+impl CatalogKind {
+    /// Name of the data file in a catalog directory, the same as in `data/`
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CatalogKind::Programs => "programs.json",
+            CatalogKind::Courses => "courses.json",
+        }
+    }
+
+    /// The API endpoint of the current undergraduate catalog, the same as the server's defaults
+    pub fn default_url(&self) -> &'static str {
+        match self {
+            CatalogKind::Programs => PROGRAMS_URL,
+            CatalogKind::Courses => COURSES_URL,
+        }
+    }
+
+    /// Path of the array of items in the data file. Ex: `{ "programs": { "program": [...] } }`
+    fn fields(&self) -> (&'static str, &'static str) {
+        match self {
+            CatalogKind::Programs => ("programs", "program"),
+            CatalogKind::Courses => ("courses", "course"),
+        }
+    }
+}
+
+impl Display for CatalogKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogKind::Programs => write!(f, "programs"),
+            CatalogKind::Courses => write!(f, "courses"),
+        }
+    }
+}
+
+/// A directory containing a `programs.json` and a `courses.json`
+#[derive(Debug, Clone)]
+pub struct CatalogDir(pub PathBuf);
+
+impl CatalogDir {
+    pub fn path(&self, kind: CatalogKind) -> PathBuf {
+        self.0.join(kind.file_name())
+    }
+
+    pub fn programs(&self) -> Result<(Vec<Program>, Vec<ParsingError>)> {
+        read_programs(&self.path(CatalogKind::Programs))
+    }
+
+    pub fn courses(&self) -> Result<(Vec<CourseDetails>, Vec<ParsingError>)> {
+        read_courses(&self.path(CatalogKind::Courses))
+    }
+}
+
+pub fn read_programs(path: &Path) -> Result<(Vec<Program>, Vec<ParsingError>)> {
+    Ok(parse_programs(read_items(path, CatalogKind::Programs)?))
+}
+
+pub fn read_courses(path: &Path) -> Result<(Vec<CourseDetails>, Vec<ParsingError>)> {
+    Ok(parse_courses(read_items(path, CatalogKind::Courses)?))
+}
+
+/// Reads the array of programs or courses nested in the data file at `path`
+pub fn read_items(path: &Path, kind: CatalogKind) -> Result<Vec<Value>> {
+    let json_str = std::fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.to_owned(),
+        source,
+    })?;
+    let mut json: Value = serde_json::from_str(&json_str).map_err(|source| Error::InvalidJson {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    items(&mut json, kind).map(std::mem::take).ok_or_else(|| {
+        let (outer, inner) = kind.fields();
+        Error::MissingItems {
+            path: path.to_owned(),
+            field: format!("{outer}.{inner}"),
+        }
+    })
+}
+
+/// Downloads a data file, making sure it has the expected shape before returning it
+pub fn fetch(url: &str, kind: CatalogKind) -> Result<Value> {
+    let fetch_error = |source| Error::Fetch {
+        url: url.to_owned(),
+        source,
+    };
+    let mut body: Value = reqwest::blocking::get(url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .map_err(fetch_error)?;
+
+    if items(&mut body, kind).is_none() {
+        let (outer, inner) = kind.fields();
+        return Err(Error::UnexpectedResponse {
+            url: url.to_owned(),
+            field: format!("{outer}.{inner}"),
+        });
+    }
+
+    Ok(body)
+}
+
+/// Writes `contents` to `path`, through a temporary file so that a data file is never left half
+/// written
+pub fn write(path: &Path, contents: &[u8]) -> Result<()> {
+    let write_error = |source| Error::Write {
+        path: path.to_owned(),
+        source,
+    };
+
+    let mut partial_path = path.as_os_str().to_owned();
+    partial_path.push(".partial");
+    std::fs::write(&partial_path, contents).map_err(write_error)?;
+    std::fs::rename(&partial_path, path).map_err(write_error)
+}
+
+/// The array of items of a data file, `None` if the JSON doesn't have the shape of `kind`
+fn items(json: &mut Value, kind: CatalogKind) -> Option<&mut Vec<Value>> {
+    let (outer, inner) = kind.fields();
+    json.get_mut(outer)
+        .and_then(|items| items.get_mut(inner))
+        .and_then(Value::as_array_mut)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reads_data_files() {
+        let catalog = CatalogDir(PathBuf::from("../data"));
+
+        let (programs, _errors) = catalog.programs().unwrap();
+        assert!(programs
+            .iter()
+            .any(|program| program.title.starts_with("Major in Computer Science")));
+
+        let (courses, _errors) = catalog.courses().unwrap();
+        assert!(courses.iter().any(|course| course.code() == "CSC 105"));
+    }
+
+    #[test]
+    fn rejects_files_of_the_wrong_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("programs.json");
+        write(
+            &path,
+            json!({ "courses": { "course": [] } })
+                .to_string()
+                .as_bytes(),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            read_items(&path, CatalogKind::Programs),
+            Err(Error::MissingItems { field, .. }) if field == "programs.program"
+        ));
+        assert_eq!(
+            read_items(&path, CatalogKind::Courses).unwrap(),
+            Vec::<Value>::new()
+        );
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_objects() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("programs.json");
+
+        for contents in [json!([1, 2]), json!({ "programs": [1] })] {
+            write(&path, contents.to_string().as_bytes()).unwrap();
+
+            assert!(matches!(
+                read_items(&path, CatalogKind::Programs),
+                Err(Error::MissingItems { field, .. }) if field == "programs.program"
+            ));
+        }
+    }
+}
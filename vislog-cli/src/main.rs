@@ -0,0 +1,276 @@
+//! `vislog`, for scripting catalog QA against the data files without starting the server.
+//!
+//! ```sh
+//! cargo run -p vislog-cli -- parse programs
+//! cargo run -p vislog-cli -- show program "Major in Computer Science"
+//! cargo run -p vislog-cli -- show course "CSC 215"
+//! cargo run -p vislog-cli -- export courses --format xlsx --output courses.xlsx
+//! cargo run -p vislog-cli -- diff data/2023 data/2024
+//! cargo run -p vislog-cli -- fetch courses
+//! ```
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+use clap::{Parser, Subcommand};
+use vislog_core::{
+    diff::CatalogDiff,
+    export::{self, course_rows, program_rows, ExportFormat},
+};
+
+use crate::{
+    catalog::{CatalogDir, CatalogKind},
+    show::{find_course, find_program, format_course, format_program, Lookup},
+};
+
+mod catalog;
+mod show;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "vislog",
+    about = "Parse, query and export the catalog data files"
+)]
+struct Cli {
+    /// Directory containing the `programs.json` and `courses.json` data files
+    #[arg(long, global = true, default_value = "data")]
+    data: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Parses a data file and reports what failed to parse, exiting with 1 if anything did
+    Parse {
+        kind: CatalogKind,
+        /// Data file to parse instead of the one in the data directory
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Prints the parsing errors as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Prints a single program or course
+    Show {
+        #[command(subcommand)]
+        item: ShowItem,
+    },
+    /// Exports the courses or the program requirements as CSV or XLSX rows
+    Export {
+        kind: CatalogKind,
+        /// Only exports the requirements of this program, looked up like in `show program`
+        #[arg(long)]
+        program: Option<String>,
+        #[arg(long, default_value = "csv", value_parser = parse_format)]
+        format: ExportFormat,
+        /// Written to stdout if not given
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Prints the changes between the catalogs of two data directories
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Downloads a data file from the catalog API into the data directory
+    Fetch {
+        kind: CatalogKind,
+        /// Defaults to the API endpoint of the current undergraduate catalog
+        #[arg(long)]
+        url: Option<String>,
+        /// Defaults to the data file in the data directory
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ShowItem {
+    /// Prints the requirements of a program, looked up by GUID or by (a part of) its title
+    Program { query: String },
+    /// Prints a course, looked up by GUID or by its code. Ex: "CSC 215"
+    Course { query: String },
+}
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let catalog = CatalogDir(cli.data);
+
+    let result = match cli.command {
+        Command::Parse { kind, file, json } => parse(&catalog, kind, file, json),
+        Command::Show { item } => show(&catalog, item),
+        Command::Export {
+            kind,
+            program,
+            format,
+            output,
+        } => export(&catalog, kind, program, format, output),
+        Command::Diff { old, new, json } => diff(CatalogDir(old), CatalogDir(new), json),
+        Command::Fetch { kind, url, output } => fetch(&catalog, kind, url, output),
+    };
+
+    match result {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse(
+    catalog: &CatalogDir,
+    kind: CatalogKind,
+    file: Option<PathBuf>,
+    json: bool,
+) -> Result<ExitCode> {
+    let path = file.unwrap_or_else(|| catalog.path(kind));
+    let (parsed, errors) = match kind {
+        CatalogKind::Programs => {
+            let (programs, errors) = catalog::read_programs(&path)?;
+            (programs.len(), errors)
+        }
+        CatalogKind::Courses => {
+            let (courses, errors) = catalog::read_courses(&path)?;
+            (courses.len(), errors)
+        }
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&errors)?);
+    } else {
+        for error in &errors {
+            println!("{error}");
+        }
+    }
+    eprintln!(
+        "Parsed {parsed} {kind} from {path:?}, {} failed to parse",
+        errors.len()
+    );
+
+    Ok(if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn show(catalog: &CatalogDir, item: ShowItem) -> Result<ExitCode> {
+    match item {
+        ShowItem::Program { query } => {
+            let (programs, _errors) = catalog.programs()?;
+            match find_program(&programs, &query) {
+                Lookup::Found(program) => print!("{}", format_program(program)),
+                Lookup::NotFound => return Err(format!("no program matches {query:?}").into()),
+                Lookup::Ambiguous(matches) => {
+                    eprintln!("{} programs match {query:?}:", matches.len());
+                    for program in matches {
+                        eprintln!("  {} {}", program.guid, program.title);
+                    }
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+        ShowItem::Course { query } => {
+            let (courses, _errors) = catalog.courses()?;
+            match find_course(&courses, &query) {
+                Lookup::Found(course) => print!("{}", format_course(course)),
+                Lookup::NotFound => return Err(format!("no course matches {query:?}").into()),
+                // Courses listed more than once under the same code, print them all
+                Lookup::Ambiguous(matches) => {
+                    let formatted: Vec<String> = matches.into_iter().map(format_course).collect();
+                    print!("{}", formatted.join("\n"));
+                }
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn export(
+    catalog: &CatalogDir,
+    kind: CatalogKind,
+    program: Option<String>,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> Result<ExitCode> {
+    let contents = match kind {
+        CatalogKind::Courses => {
+            let (courses, _errors) = catalog.courses()?;
+            export::write(format, &course_rows(&courses))?
+        }
+        CatalogKind::Programs => {
+            let (programs, _errors) = catalog.programs()?;
+            let programs = match program {
+                Some(query) => match find_program(&programs, &query) {
+                    Lookup::Found(program) => vec![program],
+                    Lookup::NotFound => return Err(format!("no program matches {query:?}").into()),
+                    Lookup::Ambiguous(matches) => {
+                        return Err(format!("{} programs match {query:?}", matches.len()).into())
+                    }
+                },
+                None => programs.iter().collect(),
+            };
+
+            let rows: Vec<_> = programs.into_iter().flat_map(program_rows).collect();
+            export::write(format, &rows)?
+        }
+    };
+
+    match output {
+        Some(path) => {
+            catalog::write(&path, &contents)?;
+            eprintln!("Wrote {kind} to {path:?}");
+        }
+        None => match std::io::stdout().write_all(&contents) {
+            // Piped into something like `head` that stopped reading
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {}
+            result => result?,
+        },
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(old: CatalogDir, new: CatalogDir, json: bool) -> Result<ExitCode> {
+    let (old_programs, _) = old.programs()?;
+    let (new_programs, _) = new.programs()?;
+    let (old_courses, _) = old.courses()?;
+    let (new_courses, _) = new.courses()?;
+
+    let diff = CatalogDiff::new(&old_programs, &new_programs, &old_courses, &new_courses);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn fetch(
+    catalog: &CatalogDir,
+    kind: CatalogKind,
+    url: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<ExitCode> {
+    let url = url.as_deref().unwrap_or(kind.default_url());
+    let path = output.unwrap_or_else(|| catalog.path(kind));
+
+    eprintln!("Fetching {kind} from {url}");
+    let body = catalog::fetch(url, kind)?;
+    catalog::write(&path, serde_json::to_string_pretty(&body)?.as_bytes())?;
+    eprintln!("Wrote {kind} to {path:?}");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_format(format: &str) -> std::result::Result<ExportFormat, String> {
+    ExportFormat::try_from(format).map_err(|_| format!("expected `csv` or `xlsx`, not `{format}`"))
+}
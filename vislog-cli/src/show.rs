@@ -0,0 +1,299 @@
+//! Looking up a single program or course and printing it for humans
+use std::fmt::Write;
+
+use vislog_core::{
+    credits::{CreditHours, Credits},
+    parsing::guid::Guid,
+    CourseDetails, CourseEntries, CourseEntry, Program, Requirement, RequirementModule,
+};
+
+/// What came out of looking up an item by a query typed by a human
+#[derive(Debug, PartialEq, Eq)]
+pub enum Lookup<'a, T> {
+    Found(&'a T),
+    NotFound,
+    /// The query matches several items, none of them exactly
+    Ambiguous(Vec<&'a T>),
+}
+
+/// Finds a program by GUID, by title or by a part of its title, ignoring case
+pub fn find_program<'a>(programs: &'a [Program], query: &str) -> Lookup<'a, Program> {
+    if let Ok(guid) = Guid::try_from(query) {
+        return programs
+            .iter()
+            .find(|program| program.guid == guid)
+            .map_or(Lookup::NotFound, Lookup::Found);
+    }
+
+    let query = query.to_lowercase();
+    if let Some(program) = programs
+        .iter()
+        .find(|program| program.title.to_lowercase() == query)
+    {
+        return Lookup::Found(program);
+    }
+
+    let mut matches: Vec<&Program> = programs
+        .iter()
+        .filter(|program| program.title.to_lowercase().contains(&query))
+        .collect();
+    match matches.len() {
+        0 => Lookup::NotFound,
+        1 => Lookup::Found(matches[0]),
+        _ => {
+            matches.sort();
+            Lookup::Ambiguous(matches)
+        }
+    }
+}
+
+/// Finds a course by GUID or by its code, ignoring case and spacing. Ex: "CSC 105" or "csc105"
+pub fn find_course<'a>(courses: &'a [CourseDetails], query: &str) -> Lookup<'a, CourseDetails> {
+    if let Ok(guid) = Guid::try_from(query) {
+        return courses
+            .iter()
+            .find(|course| course.guid == guid)
+            .map_or(Lookup::NotFound, Lookup::Found);
+    }
+
+    let query = normalize_code(query);
+    let matches: Vec<&CourseDetails> = courses
+        .iter()
+        .filter(|course| normalize_code(&course.code()) == query)
+        .collect();
+    match matches.len() {
+        0 => Lookup::NotFound,
+        1 => Lookup::Found(matches[0]),
+        _ => Lookup::Ambiguous(matches),
+    }
+}
+
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// The title of the program followed by an outline of its requirements
+pub fn format_program(program: &Program) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", program.title).unwrap();
+    writeln!(out, "GUID: {}", program.guid).unwrap();
+    writeln!(out, "URL: {}", program.url).unwrap();
+    writeln!(out, "Courses: {}", program.courses().len()).unwrap();
+
+    for module in program.requirement_modules() {
+        writeln!(out).unwrap();
+        match module {
+            RequirementModule::Unimplemented(_) => {
+                writeln!(out, "(requirements that could not be parsed)").unwrap()
+            }
+            RequirementModule::SelectOneEmphasis { .. } => {
+                writeln!(out, "Select one emphasis:").unwrap()
+            }
+            _ => writeln!(out, "{}", module.title().unwrap_or("Requirements")).unwrap(),
+        }
+
+        for requirement in module.requirements() {
+            format_requirement(&mut out, requirement);
+        }
+    }
+
+    out
+}
+
+fn format_requirement(out: &mut String, requirement: &Requirement) {
+    let indent = 1;
+    match requirement {
+        Requirement::Label {
+            title,
+            req_narrative,
+        } => {
+            for text in [title, req_narrative].into_iter().flatten() {
+                writeln!(out, "{}{text}", "  ".repeat(indent)).unwrap();
+            }
+        }
+        Requirement::Courses { title, courses } => {
+            let depth = match title {
+                Some(title) => {
+                    writeln!(out, "{}{title}", "  ".repeat(indent)).unwrap();
+                    indent + 1
+                }
+                None => indent,
+            };
+            format_entries(out, courses, depth);
+        }
+        Requirement::SelectFromCourses { title, courses } => {
+            writeln!(out, "{}{title}", "  ".repeat(indent)).unwrap();
+            if let Some(courses) = courses {
+                format_entries(out, courses, indent + 1);
+            }
+        }
+    }
+}
+
+fn format_entries(out: &mut String, entries: &CourseEntries, depth: usize) {
+    let indent = "  ".repeat(depth);
+    for entry in entries.iter() {
+        match entry {
+            CourseEntry::And(entries) => {
+                writeln!(out, "{indent}All of:").unwrap();
+                format_entries(out, entries, depth + 1);
+            }
+            CourseEntry::Or(entries) => {
+                writeln!(out, "{indent}One of:").unwrap();
+                format_entries(out, entries, depth + 1);
+            }
+            CourseEntry::Label(label) => {
+                writeln!(
+                    out,
+                    "{indent}{} ({})",
+                    label.name,
+                    format_credits(label.credits)
+                )
+                .unwrap();
+            }
+            CourseEntry::Course(course) => {
+                writeln!(
+                    out,
+                    "{indent}{} {} ({})",
+                    course.code(),
+                    course.name.as_deref().unwrap_or_default(),
+                    format_credits(course.credits)
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+/// The course with its description and requisites as they are written in the catalog
+pub fn format_course(course: &CourseDetails) -> String {
+    let mut out = String::new();
+    writeln!(out, "{} {}", course.code(), course.name).unwrap();
+    writeln!(out, "GUID: {}", course.guid).unwrap();
+    writeln!(out, "URL: {}", course.url).unwrap();
+    if let Some(subject_name) = &course.subject_name {
+        writeln!(out, "Subject: {subject_name}").unwrap();
+    }
+    writeln!(out, "Credits: {}", format_credits(course.credits)).unwrap();
+    if let Some(prerequisites) = &course.prerequisite_narrative {
+        writeln!(out, "Prerequisites: {prerequisites}").unwrap();
+    }
+    if let Some(corequisites) = &course.corequisite_narrative {
+        writeln!(out, "Corequisites: {corequisites}").unwrap();
+    }
+    if !course.cross_listed.is_empty() {
+        writeln!(out, "Cross-listed as: {}", course.cross_listed.join(", ")).unwrap();
+    }
+    let narratives = [
+        ("Offered", &course.offered),
+        ("Distribution", &course.distribution),
+        ("Instructor", &course.instructor),
+        ("Notes", &course.notes),
+    ];
+    for (label, narrative) in narratives {
+        if let Some(narrative) = narrative {
+            writeln!(out, "{label}: {narrative}").unwrap();
+        }
+    }
+    if !course.description.is_empty() {
+        writeln!(out, "\n{}", course.description).unwrap();
+    }
+
+    out
+}
+
+/// Ex: "1 credit", "0.5 credits" or "1-4 credits"
+fn format_credits(credits: Credits) -> String {
+    if credits == Credits::fixed(CreditHours::whole(1)) {
+        "1 credit".to_owned()
+    } else {
+        format!("{credits} credits")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::catalog::{read_courses, read_programs};
+
+    use super::*;
+
+    fn programs() -> Vec<Program> {
+        read_programs(Path::new("../data/programs.json")).unwrap().0
+    }
+
+    fn courses() -> Vec<CourseDetails> {
+        read_courses(Path::new("../data/courses.json")).unwrap().0
+    }
+
+    #[test]
+    fn finds_programs_by_guid_and_title() {
+        let programs = programs();
+
+        let Lookup::Found(by_guid) =
+            find_program(&programs, "5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5")
+        else {
+            panic!("expected the CS major");
+        };
+        assert_eq!(by_guid.title, "Major in Computer Science—42 hours");
+
+        assert_eq!(
+            find_program(&programs, "major in computer science—42 hours"),
+            Lookup::Found(by_guid)
+        );
+        assert!(matches!(
+            find_program(&programs, "Computer Science"),
+            Lookup::Ambiguous(matches) if matches.contains(&by_guid)
+        ));
+        assert_eq!(
+            find_program(&programs, "Major in Basket Weaving"),
+            Lookup::NotFound
+        );
+    }
+
+    #[test]
+    fn finds_courses_by_code() {
+        let courses = courses();
+
+        let Lookup::Found(course) = find_course(&courses, "CSC 105") else {
+            panic!("expected CSC 105");
+        };
+        assert_eq!(course.code(), "CSC 105");
+        assert_eq!(find_course(&courses, "csc105"), Lookup::Found(course));
+        assert_eq!(
+            find_course(&courses, &course.guid.to_string()),
+            Lookup::Found(course)
+        );
+        assert_eq!(find_course(&courses, "CSC 999"), Lookup::NotFound);
+    }
+
+    #[test]
+    fn formats_program_outline() {
+        let programs = programs();
+        let Lookup::Found(program) = find_program(&programs, "Major in Computer Science—42 hours")
+        else {
+            panic!("expected the CS major");
+        };
+
+        let outline = format_program(program);
+        assert!(outline.starts_with("Major in Computer Science—42 hours\nGUID: "));
+        assert!(outline
+            .contains("\n    CSC 115 Computer Science: Introduction and Overview (3 credits)\n"));
+    }
+
+    #[test]
+    fn formats_fractional_and_variable_credits() {
+        let hours = CreditHours::from_hundredths;
+
+        assert_eq!(format_credits(Credits::fixed(hours(100))), "1 credit");
+        assert_eq!(format_credits(Credits::fixed(hours(50))), "0.5 credits");
+        assert_eq!(
+            format_credits(Credits::range(hours(100), hours(400))),
+            "1-4 credits"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+// Generated by vislog-core from its serialized types, do not edit by hand.
+
+/**
+ * Representation of a the bare minimum of course in the catalog more details
+ * are contained in (CourseDetails)[crate::CourseDetails]
+ */
+export type Course = { url: string, path: string, guid: Guid, 
+/**
+ * This field is normally not, but sometimes can be empty for special courses.
+ *
+ * ### Examples
+ * Such examples can be found in the "Applied Studies" `Requirement` for [Bachelor of Music with Major in Worship Leadership](https://iq5prod1.smartcatalogiq.com/en/catalogs/union-university/2023/academic-catalogue-undergraduate-catalogue/college-of-arts-and-sciences/department-of-music/bachelor-of-music-with-major-in-worship-leadership-83-hours-36-hours-47-hour-worship-leadership-core)
+ *
+ * #### Ex: Applied Studies-12 hours:
+ * | Course  |     Name     |     Credits     |
+ * |---------|--------------|-----------------|
+ * | MUS 150 | <empty-name> | <empty-credits> |
+ * | MUS 150 | <empty-name> | <empty-credits> |
+ * | MUS 250 | <empty-name> | <empty-credits> |
+ * | MUS 250 | <empty-name> | <empty-credits> |
+ * | MUS 350 | <empty-name> | <empty-credits> |
+ * | MUS 350 | <empty-name> | <empty-credits> |
+ * | MUS 450 | <empty-name> | <empty-credits> |
+ *
+ */
+name: string | null, number: string, subject_name: string | null, subject_code: string, 
+/**
+ * The credits earned by completing the course, a range of them when the student chooses how
+ * many
+ */
+credits: Credits, };
+
+/**
+ * Representation of a course along with additional details
+ */
+export type CourseDetails = { url: string, guid: Guid, path: string, subject_code: string, subject_name: string | null, number: string, name: string, 
+/**
+ * Parsed from `credits_min` and `credits_max` in the catalog, or from `credits_narrative` when
+ * they are missing
+ */
+credits: Credits, description: string, prerequisite_narrative: string | null, prerequisite: Guid | null, corequisite_narrative: string | null, corequisite: Guid | null, 
+/**
+ * Ex: "Also listed as SOC 318."
+ */
+crosslist_narrative: string | null, 
+/**
+ * Codes of the courses this one is cross-listed with, parsed from `crosslist_narrative` and
+ * the description. Ex: ["SOC 318"]. See [Equivalences](crate::equivalence::Equivalences)
+ * for the groups of courses counting as the same course
+ */
+cross_listed: Array<string>, 
+/**
+ * The credits as written in the catalog. Ex: "1.0 - 4.0"
+ */
+credits_narrative: string | null, 
+/**
+ * When the course is offered as written in the catalog. Ex: "Fall of odd years"
+ */
+offered: string | null, 
+/**
+ * `offered` parsed into terms, empty if it's missing or couldn't be parsed
+ */
+offerings: Array<Offering>, 
+/**
+ * The general education requirements the course counts toward as written in the catalog
+ */
+distribution: string | null, 
+/**
+ * The categories recognized in `distribution`
+ */
+gen_ed: Array<GenEdCategory>, instructor: string | null, notes: string | null, };
+
+export type CourseEntries = Array<CourseEntry>;
+
+export type CourseEntry = { "type": "And", "data": CourseEntries } | { "type": "Or", "data": CourseEntries } | { "type": "Label", "data": Label } | { "type": "Course", "data": Course };
+
+/**
+ * Credit hours earned by completing a course. When the catalog gives a range the student chooses
+ * an amount from `min` to `max`, otherwise both are the same
+ */
+export type Credits = { min: number, max: number, 
+/**
+ * Whether `max` is greater than `min`, i.e. the amount of credits is up to the student
+ */
+variable: boolean, };
+
+export type Edge = { 
+/**
+ * Made of the kind and the ids of both ends, so it's only as stable as the ids of the nodes.
+ * Ex: "prerequisite:course-{GUID}->course-{GUID}"
+ */
+id: string, from: string, to: string, kind: EdgeKind, };
+
+export type EdgeKind = "contains" | "prerequisite" | "corequisite";
+
+/**
+ * Categories of the general education requirements
+ */
+export type GenEdCategory = "christian-studies" | "communication" | "fine-arts" | "foreign-language" | "history" | "humanities" | "mathematics" | "natural-science" | "social-science" | "wellness";
+
+/**
+ * Uppercase hyphenated GUID without braces. Ex: "5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5"
+ */
+export type Guid = string;
+
+export type JsonValue = number | string | boolean | Array<JsonValue> | { [key in string]?: JsonValue } | null;
+
+export type Label = { url: string, guid: Guid, name: string, number: string | null, subject_code: string | null, credits: Credits, };
+
+export type Node = { 
+/**
+ * Courses and labels are identified by their GUID, so their ids are stable across versions
+ * of the catalog. Everything else is identified by its position in the requirements of the
+ * program (Ex: "module-0", "requirement-0-1", "or-0-1-2"), which changes whenever an entry is
+ * added, removed or moved before it
+ */
+id: string, kind: NodeKind, 
+/**
+ * Short text identifying the node. Ex: "CSC 215" for a course
+ */
+label: string, 
+/**
+ * Name of a course or label. Ex: "Data Structures" for a course
+ */
+name: string | null, 
+/**
+ * Id of the module the node is drawn in. `None` for modules and for the courses only
+ * reached as prerequisites of courses in the program
+ */
+module: string | null, 
+/**
+ * Level of a course based on its number. Ex: 200 for "CSC 215"
+ */
+level: number | null, };
+
+export type NodeKind = "module" | "requirement" | "and-group" | "or-group" | "course" | "label";
+
+/**
+ * A term in which a course is offered
+ */
+export type Offering = { term: Term, years: Years, };
+
+/**
+ * The courses a student takes each term, in the order the terms are taken
+ */
+export type Plan = { terms: Array<PlanTerm>, };
+
+export type PlanTerm = { 
+/**
+ * Ex: "Fall 2024" or "Year 1 - Spring"
+ */
+name: string, 
+/**
+ * GUIDs of the courses taken during the term
+ */
+courses: Array<Guid>, };
+
+/**
+ * Representation of a program in the catalog
+ *
+ */
+export type Program = { 
+/**
+ * Link to the official catalog
+ */
+url: string, 
+/**
+ * GUID given by the system
+ */
+guid: Guid, 
+/**
+ * Name of the program
+ */
+title: string, 
+/**
+ * Introductory information about the program
+ */
+content: string | null, 
+/**
+ * Last information on the page about the program
+ */
+bottom_content: string | null, 
+/**
+ * Course requirements for the Program
+ */
+requirements: Requirements | null, };
+
+/**
+ * Every module, requirement, operator group, course and label of a program as the nodes of a
+ * graph, in the order they appear in the catalog.
+ *
+ * Serialized as a flat list of nodes and edges that graph libraries can load as is, without
+ * walking the nested requirements of the program
+ */
+export type ProgramGraph = { guid: Guid, title: string, url: string, nodes: Array<Node>, edges: Array<Edge>, };
+
+export type Requirement = { "type": "Courses", "data": { title: string | null, 
+/**
+ * Originally `course` in the JSON payload:w
+ */
+courses: CourseEntries, } } | { "type": "SelectFromCourses", "data": { title: string, courses: CourseEntries | null, } } | { "type": "Label", "data": { title: string | null, req_narrative: string | null, } };
+
+export type RequirementModule = { "type": "SingleBasicRequirement", "data": { title: string | null, 
+/**
+ * Originally `requirement_list` in the JSON payload
+ */
+requirement: Requirement, } } | { "type": "BasicRequirements", "data": { title: string | null, requirements: Array<Requirement>, } } | { "type": "SelectOneEmphasis", "data": { emphases: Array<Requirement>, } } | { "type": "Label", "data": { title: string, } } | { "type": "Unimplemented", "data": JsonValue };
+
+export type Requirements = { "type": "Single", "data": RequirementModule } | { "type": "Many", "data": Array<RequirementModule> } | { "type": "SelectTrack" };
+
+export type Term = "fall" | "spring" | "summer";
+
+/**
+ * The years in which a term offering takes place
+ */
+export type Years = "every" | "odd" | "even";
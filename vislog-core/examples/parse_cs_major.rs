@@ -1,4 +1,3 @@
-use serde_json;
 use vislog_core::{Course, CourseEntries, Label, Program, Requirement};
 
 fn main() {
@@ -23,11 +22,11 @@ fn main() {
         .map(|req_mods| {
             req_mods
                 .iter()
-                .map(|m| match m {
+                .flat_map(|m| match m {
                     vislog_core::RequirementModule::SingleBasicRequirement {
                         requirement, ..
                     } => {
-                        vec![get_req_title(&requirement)]
+                        vec![get_req_title(requirement)]
                     }
                     vislog_core::RequirementModule::BasicRequirements { requirements, .. } => {
                         requirements.iter().map(get_req_title).collect()
@@ -37,7 +36,6 @@ fn main() {
                     vislog_core::RequirementModule::Unimplemented(_) => todo!(),
                 })
                 .flatten()
-                .flatten()
                 .collect::<Vec<_>>()
         });
     // println!("Requirements: {:?}", requirements);
@@ -60,12 +58,11 @@ fn main() {
                 .map(|m| match m {
                     vislog_core::RequirementModule::SingleBasicRequirement {
                         requirement, ..
-                    } => get_req_courses_titles(&requirement),
+                    } => get_req_courses_titles(requirement),
                     vislog_core::RequirementModule::BasicRequirements { requirements, .. } => {
                         requirements
                             .iter()
-                            .map(get_req_courses_titles)
-                            .flatten()
+                            .flat_map(get_req_courses_titles)
                             .collect()
                     }
                     vislog_core::RequirementModule::SelectOneEmphasis { .. } => todo!(),
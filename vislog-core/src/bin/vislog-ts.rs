@@ -0,0 +1,23 @@
+//! Writes the TypeScript declarations of the `vislog-core` types to the given file, or to stdout
+//! if no file is given.
+//!
+//! Ex: `cargo run -p vislog-core --features typescript --bin vislog-ts -- types/vislog-core.d.ts`
+
+use std::path::PathBuf;
+
+fn main() -> anyhow::Result<()> {
+    let declarations = vislog_core::typescript::declarations();
+
+    match std::env::args_os().nth(1).map(PathBuf::from) {
+        Some(path) => {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, declarations)?;
+            eprintln!("Wrote TypeScript declarations to {path:?}");
+        }
+        None => print!("{declarations}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,333 @@
+//! Credit hours of courses and labels. Credits in the catalog can be fractional, like the 0.5 hour
+//! ensembles, and variable, like the "1.0 - 4.0" hours of independent studies
+use std::{fmt::Display, iter::Sum, ops::Add, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// An amount of credit hours kept in hundredths of an hour so that fractional credits add up
+/// exactly. Serialized as a decimal number. Ex: 1.5
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CreditHours(u32);
+
+impl CreditHours {
+    pub const ZERO: CreditHours = CreditHours(0);
+
+    /// Saturates at the largest amount of hundredths instead of overflowing
+    pub const fn whole(hours: u32) -> Self {
+        Self(hours.saturating_mul(100))
+    }
+
+    pub const fn from_hundredths(hundredths: u32) -> Self {
+        Self(hundredths)
+    }
+
+    pub const fn hundredths(&self) -> u32 {
+        self.0
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        f64::from(self.0) / 100.0
+    }
+}
+
+impl FromStr for CreditHours {
+    type Err = CreditsParsingError;
+
+    /// Ex: "3", "3.0", "0.5" or "1.25"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+
+        let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !all_digits(whole) || !all_digits(fraction)
+        {
+            return Err(CreditsParsingError::InvalidHours(s.to_owned()));
+        }
+
+        // Digits past the hundredths can only be trailing zeros. Ex: "3.000"
+        let (hundredths, rest) = fraction.split_at(fraction.len().min(2));
+        if rest.chars().any(|c| c != '0') {
+            return Err(CreditsParsingError::TooPrecise(s.to_owned()));
+        }
+
+        let whole: u32 = match whole {
+            "" => 0,
+            whole => whole
+                .parse()
+                .map_err(|_| CreditsParsingError::InvalidHours(s.to_owned()))?,
+        };
+        let hundredths: u32 = format!("{hundredths:0<2}").parse().unwrap_or_default();
+
+        whole
+            .checked_mul(100)
+            .and_then(|whole| whole.checked_add(hundredths))
+            .map(Self)
+            .ok_or_else(|| CreditsParsingError::InvalidHours(s.to_owned()))
+    }
+}
+
+impl Display for CreditHours {
+    /// Without trailing zeros. Ex: "3", "1.5" or "0.25"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (whole, hundredths) = (self.0 / 100, self.0 % 100);
+        match hundredths {
+            0 => write!(f, "{whole}"),
+            _ if hundredths % 10 == 0 => write!(f, "{whole}.{}", hundredths / 10),
+            _ => write!(f, "{whole}.{hundredths:02}"),
+        }
+    }
+}
+
+/// Saturates instead of overflowing, since every amount up to `u32::MAX / 100` hours can be
+/// deserialized and summing a few of them would otherwise panic or wrap around
+impl Add for CreditHours {
+    type Output = CreditHours;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sum for CreditHours {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(CreditHours::ZERO, Add::add)
+    }
+}
+
+impl Serialize for CreditHours {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_f64(self.as_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for CreditHours {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let hours = f64::deserialize(deserializer)?;
+        if !(0.0..=f64::from(u32::MAX / 100)).contains(&hours) {
+            return Err(serde::de::Error::custom(format!(
+                "credit hours out of range: {hours}"
+            )));
+        }
+
+        Ok(Self((hours * 100.0).round() as u32))
+    }
+}
+
+/// Credit hours earned by completing a course. When the catalog gives a range the student chooses
+/// an amount from `min` to `max`, otherwise both are the same
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(from = "RawCredits")]
+pub struct Credits {
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub min: CreditHours,
+    #[cfg_attr(feature = "openapi", schema(value_type = f64))]
+    #[cfg_attr(feature = "typescript", ts(type = "number"))]
+    pub max: CreditHours,
+    /// Whether `max` is greater than `min`, i.e. the amount of credits is up to the student
+    pub variable: bool,
+}
+
+/// The bounds of serialized [Credits], which go through [Credits::range] so that they are in
+/// order and `variable` agrees with them
+#[derive(Deserialize)]
+struct RawCredits {
+    min: CreditHours,
+    max: CreditHours,
+}
+
+impl From<RawCredits> for Credits {
+    fn from(raw: RawCredits) -> Self {
+        Self::range(raw.min, raw.max)
+    }
+}
+
+impl Credits {
+    pub const fn fixed(hours: CreditHours) -> Self {
+        Self {
+            min: hours,
+            max: hours,
+            variable: false,
+        }
+    }
+
+    /// The bounds are swapped if they are written the other way around
+    pub fn range(min: CreditHours, max: CreditHours) -> Self {
+        let (min, max) = (min.min(max), min.max(max));
+        Self {
+            min,
+            max,
+            variable: min != max,
+        }
+    }
+
+    /// Labels and some courses are listed with 0 credits in the catalog
+    pub fn is_known(&self) -> bool {
+        self.max > CreditHours::ZERO
+    }
+}
+
+impl FromStr for Credits {
+    type Err = CreditsParsingError;
+
+    /// Ex: "3", "0.5", "1.0-3.0", "1.0 - 4.0" or "1 to 3"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let range = s.split_once(['-', '–']).or_else(|| s.split_once(" to "));
+
+        match range {
+            Some((min, max)) => Ok(Self::range(min.parse()?, max.parse()?)),
+            None => Ok(Self::fixed(s.parse()?)),
+        }
+    }
+}
+
+impl Display for Credits {
+    /// Ex: "3" or "1-4"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.variable {
+            write!(f, "{}-{}", self.min, self.max)
+        } else {
+            write!(f, "{}", self.min)
+        }
+    }
+}
+
+impl Add for Credits {
+    type Output = Credits;
+
+    fn add(self, other: Self) -> Self {
+        Self::range(self.min + other.min, self.max + other.max)
+    }
+}
+
+impl Sum for Credits {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Credits::default(), Add::add)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CreditsParsingError {
+    #[error("Invalid amount of credit hours: {0:?}")]
+    InvalidHours(String),
+
+    #[error("Credit hours are more precise than hundredths: {0:?}")]
+    TooPrecise(String),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hours(hundredths: u32) -> CreditHours {
+        CreditHours::from_hundredths(hundredths)
+    }
+
+    #[test]
+    fn can_parse_single_digit_course_credit() {
+        assert_eq!("1".parse(), Ok(Credits::fixed(CreditHours::whole(1))));
+        assert_eq!("14".parse(), Ok(Credits::fixed(CreditHours::whole(14))));
+        assert_eq!("3.0".parse(), Ok(Credits::fixed(CreditHours::whole(3))));
+    }
+
+    #[test]
+    fn can_parse_range_of_course_credits() {
+        let one_to_three = Credits::range(CreditHours::whole(1), CreditHours::whole(3));
+
+        assert_eq!("1.0-3.0".parse(), Ok(one_to_three));
+        assert_eq!("1.0 - 3.0".parse(), Ok(one_to_three));
+        assert_eq!("1.0 -3.0".parse(), Ok(one_to_three));
+        assert_eq!("1 to 3".parse(), Ok(one_to_three));
+        assert!(one_to_three.variable);
+    }
+
+    #[test]
+    fn keeps_fractional_credits() {
+        assert_eq!("0.5".parse(), Ok(Credits::fixed(hours(50))));
+        assert_eq!(".25".parse(), Ok(Credits::fixed(hours(25))));
+        assert_eq!(
+            "1.5-3.0".parse(),
+            Ok(Credits::range(hours(150), hours(300)))
+        );
+        assert_eq!("2.500".parse(), Ok(Credits::fixed(hours(250))));
+
+        assert_eq!(
+            "1.333".parse::<Credits>(),
+            Err(CreditsParsingError::TooPrecise("1.333".to_owned()))
+        );
+        for invalid in ["", "three", "1.0-", "1..0", "-1"] {
+            assert!(invalid.parse::<Credits>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn ranges_of_one_amount_are_not_variable() {
+        let credits: Credits = "3.0 - 3.0".parse().unwrap();
+
+        assert_eq!(credits, Credits::fixed(CreditHours::whole(3)));
+        assert!(!credits.variable);
+    }
+
+    #[test]
+    fn formats_and_adds_credits() {
+        assert_eq!(Credits::fixed(hours(300)).to_string(), "3");
+        assert_eq!(Credits::fixed(hours(50)).to_string(), "0.5");
+        assert_eq!(Credits::fixed(hours(125)).to_string(), "1.25");
+        assert_eq!(Credits::range(hours(100), hours(400)).to_string(), "1-4");
+
+        let total: Credits = [
+            Credits::fixed(hours(300)),
+            Credits::fixed(hours(50)),
+            Credits::range(hours(100), hours(300)),
+        ]
+        .into_iter()
+        .sum();
+        assert_eq!(total, Credits::range(hours(450), hours(650)));
+        assert!(!Credits::default().is_known());
+    }
+
+    #[test]
+    fn saturates_instead_of_overflowing() {
+        let max = CreditHours::from_hundredths(u32::MAX);
+        let largest: Credits = serde_json::from_str(&format!(
+            r#"{{"min":{0},"max":{0},"variable":false}}"#,
+            u32::MAX / 100
+        ))
+        .unwrap();
+
+        assert_eq!(max + hours(1), max);
+        assert_eq!(CreditHours::whole(u32::MAX), max);
+        assert_eq!((largest + largest).max, max);
+        assert_eq!([largest; 3].into_iter().sum::<Credits>().min, max);
+    }
+
+    #[test]
+    fn serializes_hours_as_numbers() {
+        let credits = Credits::range(hours(50), hours(300));
+        let json = serde_json::to_string(&credits).unwrap();
+
+        assert_eq!(json, r#"{"min":0.5,"max":3.0,"variable":true}"#);
+        assert_eq!(serde_json::from_str::<Credits>(&json).unwrap(), credits);
+    }
+
+    #[test]
+    fn deserializes_credits_through_their_range() {
+        let swapped: Credits =
+            serde_json::from_str(r#"{"min":3.0,"max":1.0,"variable":false}"#).unwrap();
+        let contradicting: Credits =
+            serde_json::from_str(r#"{"min":3.0,"max":3.0,"variable":true}"#).unwrap();
+
+        assert_eq!(swapped, Credits::range(hours(100), hours(300)));
+        assert!(swapped.variable);
+        assert_eq!(contradicting, Credits::fixed(hours(300)));
+    }
+}
@@ -0,0 +1,915 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    credits::Credits, equivalence::Equivalences, parsing::guid::Guid, CourseDetails, Program,
+    RequirementModule,
+};
+
+/// Changes between two versions of the catalog (two catalog years or two fetched snapshots)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CatalogDiff {
+    pub programs: ProgramsDiff,
+    pub courses: CoursesDiff,
+}
+
+impl CatalogDiff {
+    /// Courses of the programs are compared with the cross-listings of `new_courses`
+    pub fn new(
+        old_programs: &[Program],
+        new_programs: &[Program],
+        old_courses: &[CourseDetails],
+        new_courses: &[CourseDetails],
+    ) -> Self {
+        Self {
+            programs: diff_programs(old_programs, new_programs, &Equivalences::new(new_courses)),
+            courses: diff_courses(old_courses, new_courses),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty() && self.courses.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProgramsDiff {
+    pub added: Vec<ProgramSummary>,
+    pub removed: Vec<ProgramSummary>,
+    pub changed: Vec<ProgramChange>,
+}
+
+impl ProgramsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProgramSummary {
+    pub guid: Guid,
+    pub title: String,
+}
+
+impl From<&Program> for ProgramSummary {
+    fn from(program: &Program) -> Self {
+        Self {
+            guid: program.guid,
+            title: program.title.clone(),
+        }
+    }
+}
+
+/// A program that exists in both versions of the catalog but is not identical
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ProgramChange {
+    /// GUID of the program in the newer catalog
+    pub guid: Guid,
+    /// Title of the program in the newer catalog
+    pub title: String,
+    pub changes: Vec<ProgramChangeKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", content = "data")]
+pub enum ProgramChangeKind {
+    /// The program was matched by title because its GUID changed between catalogs
+    GuidChanged {
+        from: Guid,
+        to: Guid,
+    },
+    Renamed {
+        from: String,
+        to: String,
+    },
+    UrlChanged {
+        from: String,
+        to: String,
+    },
+    /// The introductory or bottom content of the program changed
+    ContentChanged,
+    ModuleAdded {
+        title: Option<String>,
+    },
+    ModuleRemoved {
+        title: Option<String>,
+    },
+    /// A `RequirementModule` with the same title (and position among the modules sharing that title)
+    /// exists in both catalogs but its requirements differ
+    ModuleChanged {
+        title: Option<String>,
+    },
+    /// A course (Ex: "CSC 215") is now required somewhere in the program. Not reported when it
+    /// replaces a course cross-listed with it
+    CourseAdded {
+        course: String,
+    },
+    /// A course (Ex: "CSC 215") is no longer required anywhere in the program. Not reported when a
+    /// course cross-listed with it replaces it
+    CourseRemoved {
+        course: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CoursesDiff {
+    pub added: Vec<CourseSummary>,
+    pub removed: Vec<CourseSummary>,
+    pub changed: Vec<CourseChange>,
+}
+
+impl CoursesDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CourseSummary {
+    pub guid: Guid,
+    /// Subject code and number. Ex: "CSC 215"
+    pub code: String,
+    pub name: String,
+}
+
+impl From<&CourseDetails> for CourseSummary {
+    fn from(course: &CourseDetails) -> Self {
+        Self {
+            guid: course.guid,
+            code: course.code(),
+            name: course.name.clone(),
+        }
+    }
+}
+
+/// A course that exists in both versions of the catalog but is not identical
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CourseChange {
+    /// GUID of the course in the newer catalog
+    pub guid: Guid,
+    /// Code of the course in the newer catalog
+    pub code: String,
+    pub changes: Vec<CourseChangeKind>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", content = "data")]
+pub enum CourseChangeKind {
+    /// The course was matched by subject code and number because its GUID changed between catalogs
+    GuidChanged {
+        from: Guid,
+        to: Guid,
+    },
+    Renamed {
+        from: String,
+        to: String,
+    },
+    Renumbered {
+        from: String,
+        to: String,
+    },
+    CreditsChanged {
+        from: Credits,
+        to: Credits,
+    },
+    DescriptionChanged,
+    PrerequisitesChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    CorequisitesChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+/// Compares two lists of programs. Programs are first matched by GUID and the ones left over are
+/// then matched by title. Swapping a course for one of its `equivalences` isn't a change.
+pub fn diff_programs(
+    old: &[Program],
+    new: &[Program],
+    equivalences: &Equivalences,
+) -> ProgramsDiff {
+    let pairs = match_items(old, new, |p| p.guid, |p| p.title.clone());
+
+    let mut diff = ProgramsDiff::default();
+
+    for pair in pairs {
+        match pair {
+            Matched::Removed(old) => diff.removed.push(old.into()),
+            Matched::Added(new) => diff.added.push(new.into()),
+            Matched::Both(old, new) if old == new => {}
+            Matched::Both(old, new) => {
+                let changes = program_changes(old, new, equivalences);
+                if !changes.is_empty() {
+                    diff.changed.push(ProgramChange {
+                        guid: new.guid,
+                        title: new.title.clone(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+
+    diff.added.sort_by(|a, b| a.title.cmp(&b.title));
+    diff.removed.sort_by(|a, b| a.title.cmp(&b.title));
+    diff.changed.sort_by(|a, b| a.title.cmp(&b.title));
+
+    diff
+}
+
+/// Compares two lists of courses. Courses are first matched by GUID and the ones left over are
+/// then matched by subject code and number.
+pub fn diff_courses(old: &[CourseDetails], new: &[CourseDetails]) -> CoursesDiff {
+    let pairs = match_items(old, new, |c| c.guid, CourseDetails::code);
+
+    let mut diff = CoursesDiff::default();
+
+    for pair in pairs {
+        match pair {
+            Matched::Removed(old) => diff.removed.push(old.into()),
+            Matched::Added(new) => diff.added.push(new.into()),
+            Matched::Both(old, new) if old == new => {}
+            Matched::Both(old, new) => {
+                let changes = course_changes(old, new);
+                if !changes.is_empty() {
+                    diff.changed.push(CourseChange {
+                        guid: new.guid,
+                        code: new.code(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+
+    diff.added.sort_by(|a, b| a.code.cmp(&b.code));
+    diff.removed.sort_by(|a, b| a.code.cmp(&b.code));
+    diff.changed.sort_by(|a, b| a.code.cmp(&b.code));
+
+    diff
+}
+
+enum Matched<'a, T> {
+    Both(&'a T, &'a T),
+    Removed(&'a T),
+    Added(&'a T),
+}
+
+/// Pairs up items from the old and new lists by `guid_of` and falls back to `fallback_key_of` for
+/// the items that couldn't be paired by GUID
+fn match_items<'a, T, G, K>(
+    old: &'a [T],
+    new: &'a [T],
+    guid_of: G,
+    fallback_key_of: K,
+) -> Vec<Matched<'a, T>>
+where
+    G: Fn(&T) -> Guid,
+    K: Fn(&T) -> String,
+{
+    let new_by_guid: HashMap<Guid, usize> = new
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| (guid_of(item), idx))
+        .collect();
+
+    let mut matched = Vec::with_capacity(old.len().max(new.len()));
+    let mut paired_new = HashSet::new();
+    let mut unpaired_old = Vec::new();
+
+    for old_item in old {
+        match new_by_guid.get(&guid_of(old_item)) {
+            Some(&idx) if paired_new.insert(idx) => {
+                matched.push(Matched::Both(old_item, &new[idx]))
+            }
+            _ => unpaired_old.push(old_item),
+        }
+    }
+
+    let mut unpaired_new_by_key: HashMap<String, usize> = new
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !paired_new.contains(idx))
+        .map(|(idx, item)| (fallback_key_of(item), idx))
+        .collect();
+
+    for old_item in unpaired_old {
+        match unpaired_new_by_key.remove(&fallback_key_of(old_item)) {
+            Some(idx) => {
+                paired_new.insert(idx);
+                matched.push(Matched::Both(old_item, &new[idx]));
+            }
+            None => matched.push(Matched::Removed(old_item)),
+        }
+    }
+
+    matched.extend(
+        new.iter()
+            .enumerate()
+            .filter(|(idx, _)| !paired_new.contains(idx))
+            .map(|(_, item)| Matched::Added(item)),
+    );
+
+    matched
+}
+
+fn program_changes(
+    old: &Program,
+    new: &Program,
+    equivalences: &Equivalences,
+) -> Vec<ProgramChangeKind> {
+    let mut changes = Vec::new();
+
+    if old.guid != new.guid {
+        changes.push(ProgramChangeKind::GuidChanged {
+            from: old.guid,
+            to: new.guid,
+        });
+    }
+
+    if old.title != new.title {
+        changes.push(ProgramChangeKind::Renamed {
+            from: old.title.clone(),
+            to: new.title.clone(),
+        });
+    }
+
+    if old.url != new.url {
+        changes.push(ProgramChangeKind::UrlChanged {
+            from: old.url.clone(),
+            to: new.url.clone(),
+        });
+    }
+
+    if old.content != new.content || old.bottom_content != new.bottom_content {
+        changes.push(ProgramChangeKind::ContentChanged);
+    }
+
+    if old.requirements != new.requirements {
+        changes.extend(requirement_changes(old, new, equivalences));
+    }
+
+    changes
+}
+
+/// Structural changes to the requirements of a program. `RequirementModule`s are compared by
+/// title and courses by their code (Ex: "CSC 215"), or by their equivalences for the ones that
+/// were added or removed
+fn requirement_changes(
+    old: &Program,
+    new: &Program,
+    equivalences: &Equivalences,
+) -> Vec<ProgramChangeKind> {
+    let mut changes = Vec::new();
+
+    let old_modules = keyed_modules(old.requirement_modules());
+    let new_modules = keyed_modules(new.requirement_modules());
+    let new_modules_by_key: HashMap<_, _> = new_modules.iter().copied().collect();
+    let old_keys: HashSet<_> = old_modules.iter().map(|(key, _)| *key).collect();
+
+    for (key @ (title, _), old_module) in &old_modules {
+        match new_modules_by_key.get(key) {
+            Some(new_module) if new_module != old_module => {
+                changes.push(ProgramChangeKind::ModuleChanged {
+                    title: title.map(str::to_owned),
+                })
+            }
+            Some(_) => {}
+            None => changes.push(ProgramChangeKind::ModuleRemoved {
+                title: title.map(str::to_owned),
+            }),
+        }
+    }
+
+    for (key @ (title, _), _) in &new_modules {
+        if !old_keys.contains(key) {
+            changes.push(ProgramChangeKind::ModuleAdded {
+                title: title.map(str::to_owned),
+            });
+        }
+    }
+
+    let old_courses: BTreeMap<String, Guid> = old
+        .courses()
+        .into_iter()
+        .map(|c| (c.code(), c.guid))
+        .collect();
+    let new_courses: BTreeMap<String, Guid> = new
+        .courses()
+        .into_iter()
+        .map(|c| (c.code(), c.guid))
+        .collect();
+
+    changes.extend(
+        new_courses
+            .iter()
+            .filter(|(code, guid)| {
+                !old_courses.contains_key(*code)
+                    && !equivalences.contains_equivalent(old_courses.values(), guid)
+            })
+            .map(|(code, _)| ProgramChangeKind::CourseAdded {
+                course: code.clone(),
+            }),
+    );
+    changes.extend(
+        old_courses
+            .iter()
+            .filter(|(code, guid)| {
+                !new_courses.contains_key(*code)
+                    && !equivalences.contains_equivalent(new_courses.values(), guid)
+            })
+            .map(|(code, _)| ProgramChangeKind::CourseRemoved {
+                course: code.clone(),
+            }),
+    );
+
+    changes
+}
+
+/// Keys every module by its title along with how many modules before it share that title, so that
+/// untitled modules (or ones with the same title) are matched up in order instead of all being
+/// compared against the first of them
+fn keyed_modules(
+    modules: Vec<&RequirementModule>,
+) -> Vec<((Option<&str>, usize), &RequirementModule)> {
+    let mut seen: HashMap<Option<&str>, usize> = HashMap::new();
+
+    modules
+        .into_iter()
+        .map(|module| {
+            let occurrence = seen.entry(module.title()).or_default();
+            let key = (module.title(), *occurrence);
+            *occurrence += 1;
+            (key, module)
+        })
+        .collect()
+}
+
+fn course_changes(old: &CourseDetails, new: &CourseDetails) -> Vec<CourseChangeKind> {
+    let mut changes = Vec::new();
+
+    if old.guid != new.guid {
+        changes.push(CourseChangeKind::GuidChanged {
+            from: old.guid,
+            to: new.guid,
+        });
+    }
+
+    if old.name != new.name {
+        changes.push(CourseChangeKind::Renamed {
+            from: old.name.clone(),
+            to: new.name.clone(),
+        });
+    }
+
+    if old.code() != new.code() {
+        changes.push(CourseChangeKind::Renumbered {
+            from: old.code(),
+            to: new.code(),
+        });
+    }
+
+    if old.credits != new.credits {
+        changes.push(CourseChangeKind::CreditsChanged {
+            from: old.credits,
+            to: new.credits,
+        });
+    }
+
+    if old.description != new.description {
+        changes.push(CourseChangeKind::DescriptionChanged);
+    }
+
+    if old.prerequisite_narrative != new.prerequisite_narrative
+        || old.prerequisite != new.prerequisite
+    {
+        changes.push(CourseChangeKind::PrerequisitesChanged {
+            from: old.prerequisite_narrative.clone(),
+            to: new.prerequisite_narrative.clone(),
+        });
+    }
+
+    if old.corequisite_narrative != new.corequisite_narrative || old.corequisite != new.corequisite
+    {
+        changes.push(CourseChangeKind::CorequisitesChanged {
+            from: old.corequisite_narrative.clone(),
+            to: new.corequisite_narrative.clone(),
+        });
+    }
+
+    changes
+}
+
+fn format_optional(s: &Option<String>) -> &str {
+    s.as_deref().unwrap_or("<none>")
+}
+
+/// Human readable report used for command line output
+impl Display for CatalogDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.programs)?;
+        writeln!(f)?;
+        write!(f, "{}", self.courses)
+    }
+}
+
+impl Display for ProgramsDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Programs: {} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+
+        for program in &self.added {
+            writeln!(f, "+ {} ({})", program.title, program.guid)?;
+        }
+
+        for program in &self.removed {
+            writeln!(f, "- {} ({})", program.title, program.guid)?;
+        }
+
+        for program in &self.changed {
+            writeln!(f, "~ {} ({})", program.title, program.guid)?;
+            for change in &program.changes {
+                writeln!(f, "    {change}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ProgramChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramChangeKind::GuidChanged { from, to } => {
+                write!(f, "GUID changed from {from} to {to}")
+            }
+            ProgramChangeKind::Renamed { from, to } => write!(f, "renamed from {from:?} to {to:?}"),
+            ProgramChangeKind::UrlChanged { from, to } => {
+                write!(f, "url changed from {from} to {to}")
+            }
+            ProgramChangeKind::ContentChanged => write!(f, "content changed"),
+            ProgramChangeKind::ModuleAdded { title } => {
+                write!(f, "module added: {}", format_optional(title))
+            }
+            ProgramChangeKind::ModuleRemoved { title } => {
+                write!(f, "module removed: {}", format_optional(title))
+            }
+            ProgramChangeKind::ModuleChanged { title } => {
+                write!(f, "module changed: {}", format_optional(title))
+            }
+            ProgramChangeKind::CourseAdded { course } => write!(f, "course added: {course}"),
+            ProgramChangeKind::CourseRemoved { course } => write!(f, "course removed: {course}"),
+        }
+    }
+}
+
+impl Display for CoursesDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Courses: {} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+
+        for course in &self.added {
+            writeln!(f, "+ {} {}", course.code, course.name)?;
+        }
+
+        for course in &self.removed {
+            writeln!(f, "- {} {}", course.code, course.name)?;
+        }
+
+        for course in &self.changed {
+            writeln!(f, "~ {}", course.code)?;
+            for change in &course.changes {
+                writeln!(f, "    {change}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for CourseChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CourseChangeKind::GuidChanged { from, to } => {
+                write!(f, "GUID changed from {from} to {to}")
+            }
+            CourseChangeKind::Renamed { from, to } => write!(f, "renamed from {from:?} to {to:?}"),
+            CourseChangeKind::Renumbered { from, to } => {
+                write!(f, "renumbered from {from} to {to}")
+            }
+            CourseChangeKind::CreditsChanged { from, to } => {
+                write!(f, "credits changed from {from} to {to}")
+            }
+            CourseChangeKind::DescriptionChanged => write!(f, "description changed"),
+            CourseChangeKind::PrerequisitesChanged { from, to } => write!(
+                f,
+                "prerequisites changed from {:?} to {:?}",
+                format_optional(from),
+                format_optional(to)
+            ),
+            CourseChangeKind::CorequisitesChanged { from, to } => write!(
+                f,
+                "corequisites changed from {:?} to {:?}",
+                format_optional(from),
+                format_optional(to)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::credits::CreditHours;
+
+    use super::*;
+
+    fn cs_major() -> Program {
+        let program_json = std::fs::read_to_string("../data/cs_major.json").unwrap();
+        serde_json::from_str(&program_json).unwrap()
+    }
+
+    fn zoology_major() -> Program {
+        let program_json = std::fs::read_to_string("../data/zoology_major.json").unwrap();
+        serde_json::from_str(&program_json).unwrap()
+    }
+
+    fn course(guid: &str, subject_code: &str, number: &str, name: &str) -> CourseDetails {
+        CourseDetails {
+            name: name.to_owned(),
+            ..CourseDetails::stub(
+                Guid::try_from(guid).unwrap(),
+                &format!("{subject_code} {number}"),
+            )
+        }
+    }
+
+    #[test]
+    fn identical_catalogs_have_no_diff() {
+        let programs = vec![cs_major(), zoology_major()];
+        let courses = vec![course(
+            "C7AD875E-1344-4D9B-A883-32E748890908",
+            "CSC",
+            "215",
+            "Data Structures",
+        )];
+
+        let diff = CatalogDiff::new(&programs, &programs, &courses, &courses);
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn detects_added_and_removed_programs() {
+        let diff = diff_programs(&[cs_major()], &[zoology_major()], &Equivalences::default());
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].guid, zoology_major().guid);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].guid, cs_major().guid);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn matches_programs_by_title_when_guid_changed() {
+        let old = cs_major();
+        let mut new = cs_major();
+        new.guid = Guid::try_from("08DD69D3-9F67-4A81-A5AA-5738B6A79D2B").unwrap();
+
+        let diff = diff_programs(
+            std::slice::from_ref(&old),
+            std::slice::from_ref(&new),
+            &Equivalences::default(),
+        );
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![ProgramChangeKind::GuidChanged {
+                from: old.guid,
+                to: new.guid
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_structural_requirement_changes() {
+        let old = cs_major();
+        let mut new = cs_major();
+
+        let Some(crate::Requirements::Single(crate::RequirementModule::BasicRequirements {
+            requirements,
+            ..
+        })) = new.requirements.as_mut()
+        else {
+            panic!("Expected cs major to have a single `BasicRequirements` module");
+        };
+        let removed_requirement = requirements.pop().unwrap();
+
+        let diff = diff_programs(
+            std::slice::from_ref(&old),
+            std::slice::from_ref(&new),
+            &Equivalences::default(),
+        );
+        let changes = &diff.changed[0].changes;
+
+        assert!(changes.contains(&ProgramChangeKind::ModuleChanged {
+            title: Some("Degree Requirements".to_owned())
+        }));
+
+        let remaining_courses: BTreeSet<String> =
+            new.courses().into_iter().map(|c| c.code()).collect();
+        let expected_removed: BTreeSet<String> = removed_requirement
+            .course_entries()
+            .map(|entries| entries.courses())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| c.code())
+            .filter(|code| !remaining_courses.contains(code))
+            .collect();
+
+        for course in expected_removed {
+            assert!(changes.contains(&ProgramChangeKind::CourseRemoved { course }));
+        }
+    }
+
+    #[test]
+    fn ignores_courses_swapped_for_cross_listed_ones() {
+        let old = cs_major();
+        let mut new = cs_major();
+
+        let Some(crate::Requirements::Single(crate::RequirementModule::BasicRequirements {
+            requirements,
+            ..
+        })) = new.requirements.as_mut()
+        else {
+            panic!("Expected cs major to have a single `BasicRequirements` module");
+        };
+        let Some(crate::Requirement::Courses { courses, .. }) = requirements.first_mut() else {
+            panic!("Expected the first requirement of the cs major to list courses");
+        };
+        let Some(crate::CourseEntry::Course(swapped)) = courses.first_mut() else {
+            panic!("Expected the first entry of the requirement to be a course");
+        };
+        let replaced = course(
+            &swapped.guid.to_string(),
+            &swapped.subject_code,
+            &swapped.number,
+            "",
+        );
+        swapped.guid = Guid::try_from("00000000-0000-0000-0000-000000000001").unwrap();
+        swapped.subject_code = "MAT".to_owned();
+        swapped.number = "999".to_owned();
+        assert_eq!(
+            old.courses()
+                .iter()
+                .filter(|c| c.code() == replaced.code())
+                .count(),
+            1
+        );
+
+        let cross_listed = CourseDetails {
+            cross_listed: vec![replaced.code()],
+            ..course("00000000-0000-0000-0000-000000000001", "MAT", "999", "")
+        };
+        let equivalences = Equivalences::new([&replaced, &cross_listed]);
+        let diff = diff_programs(
+            std::slice::from_ref(&old),
+            std::slice::from_ref(&new),
+            &equivalences,
+        );
+
+        assert!(!diff.changed[0].changes.iter().any(|change| matches!(
+            change,
+            ProgramChangeKind::CourseAdded { .. } | ProgramChangeKind::CourseRemoved { .. }
+        )));
+
+        let diff = diff_programs(
+            std::slice::from_ref(&old),
+            std::slice::from_ref(&new),
+            &Equivalences::default(),
+        );
+        assert!(diff.changed[0]
+            .changes
+            .contains(&ProgramChangeKind::CourseAdded {
+                course: "MAT 999".to_owned()
+            }));
+        assert!(diff.changed[0]
+            .changes
+            .contains(&ProgramChangeKind::CourseRemoved {
+                course: replaced.code()
+            }));
+    }
+
+    #[test]
+    fn matches_untitled_modules_in_order() {
+        let untitled =
+            |n: u8| crate::RequirementModule::Unimplemented(serde_json::json!({ "n": n }));
+        let mut old = cs_major();
+        old.requirements = Some(crate::Requirements::Many(vec![untitled(1), untitled(2)]));
+        let mut new = old.clone();
+        new.requirements = Some(crate::Requirements::Many(vec![
+            untitled(1),
+            untitled(2),
+            untitled(3),
+        ]));
+
+        let diff = diff_programs(
+            std::slice::from_ref(&old),
+            std::slice::from_ref(&new),
+            &Equivalences::default(),
+        );
+
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![ProgramChangeKind::ModuleAdded { title: None }]
+        );
+    }
+
+    #[test]
+    fn detects_course_changes() {
+        let old = course(
+            "C7AD875E-1344-4D9B-A883-32E748890908",
+            "CSC",
+            "215",
+            "Data Structures",
+        );
+        let mut renamed = old.clone();
+        renamed.name = "Data Structures and Algorithms".to_owned();
+        renamed.credits = Credits::range(CreditHours::whole(3), CreditHours::whole(4));
+
+        let diff = diff_courses(std::slice::from_ref(&old), &[renamed]);
+
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![
+                CourseChangeKind::Renamed {
+                    from: "Data Structures".to_owned(),
+                    to: "Data Structures and Algorithms".to_owned()
+                },
+                CourseChangeKind::CreditsChanged {
+                    from: Credits::fixed(CreditHours::whole(3)),
+                    to: Credits::range(CreditHours::whole(3), CreditHours::whole(4))
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_courses_by_code_when_guid_changed() {
+        let old = course(
+            "C7AD875E-1344-4D9B-A883-32E748890908",
+            "CSC",
+            "215",
+            "Data Structures",
+        );
+        let new = course(
+            "08DD69D3-9F67-4A81-A5AA-5738B6A79D2B",
+            "CSC",
+            "215",
+            "Data Structures",
+        );
+        let added = course(
+            "5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5",
+            "CSC",
+            "300",
+            "Operating Systems",
+        );
+
+        let diff = diff_courses(&[old], &[new, added]);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].code, "CSC 300");
+        assert!(diff.removed.is_empty());
+        assert!(matches!(
+            diff.changed[0].changes[..],
+            [CourseChangeKind::GuidChanged { .. }]
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Groups of courses that count as the same course because they are cross-listed
+use std::collections::HashMap;
+
+use crate::{parsing::guid::Guid, CourseDetails};
+
+/// Courses grouped by their cross-listings. A listing only has to be written on one side, and
+/// listings chain: if PSY 318 is listed as SOC 318 and SOC 318 as ANT 318, all three are equivalent
+#[derive(Debug, Clone, Default)]
+pub struct Equivalences {
+    /// Index in `groups` of every course cross-listed with another one
+    group_of: HashMap<Guid, usize>,
+    /// Sorted by course code
+    groups: Vec<Vec<Guid>>,
+}
+
+impl Equivalences {
+    pub fn new<'a>(courses: impl IntoIterator<Item = &'a CourseDetails>) -> Self {
+        let courses: Vec<&CourseDetails> = courses.into_iter().collect();
+        let by_code: HashMap<String, usize> = courses
+            .iter()
+            .enumerate()
+            .map(|(i, course)| (course.code(), i))
+            .collect();
+
+        let mut sets = DisjointSets::new(courses.len());
+        for (i, course) in courses.iter().enumerate() {
+            for code in &course.cross_listed {
+                if let Some(&j) = by_code.get(code) {
+                    sets.union(i, j);
+                }
+            }
+        }
+
+        let mut members: HashMap<usize, Vec<&CourseDetails>> = HashMap::new();
+        for (i, course) in courses.iter().enumerate() {
+            members.entry(sets.find(i)).or_default().push(course);
+        }
+
+        let mut groups: Vec<Vec<&CourseDetails>> = members
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .collect();
+        for group in &mut groups {
+            group.sort_by_key(|course| course.code());
+        }
+        groups.sort_by_key(|group| group[0].code());
+
+        let mut equivalences = Self::default();
+        for (i, group) in groups.into_iter().enumerate() {
+            for course in &group {
+                equivalences.group_of.insert(course.guid, i);
+            }
+            equivalences
+                .groups
+                .push(group.into_iter().map(|course| course.guid).collect());
+        }
+
+        equivalences
+    }
+
+    /// The courses equivalent to the course, without the course itself
+    pub fn equivalents(&self, guid: &Guid) -> Vec<Guid> {
+        self.group_of
+            .get(guid)
+            .map(|&i| {
+                self.groups[i]
+                    .iter()
+                    .filter(|other| *other != guid)
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether both are the same course or are cross-listed
+    pub fn are_equivalent(&self, a: &Guid, b: &Guid) -> bool {
+        a == b
+            || self
+                .group_of
+                .get(a)
+                .is_some_and(|group| self.group_of.get(b) == Some(group))
+    }
+
+    /// Whether the course or one equivalent to it is in `guids`
+    pub fn contains_equivalent<'a>(
+        &self,
+        guids: impl IntoIterator<Item = &'a Guid>,
+        guid: &Guid,
+    ) -> bool {
+        guids
+            .into_iter()
+            .any(|other| self.are_equivalent(other, guid))
+    }
+
+    /// Every group of two or more equivalent courses
+    pub fn groups(&self) -> impl Iterator<Item = &[Guid]> {
+        self.groups.iter().map(Vec::as_slice)
+    }
+}
+
+/// Union-find over the indices `0..len`
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        let parent = self.parents[i];
+        if parent == i {
+            return i;
+        }
+
+        let root = self.find(parent);
+        self.parents[i] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a] = b;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn course(guid: &str, code: &str, cross_listed: &[&str]) -> CourseDetails {
+        CourseDetails {
+            cross_listed: cross_listed.iter().map(|code| code.to_string()).collect(),
+            ..CourseDetails::stub(Guid::try_from(guid).unwrap(), code)
+        }
+    }
+
+    const PSY: &str = "00000000-0000-0000-0000-000000000001";
+    const SOC: &str = "00000000-0000-0000-0000-000000000002";
+    const ANT: &str = "00000000-0000-0000-0000-000000000003";
+    const MAT: &str = "00000000-0000-0000-0000-000000000004";
+
+    fn guid(s: &str) -> Guid {
+        Guid::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn groups_chained_one_sided_listings() {
+        let courses = [
+            course(PSY, "PSY 318", &["SOC 318"]),
+            course(SOC, "SOC 318", &[]),
+            course(ANT, "ANT 318", &["SOC 318", "HIS 999"]),
+            course(MAT, "MAT 111", &[]),
+        ];
+        let equivalences = Equivalences::new(&courses);
+
+        assert_eq!(
+            equivalences.groups().collect::<Vec<_>>(),
+            vec![[guid(ANT), guid(PSY), guid(SOC)].as_slice()]
+        );
+        assert_eq!(
+            equivalences.equivalents(&guid(SOC)),
+            vec![guid(ANT), guid(PSY)]
+        );
+        assert!(equivalences.equivalents(&guid(MAT)).is_empty());
+    }
+
+    #[test]
+    fn matches_equivalent_courses() {
+        let courses = [
+            course(PSY, "PSY 318", &["SOC 318"]),
+            course(SOC, "SOC 318", &["PSY 318"]),
+            course(MAT, "MAT 111", &[]),
+        ];
+        let equivalences = Equivalences::new(&courses);
+
+        assert!(equivalences.are_equivalent(&guid(PSY), &guid(SOC)));
+        assert!(equivalences.are_equivalent(&guid(MAT), &guid(MAT)));
+        assert!(!equivalences.are_equivalent(&guid(PSY), &guid(MAT)));
+        assert!(equivalences.contains_equivalent(&[guid(MAT), guid(SOC)], &guid(PSY)));
+        assert!(!equivalences.contains_equivalent(&[guid(SOC)], &guid(MAT)));
+    }
+}
@@ -0,0 +1,456 @@
+//! Courses and programs flattened into rows for spreadsheets, written as CSV or XLSX
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+    credits::CreditHours, equivalence::Equivalences, parsing::guid::Guid, CourseDetails,
+    CourseEntries, CourseEntry, Program,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// Excel workbook with a single sheet
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+impl TryFrom<&str> for ExportFormat {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "xlsx" => Ok(ExportFormat::Xlsx),
+            _ => Err(Error::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Failed to write CSV: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Failed to write spreadsheet: {0}")]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+
+    #[error("Unknown export format: {0:?}")]
+    UnknownFormat(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A value of a row, kept as a number when it is one so that spreadsheets can add them up
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl From<&str> for Cell {
+    fn from(s: &str) -> Self {
+        Cell::Text(s.to_owned())
+    }
+}
+
+impl From<Option<&str>> for Cell {
+    fn from(s: Option<&str>) -> Self {
+        s.map_or(Cell::Empty, Cell::from)
+    }
+}
+
+impl From<Option<CreditHours>> for Cell {
+    fn from(hours: Option<CreditHours>) -> Self {
+        hours.map_or(Cell::Empty, |hours| Cell::Number(hours.as_f64()))
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cell::Text(s) => write!(f, "{s}"),
+            Cell::Number(n) => write!(f, "{n}"),
+            Cell::Empty => Ok(()),
+        }
+    }
+}
+
+/// Something exported as a row under the same headers as the other rows of its kind
+pub trait Row {
+    const SHEET_NAME: &'static str;
+    const HEADERS: &'static [&'static str];
+
+    /// One cell for each of the `HEADERS`
+    fn cells(&self) -> Vec<Cell>;
+}
+
+/// A course with its prerequisites and corequisites as they are written in the catalog, and the
+/// codes of the courses equivalent to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CourseRow<'a> {
+    pub course: &'a CourseDetails,
+    /// Sorted codes of the courses cross-listed with it, whichever side the listing is written on
+    /// and including the ones only reached through other listings. Ex: ["ANT 318", "SOC 318"]
+    pub equivalents: Vec<String>,
+}
+
+impl Row for CourseRow<'_> {
+    const SHEET_NAME: &'static str = "Courses";
+    const HEADERS: &'static [&'static str] = &[
+        "subject",
+        "subject_name",
+        "number",
+        "name",
+        "credits",
+        "credits_min",
+        "credits_max",
+        "prerequisites",
+        "corequisites",
+        "cross_listed",
+        "guid",
+        "url",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        let course = self.course;
+
+        vec![
+            course.subject_code.as_str().into(),
+            course.subject_name.as_deref().into(),
+            course.number.as_str().into(),
+            course.name.as_str().into(),
+            course.credits.to_string().as_str().into(),
+            Some(course.credits.min).into(),
+            course.credits.variable.then_some(course.credits.max).into(),
+            course.prerequisite_narrative.as_deref().into(),
+            course.corequisite_narrative.as_deref().into(),
+            self.equivalents.join(", ").as_str().into(),
+            course.guid.to_string().as_str().into(),
+            course.url.as_str().into(),
+        ]
+    }
+}
+
+/// Courses sorted by subject and number, along with their [Equivalences] among `courses`
+pub fn course_rows(courses: &[CourseDetails]) -> Vec<CourseRow<'_>> {
+    let equivalences = Equivalences::new(courses);
+    let codes: HashMap<Guid, String> = courses
+        .iter()
+        .map(|course| (course.guid, course.code()))
+        .collect();
+
+    let mut rows: Vec<CourseRow> = courses
+        .iter()
+        .map(|course| CourseRow {
+            course,
+            equivalents: equivalences
+                .equivalents(&course.guid)
+                .iter()
+                .filter_map(|guid| codes.get(guid).cloned())
+                .collect(),
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        (&a.course.subject_code, &a.course.number, &a.course.name).cmp(&(
+            &b.course.subject_code,
+            &b.course.number,
+            &b.course.name,
+        ))
+    });
+
+    rows
+}
+
+/// A course or label of a program along with the module, requirement and operator group it's
+/// listed in. Modules and requirements without courses get a row of their own
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramRow {
+    pub program: String,
+    pub module: Option<String>,
+    pub requirement: Option<String>,
+    /// Id of the innermost operator group the course is in, the same as its node in the
+    /// [ProgramGraph](crate::graph::ProgramGraph). Ex: "or-0-1-2"
+    pub group: Option<String>,
+    /// "and" or "or"
+    pub group_kind: Option<&'static str>,
+    /// Ex: "CSC 215"
+    pub course: Option<String>,
+    /// Name of the course or label
+    pub name: Option<String>,
+    pub credits: Option<String>,
+    pub guid: Option<String>,
+}
+
+impl Row for ProgramRow {
+    const SHEET_NAME: &'static str = "Requirements";
+    const HEADERS: &'static [&'static str] = &[
+        "program",
+        "module",
+        "requirement",
+        "group",
+        "group_kind",
+        "course",
+        "name",
+        "credits",
+        "guid",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            self.program.as_str().into(),
+            self.module.as_deref().into(),
+            self.requirement.as_deref().into(),
+            self.group.as_deref().into(),
+            self.group_kind.into(),
+            self.course.as_deref().into(),
+            self.name.as_deref().into(),
+            self.credits.as_deref().into(),
+            self.guid.as_deref().into(),
+        ]
+    }
+}
+
+/// Every course and label of the program in the order they appear in the catalog
+pub fn program_rows(program: &Program) -> Vec<ProgramRow> {
+    let mut rows = Vec::new();
+
+    for (m, module) in program.requirement_modules().into_iter().enumerate() {
+        let module_row = ProgramRow {
+            program: program.title.clone(),
+            module: module.title().map(str::to_owned),
+            ..Default::default()
+        };
+
+        let requirements = module.requirements();
+        if requirements.is_empty() {
+            rows.push(module_row.clone());
+        }
+
+        for (r, requirement) in requirements.into_iter().enumerate() {
+            let requirement_row = ProgramRow {
+                requirement: requirement.title().map(str::to_owned),
+                ..module_row.clone()
+            };
+
+            match requirement.course_entries() {
+                Some(entries) if !entries.is_empty() => {
+                    entry_rows(&format!("{m}-{r}"), entries, &requirement_row, &mut rows)
+                }
+                _ => rows.push(requirement_row),
+            }
+        }
+    }
+
+    rows
+}
+
+fn entry_rows(
+    path: &str,
+    entries: &CourseEntries,
+    parent: &ProgramRow,
+    rows: &mut Vec<ProgramRow>,
+) {
+    for (i, entry) in entries.iter().enumerate() {
+        let path = format!("{path}-{i}");
+
+        match entry {
+            CourseEntry::And(entries) | CourseEntry::Or(entries) => {
+                let kind = match entry {
+                    CourseEntry::And(_) => "and",
+                    _ => "or",
+                };
+                let group_row = ProgramRow {
+                    group: Some(format!("{kind}-{path}")),
+                    group_kind: Some(kind),
+                    ..parent.clone()
+                };
+
+                entry_rows(&path, entries, &group_row, rows);
+            }
+            CourseEntry::Course(course) => rows.push(ProgramRow {
+                course: Some(course.code()),
+                name: course.name.clone(),
+                credits: Some(course.credits.to_string()),
+                guid: Some(course.guid.to_string()),
+                ..parent.clone()
+            }),
+            CourseEntry::Label(label) => rows.push(ProgramRow {
+                name: Some(label.name.clone()),
+                credits: Some(label.credits.to_string()),
+                guid: Some(label.guid.to_string()),
+                ..parent.clone()
+            }),
+        }
+    }
+}
+
+/// Writes the headers followed by the rows
+pub fn write<R: Row>(format: ExportFormat, rows: &[R]) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Csv => to_csv(rows),
+        ExportFormat::Xlsx => to_xlsx(rows),
+    }
+}
+
+pub fn to_csv<R: Row>(rows: &[R]) -> Result<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    writer.write_record(R::HEADERS)?;
+    for row in rows {
+        writer.write_record(row.cells().iter().map(Cell::to_string))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|err| Error::Csv(err.into_error().into()))
+}
+
+/// A workbook with a single sheet named after the kind of rows, with the headers frozen
+pub fn to_xlsx<R: Row>(rows: &[R]) -> Result<Vec<u8>> {
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let bold = rust_xlsxwriter::Format::new().set_bold();
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(R::SHEET_NAME)?;
+    worksheet.set_freeze_panes(1, 0)?;
+
+    for (col, header) in R::HEADERS.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &bold)?;
+    }
+
+    for (row, cells) in rows.iter().map(Row::cells).enumerate() {
+        let row = row as u32 + 1;
+        for (col, cell) in cells.into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Text(s) => worksheet.write_string(row, col, s)?,
+                Cell::Number(n) => worksheet.write_number(row, col, n)?,
+                Cell::Empty => continue,
+            };
+        }
+    }
+    worksheet.autofit();
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::test::{cs_courses, cs_major};
+
+    use super::*;
+
+    #[test]
+    fn exports_courses_as_csv() {
+        let program = cs_major();
+        let courses: Vec<CourseDetails> = cs_courses(&program).into_values().collect();
+        let csv = String::from_utf8(to_csv(&course_rows(&courses)).unwrap()).unwrap();
+        let mut lines = csv.lines();
+
+        assert_eq!(
+            lines.next().unwrap(),
+            "subject,subject_name,number,name,credits,credits_min,credits_max,prerequisites,\
+             corequisites,cross_listed,guid,url"
+        );
+        // Sorted by subject, so CSC comes first
+        assert!(lines.next().unwrap().starts_with("CSC,"));
+        assert_eq!(csv.lines().count(), courses.len() + 1);
+    }
+
+    #[test]
+    fn exports_equivalents_on_both_sides_of_a_listing() {
+        let program = cs_major();
+        let mut courses: Vec<CourseDetails> = cs_courses(&program).into_values().collect();
+        let listed = courses[1].code();
+        courses[0].cross_listed = vec![listed.clone()];
+        let listing = courses[0].code();
+
+        let rows = course_rows(&courses);
+        let equivalents = |code: &str| {
+            rows.iter()
+                .find(|row| row.course.code() == code)
+                .unwrap()
+                .equivalents
+                .clone()
+        };
+
+        assert_eq!(equivalents(&listing), vec![listed.clone()]);
+        assert_eq!(equivalents(&listed), vec![listing]);
+    }
+
+    #[test]
+    fn flattens_programs_into_rows() {
+        let program = cs_major();
+        let rows = program_rows(&program);
+
+        assert_eq!(
+            rows.iter().filter(|row| row.course.is_some()).count(),
+            program.courses().len()
+        );
+        assert!(rows
+            .iter()
+            .all(|row| row.program == "Major in Computer Science—42 hours"));
+        assert_eq!(rows[0].module.as_deref(), Some("Degree Requirements"));
+        assert_eq!(rows[0].group, None);
+
+        let grouped = rows
+            .iter()
+            .find(|row| row.group.is_some())
+            .expect("the CS major has operator groups");
+        let graph = crate::graph::ProgramGraph::new(&program, &Default::default());
+        assert!(graph.node(grouped.group.as_deref().unwrap()).is_some());
+    }
+
+    #[test]
+    fn exports_xlsx_workbook() {
+        let program = cs_major();
+        let xlsx = to_xlsx(&program_rows(&program)).unwrap();
+
+        // XLSX files are zip archives
+        assert!(xlsx.starts_with(b"PK"));
+    }
+
+    #[test]
+    fn escapes_csv_fields() {
+        let row = ProgramRow {
+            program: "Major, with \"quotes\"".to_owned(),
+            ..Default::default()
+        };
+        let csv = String::from_utf8(to_csv(&[row]).unwrap()).unwrap();
+
+        assert_eq!(
+            csv.lines().nth(1).unwrap(),
+            "\"Major, with \"\"quotes\"\"\",,,,,,,,"
+        );
+    }
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!(ExportFormat::try_from("xlsx").unwrap(), ExportFormat::Xlsx);
+        assert!(ExportFormat::try_from("pdf").is_err());
+    }
+}
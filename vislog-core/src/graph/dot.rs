@@ -0,0 +1,152 @@
+//! [Graphviz DOT](https://graphviz.org/doc/info/lang.html) export of a [ProgramGraph]
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::{EdgeKind, Node, NodeKind, ProgramGraph};
+
+/// One cluster per module with its courses ranked by level, and the prerequisites drawn as dashed
+/// edges between them
+pub fn to_dot(graph: &ProgramGraph) -> String {
+    let mut dot = String::new();
+
+    writeln!(dot, "digraph {} {{", quote(&graph.guid.to_string())).unwrap();
+    writeln!(
+        dot,
+        "    graph [label={}, labelloc=t, rankdir=TB, compound=true, fontname=\"Helvetica\"];",
+        quote(&graph.title)
+    )
+    .unwrap();
+    writeln!(dot, "    node [fontname=\"Helvetica\", fontsize=10];").unwrap();
+    writeln!(dot, "    edge [fontname=\"Helvetica\", fontsize=9];").unwrap();
+
+    for module in graph.modules() {
+        writeln!(dot).unwrap();
+        writeln!(
+            dot,
+            "    subgraph {} {{",
+            quote(&format!("cluster_{}", module.id))
+        )
+        .unwrap();
+        writeln!(dot, "        label={};", quote(&module.label)).unwrap();
+        writeln!(dot, "        style=rounded;").unwrap();
+
+        let nodes = graph.module_nodes(&module.id);
+        for node in &nodes {
+            writeln!(dot, "        {}", node_statement(node)).unwrap();
+        }
+
+        // Keep courses of the same level on the same row
+        let mut levels: BTreeMap<u16, Vec<&str>> = BTreeMap::new();
+        for node in nodes.iter().filter(|node| node.kind == NodeKind::Course) {
+            if let Some(level) = node.level {
+                levels.entry(level).or_default().push(&node.id);
+            }
+        }
+        for ids in levels.values().filter(|ids| ids.len() > 1) {
+            let ids: Vec<String> = ids.iter().map(|id| quote(id)).collect();
+            writeln!(dot, "        {{ rank=same; {}; }}", ids.join("; ")).unwrap();
+        }
+
+        writeln!(dot, "    }}").unwrap();
+    }
+
+    let loose_nodes: Vec<&Node> = graph.loose_nodes().collect();
+    if !loose_nodes.is_empty() {
+        writeln!(dot).unwrap();
+    }
+    for node in loose_nodes {
+        writeln!(dot, "    {}", node_statement(node)).unwrap();
+    }
+
+    writeln!(dot).unwrap();
+    for edge in graph.drawn_edges() {
+        let attributes = match edge.kind {
+            EdgeKind::Contains => "",
+            EdgeKind::Prerequisite => {
+                " [style=dashed, color=\"#c0392b\", label=\"prerequisite\", constraint=false]"
+            }
+            EdgeKind::Corequisite => {
+                " [style=dotted, color=\"#2471a3\", label=\"corequisite\", constraint=false]"
+            }
+        };
+
+        writeln!(
+            dot,
+            "    {} -> {}{attributes};",
+            quote(&edge.from),
+            quote(&edge.to)
+        )
+        .unwrap();
+    }
+
+    dot.push_str("}\n");
+
+    dot
+}
+
+fn node_statement(node: &Node) -> String {
+    let label = match &node.name {
+        Some(name) if !name.is_empty() => format!("{}\n{}", node.label, name),
+        _ => node.label.clone(),
+    };
+
+    let attributes = match node.kind {
+        NodeKind::Module | NodeKind::Requirement => "shape=box, style=rounded",
+        NodeKind::AndGroup | NodeKind::OrGroup => "shape=diamond, fontsize=8",
+        NodeKind::Course if node.module.is_none() => {
+            "shape=box, style=\"filled,dashed\", fillcolor=\"#f4f6f7\""
+        }
+        NodeKind::Course => "shape=box, style=filled, fillcolor=\"#eaf2f8\"",
+        NodeKind::Label => "shape=note",
+    };
+
+    format!(
+        "{} [label={}, {attributes}];",
+        quote(&node.id),
+        quote(&label)
+    )
+}
+
+/// A DOT string literal
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::{course_id, test::cs_courses, test::cs_major};
+
+    use super::*;
+
+    #[test]
+    fn exports_clusters_and_requisites() {
+        let program = cs_major();
+        let graph = ProgramGraph::new(&program, &cs_courses(&program));
+        let dot = to_dot(&graph);
+
+        assert!(dot.starts_with("digraph \"5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5\" {"));
+        assert!(dot.contains("subgraph \"cluster_module-0\" {"));
+        assert!(dot.contains("label=\"Degree Requirements\";"));
+        assert!(dot.contains("{ rank=same; "));
+        assert!(dot.contains("label=\"prerequisite\""));
+        assert!(dot.contains(&format!(
+            "\"requirement-0-0\" -> \"{}\";",
+            course_id(&program.courses()[0].guid)
+        )));
+        assert!(dot.trim_end().ends_with('}'));
+
+        // Module nodes are the clusters themselves
+        assert!(!dot.contains("\"module-0\" ["));
+        assert!(!dot.contains("\"module-0\" ->"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert_eq!(quote("Say \"hi\"\\\n"), "\"Say \\\"hi\\\"\\\\\\n\"");
+    }
+}
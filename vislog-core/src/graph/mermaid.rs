@@ -0,0 +1,111 @@
+//! [Mermaid](https://mermaid.js.org/syntax/flowchart.html) flowchart export of a [ProgramGraph]
+use std::fmt::Write;
+
+use super::{EdgeKind, Node, NodeKind, ProgramGraph};
+
+/// One subgraph per module with its courses listed by level, and the prerequisites drawn as
+/// dotted edges between them
+pub fn to_mermaid(graph: &ProgramGraph) -> String {
+    let mut mermaid = String::new();
+
+    writeln!(mermaid, "---\ntitle: {}\n---", escape(&graph.title)).unwrap();
+    writeln!(mermaid, "flowchart TB").unwrap();
+
+    for module in graph.modules() {
+        writeln!(
+            mermaid,
+            "    subgraph {}[\"{}\"]",
+            id(&module.id),
+            escape(&module.label)
+        )
+        .unwrap();
+        writeln!(mermaid, "        direction TB").unwrap();
+
+        for node in graph.module_nodes(&module.id) {
+            writeln!(mermaid, "        {}", node_statement(node)).unwrap();
+        }
+
+        writeln!(mermaid, "    end").unwrap();
+    }
+
+    for node in graph.loose_nodes() {
+        writeln!(mermaid, "    {}", node_statement(node)).unwrap();
+    }
+
+    for edge in graph.drawn_edges() {
+        let arrow = match edge.kind {
+            EdgeKind::Contains => "-->",
+            EdgeKind::Prerequisite => "-. prerequisite .->",
+            EdgeKind::Corequisite => "-. corequisite .-",
+        };
+
+        writeln!(mermaid, "    {} {arrow} {}", id(&edge.from), id(&edge.to)).unwrap();
+    }
+
+    let external: Vec<String> = graph.loose_nodes().map(|node| id(&node.id)).collect();
+    if !external.is_empty() {
+        writeln!(
+            mermaid,
+            "    classDef external stroke-dasharray: 4 4\n    class {} external",
+            external.join(",")
+        )
+        .unwrap();
+    }
+
+    mermaid
+}
+
+fn node_statement(node: &Node) -> String {
+    let label = match &node.name {
+        Some(name) if !name.is_empty() => format!("{}<br/>{}", escape(&node.label), escape(name)),
+        _ => escape(&node.label),
+    };
+
+    let id = id(&node.id);
+    match node.kind {
+        NodeKind::Module | NodeKind::Requirement => format!("{id}(\"{label}\")"),
+        NodeKind::AndGroup | NodeKind::OrGroup => format!("{id}{{\"{label}\"}}"),
+        NodeKind::Course => format!("{id}[\"{label}\"]"),
+        NodeKind::Label => format!("{id}[/\"{label}\"/]"),
+    }
+}
+
+/// Mermaid ids can't contain the `-` of GUIDs since it's also used for edges
+fn id(id: &str) -> String {
+    id.replace('-', "_")
+}
+
+/// Text that can go in a quoted Mermaid label
+fn escape(s: &str) -> String {
+    s.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graph::{course_id, test::cs_courses, test::cs_major};
+
+    use super::*;
+
+    #[test]
+    fn exports_subgraphs_and_requisites() {
+        let program = cs_major();
+        let graph = ProgramGraph::new(&program, &cs_courses(&program));
+        let mermaid = to_mermaid(&graph);
+
+        assert!(mermaid.contains("title: Major in Computer Science—42 hours"));
+        assert!(mermaid.contains("flowchart TB"));
+        assert!(mermaid.contains("    subgraph module_0[\"Degree Requirements\"]"));
+        assert!(mermaid.contains(&format!(
+            "    requirement_0_0 --> {}",
+            id(&course_id(&program.courses()[0].guid))
+        )));
+        assert!(mermaid.contains("-. prerequisite .->"));
+        assert!(mermaid.contains("-. corequisite .-"));
+        assert!(mermaid.contains("class course_00000000_0000_0000_0000_000000000001 external"));
+    }
+
+    #[test]
+    fn escapes_labels() {
+        assert_eq!(escape("Say \"hi\"\nbye"), "Say #quot;hi#quot;<br/>bye");
+    }
+}
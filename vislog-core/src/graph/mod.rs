@@ -0,0 +1,552 @@
+//! Graphs of the requirements of a program and the prerequisites between its courses, the basis
+//! of the program visualizations
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::equivalence::Equivalences;
+use crate::{parsing::guid::Guid, CourseDetails, CourseEntries, CourseEntry, Program};
+use crate::{Requirement, RequirementModule};
+
+pub mod dot;
+pub mod mermaid;
+#[cfg(feature = "svg")]
+pub mod svg;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "kebab-case")]
+pub enum NodeKind {
+    Module,
+    Requirement,
+    /// All of the entries it contains are required
+    AndGroup,
+    /// Only one of the entries it contains is required
+    OrGroup,
+    Course,
+    Label,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Node {
+    /// Courses and labels are identified by their GUID, so their ids are stable across versions
+    /// of the catalog. Everything else is identified by its position in the requirements of the
+    /// program (Ex: "module-0", "requirement-0-1", "or-0-1-2"), which changes whenever an entry is
+    /// added, removed or moved before it
+    pub id: String,
+    pub kind: NodeKind,
+    /// Short text identifying the node. Ex: "CSC 215" for a course
+    pub label: String,
+    /// Name of a course or label. Ex: "Data Structures" for a course
+    pub name: Option<String>,
+    /// Id of the module the node is drawn in. `None` for modules and for the courses only
+    /// reached as prerequisites of courses in the program
+    pub module: Option<String>,
+    /// Level of a course based on its number. Ex: 200 for "CSC 215"
+    pub level: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeKind {
+    /// From a module, requirement or group to what it's made of
+    Contains,
+    /// From a course to the course requiring it
+    Prerequisite,
+    /// From a course to the course requiring it to be taken at the same time
+    Corequisite,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Edge {
+    /// Made of the kind and the ids of both ends, so it's only as stable as the ids of the nodes.
+    /// Ex: "prerequisite:course-{GUID}->course-{GUID}"
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+}
+
+impl EdgeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Contains => "contains",
+            EdgeKind::Prerequisite => "prerequisite",
+            EdgeKind::Corequisite => "corequisite",
+        }
+    }
+}
+
+impl Edge {
+    pub fn new(from: String, to: String, kind: EdgeKind) -> Self {
+        Self {
+            id: format!("{}:{from}->{to}", kind.as_str()),
+            from,
+            to,
+            kind,
+        }
+    }
+}
+
+/// Every module, requirement, operator group, course and label of a program as the nodes of a
+/// graph, in the order they appear in the catalog.
+///
+/// Serialized as a flat list of nodes and edges that graph libraries can load as is, without
+/// walking the nested requirements of the program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct ProgramGraph {
+    pub guid: Guid,
+    pub title: String,
+    pub url: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl ProgramGraph {
+    /// `courses` holds the details used for the prerequisite and corequisite edges of the courses
+    /// in the program. A requisite cross-listed with a course of the program is drawn from that
+    /// course. Other requisites outside of the program get a node outside of every module, but
+    /// their own requisites are not followed.
+    pub fn new(program: &Program, courses: &HashMap<Guid, CourseDetails>) -> Self {
+        let mut builder = Builder {
+            graph: ProgramGraph {
+                guid: program.guid,
+                title: program.title.clone(),
+                url: program.url.clone(),
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+            node_ids: HashSet::new(),
+            edges: HashSet::new(),
+        };
+
+        for (i, module) in program.requirement_modules().into_iter().enumerate() {
+            builder.module(&i.to_string(), module);
+        }
+
+        // In the order they are listed so that the first listed equivalent of a requisite wins
+        let mut program_courses = Vec::new();
+        let mut seen = HashSet::new();
+        for course in program.courses() {
+            if seen.insert(course.guid) {
+                program_courses.push(course.guid);
+            }
+        }
+
+        let equivalences = Equivalences::new(courses.values());
+        for guid in &program_courses {
+            let Some(details) = courses.get(guid) else {
+                continue;
+            };
+
+            let requisites = [
+                (details.prerequisite, EdgeKind::Prerequisite),
+                (details.corequisite, EdgeKind::Corequisite),
+            ];
+            for (requisite, kind) in requisites {
+                let Some(requisite) = requisite.and_then(|guid| courses.get(&guid)) else {
+                    continue;
+                };
+
+                let in_program = program_courses
+                    .iter()
+                    .find(|guid| equivalences.are_equivalent(guid, &requisite.guid));
+                let from = match in_program {
+                    Some(equivalent) => *equivalent,
+                    None => {
+                        builder.node(Node {
+                            id: course_id(&requisite.guid),
+                            kind: NodeKind::Course,
+                            label: requisite.code(),
+                            name: Some(requisite.name.clone()),
+                            module: None,
+                            level: course_level(&requisite.number),
+                        });
+                        requisite.guid
+                    }
+                };
+                builder.edge(course_id(&from), course_id(guid), kind);
+            }
+        }
+
+        builder.graph
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = &Node> {
+        self.nodes
+            .iter()
+            .filter(|node| node.kind == NodeKind::Module)
+    }
+
+    /// Nodes drawn inside of the module with `module_id`, sorted by course level so that layouts
+    /// keep lower level courses first
+    pub fn module_nodes(&self, module_id: &str) -> Vec<&Node> {
+        let mut nodes: Vec<&Node> = self
+            .nodes
+            .iter()
+            .filter(|node| node.module.as_deref() == Some(module_id))
+            .collect();
+        nodes.sort_by_key(|node| node.level);
+
+        nodes
+    }
+
+    /// Nodes other than modules that are not drawn inside of any module
+    pub fn loose_nodes(&self) -> impl Iterator<Item = &Node> {
+        self.nodes
+            .iter()
+            .filter(|node| node.kind != NodeKind::Module && node.module.is_none())
+    }
+
+    /// Edges between nodes other than modules, which are drawn as the boxes around their nodes
+    pub fn drawn_edges(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(|edge| {
+            self.node(&edge.from)
+                .is_some_and(|node| node.kind != NodeKind::Module)
+        })
+    }
+}
+
+pub fn course_id(guid: &Guid) -> String {
+    format!("course-{guid}")
+}
+
+pub fn label_id(guid: &Guid) -> String {
+    format!("label-{guid}")
+}
+
+/// The hundreds of the course number. Ex: 200 for "215" and 400 for "499L"
+pub fn course_level(number: &str) -> Option<u16> {
+    let digits: String = number.chars().take_while(char::is_ascii_digit).collect();
+
+    digits.parse::<u16>().ok().map(|number| number / 100 * 100)
+}
+
+struct Builder {
+    graph: ProgramGraph,
+    node_ids: HashSet<String>,
+    edges: HashSet<Edge>,
+}
+
+impl Builder {
+    /// Courses and labels listed more than once keep the module they were first listed in
+    fn node(&mut self, node: Node) {
+        if self.node_ids.insert(node.id.clone()) {
+            self.graph.nodes.push(node);
+        }
+    }
+
+    fn edge(&mut self, from: String, to: String, kind: EdgeKind) {
+        let edge = Edge::new(from, to, kind);
+        if !self.edges.contains(&edge) {
+            self.edges.insert(edge.clone());
+            self.graph.edges.push(edge);
+        }
+    }
+
+    fn module(&mut self, path: &str, module: &RequirementModule) {
+        let id = format!("module-{path}");
+        let label = module.title().unwrap_or(match module {
+            RequirementModule::SelectOneEmphasis { .. } => "Select one emphasis",
+            _ => "Requirements",
+        });
+
+        self.node(Node {
+            id: id.clone(),
+            kind: NodeKind::Module,
+            label: label.to_owned(),
+            name: None,
+            module: None,
+            level: None,
+        });
+
+        for (i, requirement) in module.requirements().into_iter().enumerate() {
+            self.requirement(&format!("{path}-{i}"), &id, requirement);
+        }
+    }
+
+    fn requirement(&mut self, path: &str, module_id: &str, requirement: &Requirement) {
+        let id = format!("requirement-{path}");
+        let label = match requirement {
+            Requirement::Label {
+                title: None,
+                req_narrative: Some(narrative),
+            } => narrative.as_str(),
+            _ => requirement.title().unwrap_or("Requirement"),
+        };
+
+        self.node(Node {
+            id: id.clone(),
+            kind: NodeKind::Requirement,
+            label: label.to_owned(),
+            name: None,
+            module: Some(module_id.to_owned()),
+            level: None,
+        });
+        self.edge(module_id.to_owned(), id.clone(), EdgeKind::Contains);
+
+        if let Some(entries) = requirement.course_entries() {
+            self.entries(path, &id, module_id, entries);
+        }
+    }
+
+    fn entries(&mut self, path: &str, parent_id: &str, module_id: &str, entries: &CourseEntries) {
+        for (i, entry) in entries.iter().enumerate() {
+            let path = format!("{path}-{i}");
+
+            let id = match entry {
+                CourseEntry::And(entries) | CourseEntry::Or(entries) => {
+                    let (id, kind, label) = match entry {
+                        CourseEntry::And(_) => {
+                            (format!("and-{path}"), NodeKind::AndGroup, "All of")
+                        }
+                        _ => (format!("or-{path}"), NodeKind::OrGroup, "One of"),
+                    };
+
+                    self.node(Node {
+                        id: id.clone(),
+                        kind,
+                        label: label.to_owned(),
+                        name: None,
+                        module: Some(module_id.to_owned()),
+                        level: None,
+                    });
+                    self.entries(&path, &id, module_id, entries);
+
+                    id
+                }
+                CourseEntry::Course(course) => {
+                    let id = course_id(&course.guid);
+                    self.node(Node {
+                        id: id.clone(),
+                        kind: NodeKind::Course,
+                        label: course.code(),
+                        name: course.name.clone(),
+                        module: Some(module_id.to_owned()),
+                        level: course_level(&course.number),
+                    });
+
+                    id
+                }
+                CourseEntry::Label(label) => {
+                    let id = label_id(&label.guid);
+                    self.node(Node {
+                        id: id.clone(),
+                        kind: NodeKind::Label,
+                        label: label.name.clone(),
+                        name: None,
+                        module: Some(module_id.to_owned()),
+                        level: None,
+                    });
+
+                    id
+                }
+            };
+
+            self.edge(parent_id.to_owned(), id, EdgeKind::Contains);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    pub fn cs_major() -> Program {
+        let program_json = std::fs::read_to_string("../data/cs_major.json").unwrap();
+        serde_json::from_str(&program_json).unwrap()
+    }
+
+    /// Details of every course in the CS major, with CSC 255 requiring the first course and an
+    /// outside course
+    pub fn cs_courses(program: &Program) -> HashMap<Guid, CourseDetails> {
+        let mut courses: HashMap<Guid, CourseDetails> = program
+            .courses()
+            .into_iter()
+            .map(|course| {
+                let details = CourseDetails {
+                    url: course.url.clone(),
+                    path: course.path.clone(),
+                    subject_name: course.subject_name.clone(),
+                    name: course.name.clone().unwrap_or_default(),
+                    credits: course.credits,
+                    ..CourseDetails::stub(course.guid, &course.code())
+                };
+
+                (course.guid, details)
+            })
+            .collect();
+
+        let outside = CourseDetails {
+            guid: Guid::try_from("00000000-0000-0000-0000-000000000001").unwrap(),
+            subject_code: "MAT".to_owned(),
+            number: "111".to_owned(),
+            name: "Calculus I".to_owned(),
+            ..courses.values().next().unwrap().clone()
+        };
+
+        let first = program.courses()[0].guid;
+        let data_structures = program
+            .courses()
+            .into_iter()
+            .find(|course| course.code() == "CSC 255")
+            .unwrap()
+            .guid;
+        let details = courses.get_mut(&data_structures).unwrap();
+        details.prerequisite = Some(first);
+        details.corequisite = Some(outside.guid);
+        courses.insert(outside.guid, outside);
+
+        courses
+    }
+
+    #[test]
+    fn builds_nodes_for_every_part_of_the_requirements() {
+        let program = cs_major();
+        let graph = ProgramGraph::new(&program, &HashMap::new());
+
+        assert_eq!(graph.modules().count(), 1);
+        assert_eq!(graph.node("module-0").unwrap().label, "Degree Requirements");
+
+        let course = program.courses()[0];
+        let node = graph.node(&course_id(&course.guid)).unwrap();
+        assert_eq!(node.kind, NodeKind::Course);
+        assert_eq!(node.label, course.code());
+        assert_eq!(node.module.as_deref(), Some("module-0"));
+
+        // Courses listed more than once only get one node
+        let mut unique_courses: Vec<_> = program.courses().iter().map(|c| c.guid).collect();
+        unique_courses.sort_by_key(|guid| guid.to_string());
+        unique_courses.dedup();
+        let course_nodes = graph
+            .nodes
+            .iter()
+            .filter(|node| node.kind == NodeKind::Course)
+            .count();
+        assert_eq!(course_nodes, unique_courses.len());
+
+        assert!(graph
+            .edges
+            .iter()
+            .all(|edge| edge.kind == EdgeKind::Contains));
+        assert!(graph
+            .edges
+            .iter()
+            .all(|edge| graph.node(&edge.from).is_some() && graph.node(&edge.to).is_some()));
+    }
+
+    #[test]
+    fn adds_requisite_edges() {
+        let program = cs_major();
+        let courses = cs_courses(&program);
+        let graph = ProgramGraph::new(&program, &courses);
+
+        let first = course_id(&program.courses()[0].guid);
+        let data_structures = graph
+            .nodes
+            .iter()
+            .find(|node| node.label == "CSC 255")
+            .unwrap();
+        assert_eq!(data_structures.level, Some(200));
+
+        assert!(graph.edges.contains(&Edge::new(
+            first,
+            data_structures.id.clone(),
+            EdgeKind::Prerequisite
+        )));
+
+        let outside = graph
+            .loose_nodes()
+            .find(|node| node.label == "MAT 111")
+            .unwrap();
+        assert!(graph.edges.contains(&Edge::new(
+            outside.id.clone(),
+            data_structures.id.clone(),
+            EdgeKind::Corequisite
+        )));
+    }
+
+    #[test]
+    fn draws_cross_listed_requisites_from_the_program() {
+        let program = cs_major();
+        let mut courses = cs_courses(&program);
+
+        // MAT 111, the corequisite of CSC 255, is cross-listed with the first course of the program
+        let first = program.courses()[0];
+        courses
+            .get_mut(&Guid::try_from("00000000-0000-0000-0000-000000000001").unwrap())
+            .unwrap()
+            .cross_listed = vec![first.code()];
+        let graph = ProgramGraph::new(&program, &courses);
+
+        let data_structures = graph
+            .nodes
+            .iter()
+            .find(|node| node.label == "CSC 255")
+            .unwrap();
+        assert!(graph.loose_nodes().all(|node| node.label != "MAT 111"));
+        assert!(graph.edges.contains(&Edge::new(
+            course_id(&first.guid),
+            data_structures.id.clone(),
+            EdgeKind::Corequisite
+        )));
+    }
+
+    #[test]
+    fn serializes_flat_nodes_and_edges() {
+        let program = cs_major();
+        let graph = ProgramGraph::new(&program, &cs_courses(&program));
+        let json = serde_json::to_value(&graph).unwrap();
+
+        assert_eq!(json["guid"], "5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5");
+        assert_eq!(
+            json["nodes"][0],
+            serde_json::json!({
+                "id": "module-0",
+                "kind": "module",
+                "label": "Degree Requirements",
+                "name": null,
+                "module": null,
+                "level": null,
+            })
+        );
+        assert_eq!(
+            json["edges"][0],
+            serde_json::json!({
+                "id": "contains:module-0->requirement-0-0",
+                "from": "module-0",
+                "to": "requirement-0-0",
+                "kind": "contains",
+            })
+        );
+
+        let kinds: HashSet<&str> = json["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| node["kind"].as_str().unwrap())
+            .collect();
+        assert!(kinds.contains("or-group") || kinds.contains("and-group"));
+    }
+
+    #[test]
+    fn parses_course_levels() {
+        assert_eq!(course_level("215"), Some(200));
+        assert_eq!(course_level("499L"), Some(400));
+        assert_eq!(course_level("95"), Some(0));
+        assert_eq!(course_level("XXX"), None);
+    }
+}
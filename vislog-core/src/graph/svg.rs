@@ -0,0 +1,191 @@
+//! SVG rendering of a [ProgramGraph], laid out in pure Rust with
+//! [layout-rs](https://github.com/nadavrot/layout) instead of an external Graphviz binary
+use std::collections::HashMap;
+
+use layout::{
+    backends::svg::SVGWriter,
+    core::{
+        base::Orientation,
+        color::Color,
+        geometry::Point,
+        style::{LineStyleKind, StyleAttr},
+    },
+    std_shapes::shapes::{Arrow, Element, LineEndKind, ShapeKind},
+    topo::layout::VisualGraph,
+};
+
+use super::{EdgeKind, Node, NodeKind, ProgramGraph};
+
+const FONT_SIZE: usize = 14;
+/// Names longer than this are wrapped over multiple lines to keep the nodes narrow
+const MAX_LINE_CHARS: usize = 28;
+
+/// The program at the top, followed by its modules and what they contain, with the prerequisites
+/// pulling the courses requiring them further down
+pub fn to_svg(graph: &ProgramGraph) -> String {
+    let mut visual = VisualGraph::new(Orientation::TopToBottom);
+
+    let root = visual.add_node(element(
+        &wrap(&graph.title),
+        style("#2c3e50", Some("#d6eaf8"), 3, 8),
+    ));
+
+    let mut handles = HashMap::new();
+    for node in &graph.nodes {
+        let handle = visual.add_node(node_element(node));
+        handles.insert(node.id.as_str(), handle);
+
+        if node.kind == NodeKind::Module {
+            visual.add_edge(Arrow::default(), root, handle);
+        }
+    }
+
+    for edge in &graph.edges {
+        let (Some(&from), Some(&to)) = (
+            handles.get(edge.from.as_str()),
+            handles.get(edge.to.as_str()),
+        ) else {
+            continue;
+        };
+
+        let arrow = match edge.kind {
+            EdgeKind::Contains => Arrow::default(),
+            EdgeKind::Prerequisite => arrow(LineStyleKind::Dashed, "#c0392b"),
+            EdgeKind::Corequisite => arrow(LineStyleKind::Dotted, "#2471a3"),
+        };
+        visual.add_edge(arrow, from, to);
+    }
+
+    let mut writer = SVGWriter::new();
+    visual.do_it(false, false, false, &mut writer);
+
+    with_title(
+        &writer.finalize().replace(
+            "font-family: Times, serif",
+            "font-family: Helvetica, Arial, sans-serif",
+        ),
+        &graph.title,
+    )
+}
+
+fn node_element(node: &Node) -> Element {
+    let label = match &node.name {
+        Some(name) if !name.is_empty() => format!("{}\n{}", node.label, wrap(name)),
+        _ => wrap(&node.label),
+    };
+
+    let look = match node.kind {
+        NodeKind::Module => style("#2c3e50", Some("#ebf5fb"), 2, 8),
+        NodeKind::Requirement => style("#566573", Some("#ffffff"), 1, 8),
+        NodeKind::AndGroup | NodeKind::OrGroup => style("#7f8c8d", Some("#fdfefe"), 1, 20),
+        NodeKind::Course if node.module.is_none() => style("#95a5a6", Some("#f4f6f7"), 1, 0),
+        NodeKind::Course => style("#1f618d", Some("#eaf2f8"), 1, 0),
+        NodeKind::Label => style("#7d6608", Some("#fef9e7"), 1, 0),
+    };
+
+    element(&label, look)
+}
+
+fn element(label: &str, look: StyleAttr) -> Element {
+    Element::create(
+        ShapeKind::new_box(label),
+        look,
+        Orientation::TopToBottom,
+        Point::zero(),
+    )
+}
+
+fn style(line: &str, fill: Option<&str>, line_width: usize, rounded: usize) -> StyleAttr {
+    StyleAttr::new(
+        Color::fast(line),
+        line_width,
+        fill.map(Color::fast),
+        rounded,
+        FONT_SIZE,
+    )
+}
+
+fn arrow(line_style: LineStyleKind, color: &str) -> Arrow {
+    Arrow::new(
+        LineEndKind::None,
+        LineEndKind::Arrow,
+        line_style,
+        "",
+        &style(color, None, 2, 0),
+        &None,
+        &None,
+    )
+}
+
+/// Breaks `text` into lines of at most `MAX_LINE_CHARS` characters where possible
+fn wrap(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= MAX_LINE_CHARS => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_owned()),
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Adds a `<title>` so that viewers and screen readers can tell which program the map is of
+fn with_title(svg: &str, title: &str) -> String {
+    let title = title
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+
+    match svg
+        .find("<svg")
+        .and_then(|start| svg[start..].find('>').map(|end| start + end + 1))
+    {
+        Some(end) => format!("{}\n<title>{title}</title>{}", &svg[..end], &svg[end..]),
+        None => svg.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::graph::test::{cs_courses, cs_major};
+
+    use super::*;
+
+    #[test]
+    fn renders_program_map() {
+        let program = cs_major();
+        let graph = ProgramGraph::new(&program, &cs_courses(&program));
+        let svg = to_svg(&graph);
+
+        assert!(svg.contains("<svg "));
+        assert!(svg.contains("<title>Major in Computer Science—42 hours</title>"));
+        assert!(svg.contains("CSC 255"));
+        assert!(svg.contains("Programming in C"));
+        assert!(svg.contains("Helvetica"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn renders_program_without_requirements() {
+        let mut program = cs_major();
+        program.requirements = None;
+        let svg = to_svg(&ProgramGraph::new(&program, &HashMap::new()));
+
+        assert!(svg.contains("Computer Science"));
+    }
+
+    #[test]
+    fn wraps_long_names() {
+        assert_eq!(
+            wrap("Computer Science II: Algorithms and Data Structures"),
+            "Computer Science II:\nAlgorithms and Data\nStructures"
+        );
+        assert_eq!(wrap("CSC 255"), "CSC 255");
+    }
+}
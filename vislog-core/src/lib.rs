@@ -1,18 +1,37 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    fmt::Display,
+    ops::{Deref, DerefMut},
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::parsing::guid::{deserialize_guid_with_curly_braces, Guid};
-
+use crate::{
+    credits::Credits,
+    parsing::guid::{deserialize_guid_with_curly_braces, Guid},
+};
+
+pub mod credits;
+pub mod diff;
+pub mod equivalence;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod graph;
 pub mod parsing;
+pub mod plan;
+pub mod stored;
+#[cfg(feature = "typescript")]
+pub mod typescript;
 
 /// Representation of a program in the catalog
 ///
 // TODO: Make Program and all of its sub-components interoperable between
 // pre-parsed JSON string, post-parsed JSON string, and the respective
-// serde_json::Value representations of each
+// serde_json::Value representations of each. Reading back the post-parsed JSON is done through
+// (Stored)[crate::stored::Stored] for now
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Program {
     /// Link to the official catalog
     pub url: String,
@@ -36,6 +55,8 @@ pub struct Program {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "data")]
 pub enum Requirements {
     Single(RequirementModule),
@@ -45,6 +66,8 @@ pub enum Requirements {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "data")]
 pub enum RequirementModule {
     SingleBasicRequirement {
@@ -73,6 +96,8 @@ pub enum RequirementModule {
 // TODO: Extract all the useful information from the `req_narrative` field for each of the variants
 // NOTE: The field `req_note` may contain useful information that can potentially be parsed
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "data")]
 pub enum Requirement {
     Courses {
@@ -100,6 +125,8 @@ pub enum CourseUnit {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CourseEntries(Vec<CourseEntry>);
 
 impl Deref for CourseEntries {
@@ -117,9 +144,14 @@ impl DerefMut for CourseEntries {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 #[serde(tag = "type", content = "data")]
 pub enum CourseEntry {
+    // Entries nest, the schema refers back to `CourseEntries` instead of expanding it forever
+    #[cfg_attr(feature = "openapi", schema(no_recursion))]
     And(CourseEntries),
+    #[cfg_attr(feature = "openapi", schema(no_recursion))]
     Or(CourseEntries),
     Label(Label),
     Course(Course),
@@ -137,6 +169,8 @@ pub enum CourseEntry {
 // actual implementation of the special deserialization is in `CourseEntries` struct's
 // `Deserialization` implementation where a sepcial `visit_map` is implemented for this use case
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Course {
     pub url: String,
     pub path: String,
@@ -166,27 +200,29 @@ pub struct Course {
     pub subject_name: Option<String>,
     pub subject_code: String,
 
-    /// The representation of possible credits earned by completing the course. The lower bound is
-    /// the minimum that you can earn while the upper bound is the max. If there is a max, then the
-    /// tuple should be interpreted as an inclusive range from the lower bound to the upper bound,
-    /// which can be think of as (lower bound..=upper bound).
-    pub credits: (u8, Option<u8>),
+    /// The credits earned by completing the course, a range of them when the student chooses how
+    /// many
+    pub credits: Credits,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct Label {
     pub url: String,
     pub guid: Guid,
     pub name: String,
     pub number: Option<String>,
     pub subject_code: Option<String>,
-    pub credits: (u8, Option<u8>),
+    pub credits: Credits,
 }
 
 /// Representation of a course along with additional details
 // TODO: Deduplicate information between (CourseDetails)[crate::CourseDetails] and
 // (Course)[crate::Course]
 #[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
 pub struct CourseDetails {
     pub url: String,
     pub guid: Guid,
@@ -195,18 +231,85 @@ pub struct CourseDetails {
     pub subject_name: Option<String>,
     pub number: String,
     pub name: String,
-    pub credits_min: u8,
-    pub credits_max: Option<u8>,
+    /// Parsed from `credits_min` and `credits_max` in the catalog, or from `credits_narrative` when
+    /// they are missing
+    pub credits: Credits,
     pub description: String,
     pub prerequisite_narrative: Option<String>,
     pub prerequisite: Option<Guid>,
     pub corequisite_narrative: Option<String>,
     pub corequisite: Option<Guid>,
+    /// Ex: "Also listed as SOC 318."
+    pub crosslist_narrative: Option<String>,
+    /// Codes of the courses this one is cross-listed with, parsed from `crosslist_narrative` and
+    /// the description. Ex: ["SOC 318"]. See [Equivalences](crate::equivalence::Equivalences)
+    /// for the groups of courses counting as the same course
+    pub cross_listed: Vec<String>,
+    /// The credits as written in the catalog. Ex: "1.0 - 4.0"
+    pub credits_narrative: Option<String>,
+    /// When the course is offered as written in the catalog. Ex: "Fall of odd years"
+    pub offered: Option<String>,
+    /// `offered` parsed into terms, empty if it's missing or couldn't be parsed
+    pub offerings: Vec<Offering>,
+    /// The general education requirements the course counts toward as written in the catalog
+    pub distribution: Option<String>,
+    /// The categories recognized in `distribution`
+    pub gen_ed: Vec<GenEdCategory>,
+    pub instructor: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// A term in which a course is offered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Offering {
+    pub term: Term,
+    pub years: Years,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "kebab-case")]
+pub enum Term {
+    Fall,
+    Spring,
+    Summer,
+}
+
+/// The years in which a term offering takes place
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "kebab-case")]
+pub enum Years {
+    Every,
+    Odd,
+    Even,
+}
+
+/// Categories of the general education requirements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+#[serde(rename_all = "kebab-case")]
+pub enum GenEdCategory {
+    ChristianStudies,
+    Communication,
+    FineArts,
+    ForeignLanguage,
+    History,
+    Humanities,
+    Mathematics,
+    NaturalScience,
+    SocialScience,
+    Wellness,
 }
 
 impl PartialOrd for Program {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.title.partial_cmp(&other.title)
+        Some(self.cmp(other))
     }
 }
 
@@ -216,10 +319,172 @@ impl Ord for Program {
     }
 }
 
+impl Program {
+    /// All the `RequirementModule`s of the program in the order they appear in the catalog
+    pub fn requirement_modules(&self) -> Vec<&RequirementModule> {
+        match &self.requirements {
+            Some(Requirements::Single(module)) => vec![module],
+            Some(Requirements::Many(modules)) => modules.iter().collect(),
+            Some(Requirements::SelectTrack) | None => Vec::new(),
+        }
+    }
+
+    /// Every `Course` referenced anywhere in the program's requirements, including the ones nested
+    /// inside of operator groups
+    pub fn courses(&self) -> Vec<&Course> {
+        self.requirement_modules()
+            .into_iter()
+            .flat_map(RequirementModule::requirements)
+            .filter_map(Requirement::course_entries)
+            .flat_map(CourseEntries::courses)
+            .collect()
+    }
+}
+
+impl RequirementModule {
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            RequirementModule::SingleBasicRequirement { title, .. }
+            | RequirementModule::BasicRequirements { title, .. } => title.as_deref(),
+            RequirementModule::Label { title } => Some(title.as_str()),
+            RequirementModule::SelectOneEmphasis { .. } | RequirementModule::Unimplemented(_) => {
+                None
+            }
+        }
+    }
+
+    pub fn requirements(&self) -> Vec<&Requirement> {
+        match self {
+            RequirementModule::SingleBasicRequirement { requirement, .. } => vec![requirement],
+            RequirementModule::BasicRequirements { requirements, .. } => {
+                requirements.iter().collect()
+            }
+            RequirementModule::SelectOneEmphasis { emphases } => emphases.iter().collect(),
+            RequirementModule::Label { .. } | RequirementModule::Unimplemented(_) => Vec::new(),
+        }
+    }
+}
+
+impl Requirement {
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Requirement::Courses { title, .. } | Requirement::Label { title, .. } => {
+                title.as_deref()
+            }
+            Requirement::SelectFromCourses { title, .. } => Some(title.as_str()),
+        }
+    }
+
+    pub fn course_entries(&self) -> Option<&CourseEntries> {
+        match self {
+            Requirement::Courses { courses, .. } => Some(courses),
+            Requirement::SelectFromCourses { courses, .. } => courses.as_ref(),
+            Requirement::Label { .. } => None,
+        }
+    }
+}
+
+impl CourseEntries {
+    /// Flattens the (possibly nested) operator groups into a list of the `Course`s they contain
+    pub fn courses(&self) -> Vec<&Course> {
+        self.iter()
+            .flat_map(|entry| match entry {
+                CourseEntry::And(entries) | CourseEntry::Or(entries) => entries.courses(),
+                CourseEntry::Course(course) => vec![course],
+                CourseEntry::Label(_) => Vec::new(),
+            })
+            .collect()
+    }
+}
+
+impl Course {
+    /// The subject code and number used by humans to refer to the course. Ex: "CSC 215"
+    pub fn code(&self) -> String {
+        format!("{} {}", self.subject_code, self.number)
+    }
+}
+
+impl CourseDetails {
+    /// The subject code and number used by humans to refer to the course. Ex: "CSC 215"
+    pub fn code(&self) -> String {
+        format!("{} {}", self.subject_code, self.number)
+    }
+
+    /// Whether the course is offered in the term of the year, `None` if the catalog doesn't say
+    pub fn is_offered_in(&self, term: Term, year: Option<i32>) -> Option<bool> {
+        if self.offerings.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.offerings
+                .iter()
+                .any(|offering| offering.term == term && offering.years.includes(year)),
+        )
+    }
+}
+
+#[cfg(test)]
+impl CourseDetails {
+    /// A 3 hour course with only its GUID and code (Ex: "CSC 215") filled in, for tests to fill in
+    /// the fields they care about with the struct update syntax
+    pub(crate) fn stub(guid: Guid, code: &str) -> Self {
+        let (subject_code, number) = code.split_once(' ').unwrap_or((code, ""));
+
+        Self {
+            url: String::new(),
+            guid,
+            path: String::new(),
+            subject_code: subject_code.to_owned(),
+            subject_name: None,
+            number: number.to_owned(),
+            name: String::new(),
+            credits: credits::Credits::fixed(credits::CreditHours::whole(3)),
+            description: String::new(),
+            prerequisite_narrative: None,
+            prerequisite: None,
+            corequisite_narrative: None,
+            corequisite: None,
+            crosslist_narrative: None,
+            cross_listed: Vec::new(),
+            credits_narrative: None,
+            offered: None,
+            offerings: Vec::new(),
+            distribution: None,
+            gen_ed: Vec::new(),
+            instructor: None,
+            notes: None,
+        }
+    }
+}
+
+impl Years {
+    /// Whether `year` is one of the years, any year is when it isn't known
+    pub fn includes(&self, year: Option<i32>) -> bool {
+        match (self, year) {
+            (Years::Every, _) | (_, None) => true,
+            (Years::Odd, Some(year)) => year % 2 != 0,
+            (Years::Even, Some(year)) => year % 2 == 0,
+        }
+    }
+}
+
+impl Display for Term {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Term::Fall => write!(f, "Fall"),
+            Term::Spring => write!(f, "Spring"),
+            Term::Summer => write!(f, "Summer"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use core::panic;
 
+    use crate::credits::CreditHours;
+
     use super::*;
 
     #[test]
@@ -374,4 +639,88 @@ mod test {
 
         assert_eq!(parsed_course_details.len(), 1870);
     }
+
+    #[test]
+    fn keeps_course_metadata() {
+        let course_json = serde_json::json!({
+            "url": "https://catalog/psy-318",
+            "GUID": "{00000000-0000-0000-0000-000000000001}",
+            "path": "/PSY-318",
+            "subject_code": "PSY",
+            "subject_name": "Psychology",
+            "number": "318",
+            "name": "Marriage and Family",
+            "credits_narrative": "3",
+            "credits_min": "3.0",
+            "credits_max": null,
+            "description": "",
+            "offered": "Fall of odd years",
+            "distribution": "Core: Social Science",
+            "instructor": "Staff",
+            "notes": "Seniors only",
+            "prerequisite_narrative": null,
+            "corequisite_narrative": null,
+            "crosslist_narrative": "Also listed as SOC 318.",
+        });
+
+        let course: CourseDetails = serde_json::from_str(&course_json.to_string()).unwrap();
+
+        assert_eq!(course.credits_narrative.as_deref(), Some("3"));
+        assert_eq!(course.offered.as_deref(), Some("Fall of odd years"));
+        assert_eq!(
+            course.offerings,
+            vec![Offering {
+                term: Term::Fall,
+                years: Years::Odd
+            }]
+        );
+        assert_eq!(course.gen_ed, vec![GenEdCategory::SocialScience]);
+        assert_eq!(course.instructor.as_deref(), Some("Staff"));
+        assert_eq!(course.notes.as_deref(), Some("Seniors only"));
+        assert_eq!(course.cross_listed, vec!["SOC 318"]);
+
+        assert_eq!(course.is_offered_in(Term::Fall, Some(2025)), Some(true));
+        assert_eq!(course.is_offered_in(Term::Fall, Some(2024)), Some(false));
+        assert_eq!(course.is_offered_in(Term::Fall, None), Some(true));
+        assert_eq!(course.is_offered_in(Term::Spring, Some(2025)), Some(false));
+        let unknown = CourseDetails {
+            offerings: Vec::new(),
+            ..course
+        };
+        assert_eq!(unknown.is_offered_in(Term::Spring, Some(2025)), None);
+    }
+
+    #[test]
+    fn parses_fractional_and_narrative_course_credits() {
+        let course = |credits_min: Option<&str>, credits_max: Option<&str>, narrative: &str| {
+            let course_json = serde_json::json!({
+                "url": "https://catalog/mus-150",
+                "GUID": "{00000000-0000-0000-0000-000000000001}",
+                "path": "/MUS-150",
+                "subject_code": "MUS",
+                "subject_name": "Music",
+                "number": "150",
+                "name": "Ensemble",
+                "credits_narrative": narrative,
+                "credits_min": credits_min,
+                "credits_max": credits_max,
+                "description": "",
+                "prerequisite_narrative": null,
+                "corequisite_narrative": null,
+            });
+            serde_json::from_str::<CourseDetails>(&course_json.to_string())
+                .unwrap()
+                .credits
+        };
+        let hours = CreditHours::from_hundredths;
+
+        assert_eq!(course(Some("0.5"), None, "0.5"), Credits::fixed(hours(50)));
+        assert_eq!(
+            course(Some("1.5"), Some("3.0"), "1.5 - 3.0"),
+            Credits::range(hours(150), hours(300))
+        );
+        assert_eq!(course(None, None, "2"), Credits::fixed(hours(200)));
+        assert_eq!(course(None, None, "Variable"), Credits::fixed(hours(0)));
+        assert_eq!(course(None, None, "See department"), Credits::default());
+    }
 }
@@ -6,6 +6,7 @@ use anyhow::Error as AnyhowError;
 use serde::Deserialize;
 use thiserror::Error;
 
+use crate::credits::Credits;
 use crate::parsing::guid::Guid;
 use crate::Label;
 use crate::{Course, CourseEntries, CourseEntry};
@@ -92,7 +93,9 @@ impl CoursesParser {
 
         match self.state {
             InitialState => match entry {
-                ParsedCourseEntry::And | ParsedCourseEntry::Or => return Err(InvalidEntry(entry)),
+                ParsedCourseEntry::And | ParsedCourseEntry::Or => {
+                    Err(InvalidEntry(Box::new(entry)))
+                }
                 ParsedCourseEntry::Blank => {
                     self.state = InitialBlankRead;
                     Ok(())
@@ -152,7 +155,7 @@ impl CoursesParser {
             },
             InitialBlankRead => match entry {
                 ParsedCourseEntry::And | ParsedCourseEntry::Or | ParsedCourseEntry::Blank => {
-                    Err(InvalidEntry(entry))
+                    Err(InvalidEntry(Box::new(entry)))
                 }
                 ParsedCourseEntry::Label(label) => match self.parsing_state.course_buffer {
                     Some(ref mut buf) => {
@@ -226,7 +229,7 @@ impl CoursesParser {
                         Ok(())
                     }
                 }
-                ParsedCourseEntry::Blank => Err(InvalidEntry(entry)),
+                ParsedCourseEntry::Blank => Err(InvalidEntry(Box::new(entry))),
                 ParsedCourseEntry::Label(label) => match self.parsing_state.course_buffer {
                     Some(ref mut buf) => {
                         buf.push(CourseEntry::Label(label));
@@ -252,7 +255,7 @@ impl CoursesParser {
             },
             OperatorRead => match entry {
                 ParsedCourseEntry::And | ParsedCourseEntry::Or | ParsedCourseEntry::Blank => {
-                    Err(InvalidEntry(entry))
+                    Err(InvalidEntry(Box::new(entry)))
                 }
                 ParsedCourseEntry::Label(label) => match self.parsing_state.course_buffer {
                     Some(ref mut buf) => {
@@ -363,7 +366,7 @@ impl CoursesParser {
                         self.state = NestingOperatorRead;
                         Ok(())
                     }
-                    ParsedCourseEntry::Blank => Err(InvalidEntry(entry)),
+                    ParsedCourseEntry::Blank => Err(InvalidEntry(Box::new(entry))),
                     ParsedCourseEntry::Label(label) => {
                         // Append parsed Operator group to `state.entries`
                         let buf = self.parsing_state.course_buffer.take().ok_or(ParsingError(
@@ -425,17 +428,21 @@ impl CoursesParser {
                 }
             }
             NestingOperatorRead => match entry {
-                ParsedCourseEntry::And | ParsedCourseEntry::Or => Err(InvalidEntry(entry)),
+                ParsedCourseEntry::And | ParsedCourseEntry::Or => {
+                    Err(InvalidEntry(Box::new(entry)))
+                }
                 ParsedCourseEntry::Blank => {
                     self.state = NestedInitialBlankRead;
                     Ok(())
                 }
-                ParsedCourseEntry::Label(_) => Err(InvalidEntry(entry)),
-                ParsedCourseEntry::Course(_) => Err(InvalidEntry(entry)),
+                ParsedCourseEntry::Label(_) => Err(InvalidEntry(Box::new(entry))),
+                ParsedCourseEntry::Course(_) => Err(InvalidEntry(Box::new(entry))),
             },
             NestedInitialBlankRead => match entry {
-                ParsedCourseEntry::And | ParsedCourseEntry::Or => Err(InvalidEntry(entry)),
-                ParsedCourseEntry::Blank => Err(InvalidEntry(entry)),
+                ParsedCourseEntry::And | ParsedCourseEntry::Or => {
+                    Err(InvalidEntry(Box::new(entry)))
+                }
+                ParsedCourseEntry::Blank => Err(InvalidEntry(Box::new(entry))),
                 ParsedCourseEntry::Label(label) => {
                     self.parsing_state
                         .course_buffer
@@ -466,7 +473,7 @@ impl CoursesParser {
                     self.state = NestedOperatorRead;
                     Ok(())
                 }
-                ParsedCourseEntry::Blank => Err(InvalidEntry(entry)),
+                ParsedCourseEntry::Blank => Err(InvalidEntry(Box::new(entry))),
                 ParsedCourseEntry::Label(label) => {
                     self.parsing_state
                         .course_buffer
@@ -486,7 +493,7 @@ impl CoursesParser {
             },
             NestedOperatorRead => match entry {
                 ParsedCourseEntry::And | ParsedCourseEntry::Or | ParsedCourseEntry::Blank => {
-                    Err(InvalidEntry(entry))
+                    Err(InvalidEntry(Box::new(entry)))
                 }
                 ParsedCourseEntry::Label(label) => match self.parsing_state.course_buffer {
                     Some(ref mut buf) => {
@@ -512,7 +519,9 @@ impl CoursesParser {
                 },
             },
             NestedReadCourseWithOp => match entry {
-                ParsedCourseEntry::And | ParsedCourseEntry::Or => Err(InvalidEntry(entry)),
+                ParsedCourseEntry::And | ParsedCourseEntry::Or => {
+                    Err(InvalidEntry(Box::new(entry)))
+                }
                 ParsedCourseEntry::Blank => {
                     self.state = NestedTerminatingBlankRead;
                     Ok(())
@@ -603,7 +612,7 @@ impl CoursesParser {
                             Err(DoubleNesting)
                         }
                     }
-                    ParsedCourseEntry::Blank => Err(InvalidEntry(entry)),
+                    ParsedCourseEntry::Blank => Err(InvalidEntry(Box::new(entry))),
                     // TODO: Find a way to eliminate the consistent repeating of parsing logic
                     // between `Label` and `Course`
                     ParsedCourseEntry::Label(label) => {
@@ -959,8 +968,8 @@ impl TryFrom<RawCourseEntry> for ParsedCourseEntry {
     type Error = AnyhowError;
 
     fn try_from(entry: RawCourseEntry) -> Result<Self, Self::Error> {
-        if entry.name.is_some() && entry.is_narrative == "True" {
-            let parsed_entry = match entry.name.as_ref().unwrap().as_str() {
+        if let (Some(name), "True") = (entry.name.as_ref(), entry.is_narrative.as_str()) {
+            let parsed_entry = match name.as_str() {
                 "And" => Self::And,
                 "Or" => Self::Or,
                 "" => Self::Blank,
@@ -972,11 +981,11 @@ impl TryFrom<RawCourseEntry> for ParsedCourseEntry {
                         Guid::try_from(guid)?
                     };
 
-                    let credits = parse_course_credits(entry.credits.as_str())?;
+                    let credits: Credits = entry.credits.parse()?;
                     Self::Label(Label {
                         url: entry.url,
                         guid,
-                        name: entry.name.unwrap(),
+                        name: name.clone(),
                         subject_code: entry.subject_code,
                         credits,
                         number: entry.number,
@@ -999,7 +1008,7 @@ impl TryFrom<RawCourseEntry> for ParsedCourseEntry {
             .ok_or(anyhow!("missing course number"))?
             .parse()?;
 
-        let credits = parse_course_credits(entry.credits.as_str())?;
+        let credits: Credits = entry.credits.parse()?;
 
         Ok(Self::Course(Course {
             url: entry.url,
@@ -1014,43 +1023,6 @@ impl TryFrom<RawCourseEntry> for ParsedCourseEntry {
     }
 }
 
-/// Parse `credits` field for `Course` struct from a `&str` containing a valid representation of
-/// numbers of credits.
-///
-/// ### Examples:
-/// - "1"
-/// - "1.0-3.0"
-pub(crate) fn parse_course_credits(credits_str: &str) -> Result<(u8, Option<u8>), AnyhowError> {
-    let splits = credits_str.split_once('-');
-
-    if let Some((lower, upper)) = splits {
-        let lower = lower.parse::<f32>()?;
-        let upper = upper.parse::<f32>()?;
-        Ok((lower.floor() as u8, Some(upper.floor() as u8)))
-    } else {
-        Ok((credits_str.parse()?, None))
-    }
-}
-
-#[cfg(test)]
-mod parse_course_credits_test {
-    use super::*;
-
-    #[test]
-    fn can_parse_single_digit_course_credit() {
-        let credits_str = "1";
-
-        assert_eq!(parse_course_credits(credits_str).unwrap(), (1, None));
-    }
-
-    #[test]
-    fn can_parse_range_of_course_credits() {
-        let credits_str = "1.0-3.0";
-
-        assert_eq!(parse_course_credits(credits_str).unwrap(), (1, Some(3)));
-    }
-}
-
 #[cfg(test)]
 mod parse_courses_test {
     use crate::{CourseEntry, Program, Requirement, RequirementModule, Requirements};
@@ -1275,7 +1247,7 @@ pub enum ParseCoursesError {
     #[error("double nesting detected and is not supported")]
     DoubleNesting,
     #[error("invalid entry found: {}", ParsedCourseEntry::name(.0))]
-    InvalidEntry(ParsedCourseEntry),
+    InvalidEntry(Box<ParsedCourseEntry>),
     #[error("parser has exhausted all input")]
     ParserExhausted,
     #[error("an error occurred when parsing: {0}")]
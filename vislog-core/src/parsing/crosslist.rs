@@ -0,0 +1,140 @@
+//! Course codes of cross-listed courses, found in the `crosslist_narrative` of a course or in
+//! sentences like "Also listed as SOC 318." in its description
+
+/// Phrases introducing the cross-listed courses in a description, lowercased
+const CROSSLIST_PHRASES: [&str; 5] = [
+    "also listed as",
+    "cross-listed as",
+    "cross-listed with",
+    "crosslisted with",
+    "same as",
+];
+
+/// Codes of the courses the course `own_code` is cross-listed with, in the order they appear and
+/// without duplicates. Ex: ["SOC 318"] for PSY 318
+pub fn parse_cross_listed(
+    own_code: &str,
+    crosslist_narrative: Option<&str>,
+    description: &str,
+) -> Vec<String> {
+    let description_sentences = description
+        .split(['.', ';', '<', '>', '\n'])
+        .filter(|sentence| {
+            let sentence = sentence.to_lowercase();
+            CROSSLIST_PHRASES
+                .iter()
+                .any(|phrase| sentence.contains(phrase))
+        });
+
+    let mut codes: Vec<String> = Vec::new();
+    for text in crosslist_narrative.into_iter().chain(description_sentences) {
+        for code in course_codes(text) {
+            if code != own_code && !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+    }
+
+    codes
+}
+
+/// Every course code in `text`, normalized to "SUBJ 123". A bare number following a code is
+/// taken to be of the same subject. Ex: "SOC 318/319" has SOC 318 and SOC 319
+fn course_codes(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '.' | ';' | '/' | '(' | ')' | ':'))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut codes = Vec::new();
+    let mut subject: Option<&str> = None;
+    let mut i = 0;
+    while i < words.len() {
+        let word = words[i];
+
+        // Written without a space. Ex: "SOC318"
+        if let Some(split) = word.find(|c: char| c.is_ascii_digit()) {
+            let (code_subject, number) = word.split_at(split);
+            if is_subject(code_subject) && is_number(number) {
+                codes.push(format!("{code_subject} {number}"));
+                subject = Some(code_subject);
+                i += 1;
+                continue;
+            }
+        }
+
+        match words.get(i + 1) {
+            Some(number) if is_subject(word) && is_number(number) => {
+                codes.push(format!("{word} {number}"));
+                subject = Some(word);
+                i += 2;
+            }
+            _ => {
+                match subject {
+                    Some(subject) if is_number(word) => codes.push(format!("{subject} {word}")),
+                    // Anything other than a conjunction ends the list of the subject
+                    _ if matches!(word, "and" | "or" | "&") => {}
+                    _ => subject = None,
+                }
+                i += 1;
+            }
+        }
+    }
+
+    codes
+}
+
+fn is_subject(word: &str) -> bool {
+    (2..=4).contains(&word.len()) && word.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Ex: "318", "150C" or "129IG"
+fn is_number(word: &str) -> bool {
+    let digits = word.chars().take_while(char::is_ascii_digit).count();
+    digits == 3 && word[digits..].chars().all(|c| c.is_ascii_uppercase())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_crosslist_narratives() {
+        assert_eq!(
+            parse_cross_listed("PSY 318", Some("Also listed as SOC 318."), ""),
+            vec!["SOC 318"]
+        );
+        assert_eq!(
+            parse_cross_listed("PSY 318", Some("PSY 318/SOC 318, ANT318"), ""),
+            vec!["SOC 318", "ANT 318"]
+        );
+        assert_eq!(
+            parse_cross_listed("HIS 350", Some("Same as PSC 350 and 351"), ""),
+            vec!["PSC 350", "PSC 351"]
+        );
+        assert!(parse_cross_listed("PSY 318", None, "").is_empty());
+    }
+
+    #[test]
+    fn only_parses_crosslisting_sentences_of_descriptions() {
+        let description = "<p>Prerequisite: PSY 211. A study of the family. Also listed as SOC \
+                           318.</p>";
+
+        assert_eq!(
+            parse_cross_listed("PSY 318", None, description),
+            vec!["SOC 318"]
+        );
+        assert!(
+            parse_cross_listed("MAT 201", None, "Prerequisite: MAT 111 or its equivalent")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parses_codes_with_and_without_spaces() {
+        assert_eq!(
+            course_codes("CMU 150C or SOC318 and 319 in 2023"),
+            vec!["CMU 150C", "SOC 318", "SOC 319"]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! General education categories from the `distribution` narrative of a course. Ex: "Core:
+//! Natural Science (Lab)" or "Humanities; Fine Arts"
+use crate::GenEdCategory;
+
+/// Lowercased phrases naming each category. Phrases of a category must not be part of the phrases
+/// of another (Ex: "science" would match "Social Science" too)
+const CATEGORY_PHRASES: [(GenEdCategory, &[&str]); 10] = [
+    (
+        GenEdCategory::ChristianStudies,
+        &["christian", "bible", "biblical", "religio", "theolog"],
+    ),
+    (
+        GenEdCategory::Communication,
+        &["communication", "composition", "writing", "speech"],
+    ),
+    (
+        GenEdCategory::FineArts,
+        &[
+            "fine art",
+            "aesthetic",
+            "art appreciation",
+            "music appreciation",
+        ],
+    ),
+    (
+        GenEdCategory::ForeignLanguage,
+        &["foreign language", "modern language", "world language"],
+    ),
+    (GenEdCategory::History, &["history", "historical"]),
+    (
+        GenEdCategory::Humanities,
+        &["humanities", "literature", "philosophy"],
+    ),
+    (
+        GenEdCategory::Mathematics,
+        &["math", "quantitative", "statistic"],
+    ),
+    (
+        GenEdCategory::NaturalScience,
+        &[
+            "natural science",
+            "physical science",
+            "life science",
+            "lab science",
+            "laboratory science",
+        ],
+    ),
+    (
+        GenEdCategory::SocialScience,
+        &[
+            "social science",
+            "behavioral science",
+            "social and behavioral",
+        ],
+    ),
+    (
+        GenEdCategory::Wellness,
+        &["wellness", "physical education", "health", "fitness"],
+    ),
+];
+
+/// The categories named in the narrative in the order they appear
+pub fn parse_distribution(distribution: &str) -> Vec<GenEdCategory> {
+    let distribution = distribution.to_lowercase();
+
+    let mut found: Vec<(usize, GenEdCategory)> = CATEGORY_PHRASES
+        .iter()
+        .filter_map(|(category, phrases)| {
+            phrases
+                .iter()
+                .filter_map(|phrase| distribution.find(phrase))
+                .min()
+                .map(|position| (position, *category))
+        })
+        .collect();
+    found.sort_by_key(|(position, _)| *position);
+
+    found.into_iter().map(|(_, category)| category).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_categories_in_order() {
+        assert_eq!(
+            parse_distribution("Core: Social Science; Natural Science (Lab)"),
+            vec![GenEdCategory::SocialScience, GenEdCategory::NaturalScience]
+        );
+        assert_eq!(
+            parse_distribution("Fulfills Biblical Studies and Written Communication"),
+            vec![
+                GenEdCategory::ChristianStudies,
+                GenEdCategory::Communication
+            ]
+        );
+        assert_eq!(
+            parse_distribution("Humanities - Literature"),
+            vec![GenEdCategory::Humanities]
+        );
+    }
+
+    #[test]
+    fn ignores_unknown_categories() {
+        assert!(parse_distribution("Elective").is_empty());
+        assert!(parse_distribution("").is_empty());
+    }
+}
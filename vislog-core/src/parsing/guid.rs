@@ -20,13 +20,12 @@ impl std::fmt::Debug for Guid {
         let hex_chars = self
             .inner
             .iter()
-            .map(|byte| {
+            .flat_map(|byte| {
                 let first_half = byte >> 4;
                 let second_half = byte & 0b00001111;
 
                 [format!("{:X}", first_half), format!("{:X}", second_half)]
             })
-            .flatten()
             .fold(String::new(), |mut acc, c| {
                 acc.push_str(&c);
                 acc
@@ -79,7 +78,7 @@ impl TryFrom<&str> for Guid {
 
         let mut inner = [0u8; 16];
 
-        for i in 0..16 {
+        for slot in inner.iter_mut() {
             let mut byte = 0u8;
             let mut byte_index = 0;
             while byte_index < 2 {
@@ -104,7 +103,7 @@ impl TryFrom<&str> for Guid {
                 }
             }
 
-            inner[i] = byte;
+            *slot = byte;
         }
 
         Ok(Self { inner })
@@ -120,6 +119,55 @@ impl Serialize for Guid {
     }
 }
 
+/// Serialized as an uppercase hyphenated string without braces
+#[cfg(feature = "openapi")]
+impl utoipa::PartialSchema for Guid {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        utoipa::openapi::ObjectBuilder::new()
+            .schema_type(utoipa::openapi::schema::Type::String)
+            .format(Some(utoipa::openapi::SchemaFormat::Custom(
+                "uuid".to_owned(),
+            )))
+            .examples(["5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5"])
+            .into()
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl utoipa::ToSchema for Guid {}
+
+/// Serialized as an uppercase hyphenated string without braces
+#[cfg(feature = "typescript")]
+impl ts_rs::TS for Guid {
+    type WithoutGenerics = Self;
+    const DOCS: Option<&'static str> =
+        Some("/**\n * Uppercase hyphenated GUID without braces. Ex: \"5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5\"\n */\n");
+
+    fn name() -> String {
+        "Guid".to_owned()
+    }
+
+    fn inline() -> String {
+        "string".to_owned()
+    }
+
+    fn inline_flattened() -> String {
+        panic!("Guid cannot be flattened")
+    }
+
+    fn decl() -> String {
+        "type Guid = string;".to_owned()
+    }
+
+    fn decl_concrete() -> String {
+        Self::decl()
+    }
+
+    fn output_path() -> Option<&'static std::path::Path> {
+        Some(std::path::Path::new("Guid.ts"))
+    }
+}
+
 // TODO: Implement deserialization for byte arrays and u128 integers
 impl<'de> Deserialize<'de> for Guid {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use serde::{
     de::{self, Visitor},
     Deserialize, Deserializer,
@@ -7,17 +5,24 @@ use serde::{
 use serde_json::Value;
 
 use crate::{
+    credits::{CreditHours, Credits},
     Course, CourseDetails, CourseEntries, CourseEntry, Label, Requirement, RequirementModule,
     Requirements,
 };
 
 use self::{
-    courses::{parse_course_credits, CoursesParser, RawCourseEntry},
+    courses::{CoursesParser, RawCourseEntry},
+    crosslist::parse_cross_listed,
+    distribution::parse_distribution,
     guid::Guid,
+    offered::parse_offered,
 };
 
 pub mod courses;
+pub mod crosslist;
+pub mod distribution;
 pub mod guid;
+pub mod offered;
 
 impl<'de> Deserialize<'de> for Requirements {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -309,7 +314,7 @@ impl<'de> Deserialize<'de> for CourseEntries {
                 let mut number: Option<Option<String>> = None;
                 let mut subject_name: Option<Option<String>> = None;
                 let mut subject_code: Option<Option<String>> = None;
-                let mut credits: Option<(u8, Option<u8>)> = None;
+                let mut credits: Option<Credits> = None;
                 let mut is_narrative: Option<bool> = None;
 
                 while let Ok(Some(key)) = map.next_key::<String>() {
@@ -376,8 +381,7 @@ impl<'de> Deserialize<'de> for CourseEntries {
                             }
 
                             let credits_str = map.next_value::<&str>()?;
-                            credits =
-                                Some(parse_course_credits(credits_str).map_err(de::Error::custom)?);
+                            credits = Some(credits_str.parse().map_err(de::Error::custom)?);
                         }
                         "is_narrative" => {
                             if is_narrative.is_some() {
@@ -487,6 +491,12 @@ impl<'de> Deserialize<'de> for CourseDetails {
                 let mut prerequisite: Option<Value> = None;
                 let mut corequisite_narrative: Option<Option<String>> = None;
                 let mut corequisite: Option<Value> = None;
+                let mut crosslist_narrative: Option<Option<String>> = None;
+                let mut credits_narrative: Option<Option<String>> = None;
+                let mut offered: Option<Option<String>> = None;
+                let mut distribution: Option<Option<String>> = None;
+                let mut instructor: Option<Option<String>> = None;
+                let mut notes: Option<Option<String>> = None;
 
                 while let Some(key) = map.next_key::<&str>()? {
                     match key {
@@ -574,6 +584,42 @@ impl<'de> Deserialize<'de> for CourseDetails {
                             }
                             corequisite = Some(map.next_value()?);
                         }
+                        "crosslist_narrative" => {
+                            if crosslist_narrative.is_some() {
+                                return Err(de::Error::duplicate_field("crosslist_narrative"));
+                            }
+                            crosslist_narrative = Some(map.next_value()?);
+                        }
+                        "credits_narrative" => {
+                            if credits_narrative.is_some() {
+                                return Err(de::Error::duplicate_field("credits_narrative"));
+                            }
+                            credits_narrative = Some(map.next_value()?);
+                        }
+                        "offered" => {
+                            if offered.is_some() {
+                                return Err(de::Error::duplicate_field("offered"));
+                            }
+                            offered = Some(map.next_value()?);
+                        }
+                        "distribution" => {
+                            if distribution.is_some() {
+                                return Err(de::Error::duplicate_field("distribution"));
+                            }
+                            distribution = Some(map.next_value()?);
+                        }
+                        "instructor" => {
+                            if instructor.is_some() {
+                                return Err(de::Error::duplicate_field("instructor"));
+                            }
+                            instructor = Some(map.next_value()?);
+                        }
+                        "notes" => {
+                            if notes.is_some() {
+                                return Err(de::Error::duplicate_field("notes"));
+                            }
+                            notes = Some(map.next_value()?);
+                        }
                         _ => {
                             let _ = map.next_value::<de::IgnoredAny>();
                         }
@@ -592,54 +638,51 @@ impl<'de> Deserialize<'de> for CourseDetails {
                 let corequisite_narrative = corequisite_narrative
                     .ok_or(de::Error::missing_field("corequisite_narrative"))?;
 
-                // Transform into integers
-                let credits_min = {
-                    let float_str = credits_min.ok_or(de::Error::missing_field("credits_min"))?;
-
-                    // NOTE: Assume credits equal zero when `credits_min` is `null` in JSON format
-                    if let Some(float_str) = float_str {
-                        let float: f32 = float_str.parse().map_err(|e| de::Error::custom(e))?;
-                        if float > u8::MAX as f32 {
-                            return Err(de::Error::custom(format!(
-                                "value of credits_max exceeded `u8::MAX` (255)"
-                            )));
-                        }
-                        float.trunc() as u8
-                    } else {
-                        0
-                    }
-                };
-
-                let credits_max = {
-                    let float_option =
-                        credits_max.ok_or(de::Error::missing_field("credits_max"))?;
-
-                    float_option
-                        .map(|float_str| float_str.parse::<f32>().map_err(|e| de::Error::custom(e)))
-                        .transpose()?
-                        .map(|float| {
-                            if float <= u8::MAX as f32 {
-                                Ok(float.trunc() as u8)
-                            } else {
-                                Err(de::Error::custom(format!(
-                                    "value of credits_max exceeded 255"
-                                )))
-                            }
-                        })
-                        .transpose()?
+                let credits_min = credits_min.ok_or(de::Error::missing_field("credits_min"))?;
+                let credits_max = credits_max.ok_or(de::Error::missing_field("credits_max"))?;
+                let credits_narrative = credits_narrative.flatten();
+
+                // NOTE: Some courses only have their credits in `credits_narrative`. Assume credits
+                // equal zero when they aren't anywhere in a format that can be parsed, like a
+                // narrative of "Variable" or "See department"
+                let credits = match (credits_min, credits_max, &credits_narrative) {
+                    (Some(min), Some(max), _) => Credits::range(
+                        min.parse().map_err(de::Error::custom)?,
+                        max.parse().map_err(de::Error::custom)?,
+                    ),
+                    (Some(min), None, _) => Credits::fixed(min.parse().map_err(de::Error::custom)?),
+                    (None, _, Some(narrative)) => narrative
+                        .parse()
+                        .unwrap_or(Credits::fixed(CreditHours::ZERO)),
+                    (None, _, None) => Credits::fixed(CreditHours::ZERO),
                 };
 
                 // These are optional fields
                 let prerequisite = prerequisite
-                    .map(|v| extract_guid_from_requisite(v).map_err(|e| de::Error::custom(e)))
+                    .map(|v| extract_guid_from_requisite(v).map_err(de::Error::custom))
                     .transpose()?;
                 let corequisite = corequisite
-                    .map(|v| extract_guid_from_requisite(v).map_err(|e| de::Error::custom(e)))
+                    .map(|v| extract_guid_from_requisite(v).map_err(de::Error::custom))
                     .transpose()?;
+                let crosslist_narrative = crosslist_narrative.flatten();
+                let offered = offered.flatten();
+                let offerings = offered.as_deref().map(parse_offered).unwrap_or_default();
+                let distribution = distribution.flatten();
+                let gen_ed = distribution
+                    .as_deref()
+                    .map(parse_distribution)
+                    .unwrap_or_default();
+                let instructor = instructor.flatten();
+                let notes = notes.flatten();
+                let cross_listed = parse_cross_listed(
+                    &format!("{subject_code} {number}"),
+                    crosslist_narrative.as_deref(),
+                    &description,
+                );
 
                 let guid_str = guid.ok_or(de::Error::missing_field("GUID"))?;
-                let guid = Guid::try_from(&guid_str[1..guid_str.len() - 1])
-                    .map_err(|e| de::Error::custom(e))?;
+                let guid =
+                    Guid::try_from(&guid_str[1..guid_str.len() - 1]).map_err(de::Error::custom)?;
 
                 // Construct CourseDetails
                 let course_details = CourseDetails {
@@ -650,13 +693,21 @@ impl<'de> Deserialize<'de> for CourseDetails {
                     subject_name,
                     number,
                     name,
-                    credits_min,
-                    credits_max,
+                    credits,
                     description,
                     prerequisite_narrative,
                     prerequisite,
                     corequisite_narrative,
                     corequisite,
+                    crosslist_narrative,
+                    cross_listed,
+                    credits_narrative,
+                    offered,
+                    offerings,
+                    distribution,
+                    gen_ed,
+                    instructor,
+                    notes,
                 };
 
                 Ok(course_details)
@@ -684,80 +735,3 @@ impl<'de> Deserialize<'de> for CourseDetails {
         deserializer.deserialize_map(CourseDetailsVisitor)
     }
 }
-
-pub(crate) fn deserialize_from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
-where
-    D: Deserializer<'de>,
-    T: FromStr,
-    <T as FromStr>::Err: std::fmt::Display,
-{
-    let s: String = Deserialize::deserialize(deserializer)?;
-    s.parse().map_err(|e| de::Error::custom(e))
-}
-
-pub(crate) fn deserialize_and_floor_u8_from_float_str<'de, D>(
-    deserializer: D,
-) -> Result<u8, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s: String = Deserialize::deserialize(deserializer)?;
-    let float: f32 = s
-        .parse()
-        .map_err(|e| de::Error::custom(format!("failed to parse f32, {e}")))?;
-    if float > 255.0 {
-        Err(de::Error::custom(format!(
-            "expected a value less than '255.0', instead got: {float}"
-        )))
-    } else {
-        Ok(float.trunc() as u8)
-    }
-}
-
-pub(crate) fn deserialize_extract_guid_only<'de, D>(
-    deserializer: D,
-) -> Result<Option<Guid>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct ExtractGuidVisitor;
-
-    impl<'d> Visitor<'d> for ExtractGuidVisitor {
-        type Value = Option<Guid>;
-
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-            formatter.write_str("a string representing a GUID surounded by curly braces")
-        }
-
-        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
-        where
-            A: de::MapAccess<'d>,
-        {
-            let mut guid: Option<String> = None;
-
-            while let Ok(Some(key)) = map.next_key::<&str>() {
-                match key {
-                    "GUID" => {
-                        guid = map.next_value()?;
-                        break;
-                    }
-                    _ => {
-                        let _ = map.next_value::<de::IgnoredAny>();
-                    }
-                }
-            }
-
-            match guid {
-                Some(s) if s.len() < 32 => {
-                    Err(de::Error::custom("string not long enough to be GUID"))
-                }
-                Some(s) => Ok(Some(
-                    Guid::try_from(&s[1..s.len() - 1]).map_err(|e| de::Error::custom(e))?,
-                )),
-                None => Ok(None),
-            }
-        }
-    }
-
-    deserializer.deserialize_map(ExtractGuidVisitor)
-}
@@ -0,0 +1,130 @@
+//! Terms in which a course is offered, from the `offered` narrative of a course. Ex: "Fall and
+//! Spring", "Spring of even years" or "Fall (odd years), every Summer"
+use crate::{Offering, Term, Years};
+
+/// The terms found in the narrative, each with the years it applies to. A year pattern applies to
+/// the terms before it that don't have one yet, or to the terms after it when there are none
+/// before it. Terms left without one are offered every year
+pub fn parse_offered(offered: &str) -> Vec<Offering> {
+    let mut offerings: Vec<Offering> = Vec::new();
+    // Terms waiting to know their years
+    let mut pending: Vec<Term> = Vec::new();
+    // Years written before the terms they apply to. Ex: "Odd-year Fall"
+    let mut leading_years: Option<Years> = None;
+
+    let lowercase = offered.to_lowercase();
+    let words = lowercase
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty());
+    for word in words {
+        if let Some(term) = term(word) {
+            match leading_years {
+                Some(years) => push(&mut offerings, term, years),
+                None => pending.push(term),
+            }
+            continue;
+        }
+
+        let years = match word {
+            "odd" => Years::Odd,
+            "even" => Years::Even,
+            "every" | "each" | "annually" | "yearly" => {
+                // Ends the reach of the years written before. Ex: "Odd Fall, every Spring"
+                leading_years = None;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if pending.is_empty() {
+            leading_years = Some(years);
+        } else {
+            for term in pending.drain(..) {
+                push(&mut offerings, term, years);
+            }
+        }
+    }
+
+    for term in pending {
+        push(&mut offerings, term, Years::Every);
+    }
+
+    offerings
+}
+
+fn term(word: &str) -> Option<Term> {
+    match word {
+        "fall" | "autumn" => Some(Term::Fall),
+        "spring" => Some(Term::Spring),
+        "summer" => Some(Term::Summer),
+        _ => None,
+    }
+}
+
+fn push(offerings: &mut Vec<Offering>, term: Term, years: Years) {
+    let offering = Offering { term, years };
+    if !offerings.contains(&offering) {
+        offerings.push(offering);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn offering(term: Term, years: Years) -> Offering {
+        Offering { term, years }
+    }
+
+    #[test]
+    fn parses_terms_offered_every_year() {
+        assert_eq!(
+            parse_offered("Fall and Spring"),
+            vec![
+                offering(Term::Fall, Years::Every),
+                offering(Term::Spring, Years::Every)
+            ]
+        );
+        assert_eq!(
+            parse_offered("Every Summer"),
+            vec![offering(Term::Summer, Years::Every)]
+        );
+        assert!(parse_offered("On demand").is_empty());
+    }
+
+    #[test]
+    fn applies_years_to_the_terms_they_follow() {
+        assert_eq!(
+            parse_offered("Fall and Spring, odd years"),
+            vec![
+                offering(Term::Fall, Years::Odd),
+                offering(Term::Spring, Years::Odd)
+            ]
+        );
+        assert_eq!(
+            parse_offered("Fall of even years and every Spring"),
+            vec![
+                offering(Term::Fall, Years::Even),
+                offering(Term::Spring, Years::Every)
+            ]
+        );
+        assert_eq!(
+            parse_offered("Fall (odd years); Spring (even years)"),
+            vec![
+                offering(Term::Fall, Years::Odd),
+                offering(Term::Spring, Years::Even)
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_leading_years_to_the_terms_after_them() {
+        assert_eq!(
+            parse_offered("Odd-year Fall, every Summer"),
+            vec![
+                offering(Term::Fall, Years::Odd),
+                offering(Term::Summer, Years::Every)
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Plans of the terms in which a student takes the courses of a program
+use serde::{Deserialize, Serialize};
+
+use crate::{parsing::guid::Guid, Term};
+
+#[cfg(feature = "pdf")]
+pub mod pdf;
+
+/// The courses a student takes each term, in the order the terms are taken
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct Plan {
+    pub terms: Vec<PlanTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[cfg_attr(feature = "typescript", derive(ts_rs::TS))]
+pub struct PlanTerm {
+    /// Ex: "Fall 2024" or "Year 1 - Spring"
+    pub name: String,
+    /// GUIDs of the courses taken during the term
+    pub courses: Vec<Guid>,
+}
+
+impl Plan {
+    /// Every course of every term
+    pub fn courses(&self) -> impl Iterator<Item = &Guid> {
+        self.terms.iter().flat_map(|term| term.courses.iter())
+    }
+}
+
+impl PlanTerm {
+    /// The season of the term and its year when the name has one. Ex: (Fall, Some(2024)) for "Fall
+    /// 2024" and (Spring, None) for "Year 1 - Spring"
+    pub fn term(&self) -> Option<(Term, Option<i32>)> {
+        let words: Vec<String> = self
+            .name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .map(str::to_lowercase)
+            .collect();
+
+        let term = words.iter().find_map(|word| match word.as_str() {
+            "fall" | "autumn" => Some(Term::Fall),
+            "spring" => Some(Term::Spring),
+            "summer" => Some(Term::Summer),
+            _ => None,
+        })?;
+        let year = words
+            .iter()
+            .filter(|word| word.len() == 4)
+            .find_map(|word| word.parse().ok());
+
+        Some((term, year))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn term(name: &str) -> Option<(Term, Option<i32>)> {
+        PlanTerm {
+            name: name.to_owned(),
+            courses: Vec::new(),
+        }
+        .term()
+    }
+
+    #[test]
+    fn parses_term_names() {
+        assert_eq!(term("Fall 2024"), Some((Term::Fall, Some(2024))));
+        assert_eq!(term("Year 1 - Spring"), Some((Term::Spring, None)));
+        assert_eq!(
+            term("2025 summer session"),
+            Some((Term::Summer, Some(2025)))
+        );
+        assert_eq!(term("Term 3"), None);
+    }
+}
@@ -0,0 +1,791 @@
+//! Printable PDF of a program with its requirements as a checklist and, when there is one, the
+//! [Plan] of the terms its courses are taken in.
+//!
+//! Only the standard Helvetica fonts that every PDF reader ships with are used, so no font has to
+//! be embedded. Text is measured with their metrics to wrap it and to break it into pages.
+use std::collections::{HashMap, HashSet};
+
+use pdf_writer::{
+    types::{ActionType, AnnotationType, BorderType},
+    Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr,
+};
+
+use crate::{
+    credits::Credits, equivalence::Equivalences, parsing::guid::Guid, CourseDetails, CourseEntries,
+    CourseEntry, Program,
+};
+
+use super::Plan;
+
+/// US Letter
+const PAGE_WIDTH: f32 = 612.0;
+const PAGE_HEIGHT: f32 = 792.0;
+const MARGIN: f32 = 54.0;
+const LINE_HEIGHT: f32 = 1.35;
+const INDENT: f32 = 14.0;
+const CHECKBOX_SIZE: f32 = 8.0;
+/// Width of the right aligned column holding credit hours
+const CREDITS_WIDTH: f32 = 54.0;
+const FOOTER_SIZE: f32 = 8.0;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+/// `plan` is optional, the courses it holds don't have to be part of the program. `courses` holds
+/// the details of the courses in the program and in the plan, the credits listed in the program
+/// are used for the ones missing.
+pub fn to_pdf(
+    program: &Program,
+    courses: &HashMap<Guid, CourseDetails>,
+    plan: Option<&Plan>,
+) -> Vec<u8> {
+    let mut document = Document::default();
+
+    document.push(Line::new(&program.title, Font::Bold, 18.0));
+    document.push(Line {
+        link: Some(program.url.clone()),
+        space_before: 4.0,
+        ..Line::new(&program.url, Font::Regular, 8.0)
+    });
+
+    let planned = planned_courses(plan, courses);
+    if let Some(plan) = plan {
+        document.plan(plan, program, courses);
+    }
+    document.checklist(program, courses, &planned);
+
+    document.render(&program.title)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn name(self) -> Name<'static> {
+        match self {
+            Font::Regular => REGULAR,
+            Font::Bold => BOLD,
+        }
+    }
+}
+
+/// A line of the document before it's broken into pages
+#[derive(Debug, Clone)]
+struct Line {
+    text: String,
+    font: Font,
+    size: f32,
+    indent: f32,
+    space_before: f32,
+    /// Credit hours written in a right aligned column
+    credits: Option<String>,
+    /// Drawn before the text, ticked if `true`
+    checkbox: Option<bool>,
+    link: Option<String>,
+}
+
+impl Line {
+    fn new(text: &str, font: Font, size: f32) -> Self {
+        Self {
+            text: text.to_owned(),
+            font,
+            size,
+            indent: 0.0,
+            space_before: 0.0,
+            credits: None,
+            checkbox: None,
+            link: None,
+        }
+    }
+
+    fn text_x(&self) -> f32 {
+        let checkbox_width = if self.checkbox.is_some() {
+            CHECKBOX_SIZE + 6.0
+        } else {
+            0.0
+        };
+
+        MARGIN + self.indent + checkbox_width
+    }
+
+    fn text_width(&self) -> f32 {
+        let credits_width = if self.credits.is_some() {
+            CREDITS_WIDTH
+        } else {
+            0.0
+        };
+
+        PAGE_WIDTH - MARGIN - self.text_x() - credits_width
+    }
+}
+
+#[derive(Debug, Default)]
+struct Document {
+    lines: Vec<Line>,
+}
+
+impl Document {
+    /// Wraps the text of the line, the lines it continues on keep its indentation
+    fn push(&mut self, line: Line) {
+        let wrapped = wrap(&line.text, line.font, line.size, line.text_width());
+
+        for (i, text) in wrapped.into_iter().enumerate() {
+            if i == 0 {
+                self.lines.push(Line {
+                    text,
+                    ..line.clone()
+                });
+                continue;
+            }
+
+            self.lines.push(Line {
+                text,
+                indent: line.text_x() - MARGIN,
+                space_before: 0.0,
+                credits: None,
+                checkbox: None,
+                ..line.clone()
+            });
+        }
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.push(Line {
+            space_before: 16.0,
+            ..Line::new(text, Font::Bold, 14.0)
+        });
+    }
+
+    fn plan(&mut self, plan: &Plan, program: &Program, courses: &HashMap<Guid, CourseDetails>) {
+        self.heading("Plan");
+
+        let mut total = Credits::default();
+        for term in &plan.terms {
+            let credits: Vec<Credits> = term
+                .courses
+                .iter()
+                .map(|guid| course_credits(guid, program, courses))
+                .collect();
+            let term_total = credits.iter().copied().sum::<Credits>();
+            total = total + term_total;
+
+            self.push(Line {
+                space_before: 8.0,
+                credits: Some(format_credits(term_total)),
+                ..Line::new(&term.name, Font::Bold, 11.0)
+            });
+
+            let season = term.term();
+            for (guid, credits) in term.courses.iter().zip(credits) {
+                let text = match courses.get(guid) {
+                    Some(course) => {
+                        let offered = season
+                            .and_then(|(season, year)| course.is_offered_in(season, year))
+                            .unwrap_or(true);
+                        let mut text = format!("{}  {}", course.code(), course.name);
+                        if !offered {
+                            text.push_str("  (not offered this term)");
+                        }
+                        text
+                    }
+                    None => format!("Unknown course {guid}"),
+                };
+
+                self.push(Line {
+                    indent: INDENT,
+                    credits: credits.is_known().then(|| format_credits(credits)),
+                    ..Line::new(&text, Font::Regular, 10.0)
+                });
+            }
+        }
+
+        self.push(Line {
+            space_before: 8.0,
+            credits: Some(format_credits(total)),
+            ..Line::new("Total credit hours", Font::Bold, 11.0)
+        });
+    }
+
+    /// Every requirement of the program with a checkbox per course, ticked for the planned ones
+    /// and the ones cross-listed with them
+    fn checklist(
+        &mut self,
+        program: &Program,
+        courses: &HashMap<Guid, CourseDetails>,
+        planned: &HashSet<Guid>,
+    ) {
+        self.heading("Requirement checklist");
+
+        let modules = program.requirement_modules();
+        if modules.is_empty() {
+            self.push(Line {
+                space_before: 4.0,
+                ..Line::new(
+                    "See the catalog for the requirements of this program.",
+                    Font::Regular,
+                    10.0,
+                )
+            });
+        }
+
+        for module in modules {
+            self.push(Line {
+                space_before: 10.0,
+                ..Line::new(module.title().unwrap_or("Requirements"), Font::Bold, 12.0)
+            });
+
+            for requirement in module.requirements() {
+                self.push(Line {
+                    indent: INDENT,
+                    space_before: 6.0,
+                    ..Line::new(
+                        requirement.title().unwrap_or("Requirement"),
+                        Font::Bold,
+                        10.0,
+                    )
+                });
+
+                if let crate::Requirement::Label {
+                    req_narrative: Some(narrative),
+                    ..
+                } = requirement
+                {
+                    self.push(Line {
+                        indent: INDENT * 2.0,
+                        ..Line::new(narrative, Font::Regular, 9.0)
+                    });
+                }
+
+                if let Some(entries) = requirement.course_entries() {
+                    self.entries(entries, INDENT * 2.0, courses, planned);
+                }
+            }
+        }
+    }
+
+    fn entries(
+        &mut self,
+        entries: &CourseEntries,
+        indent: f32,
+        courses: &HashMap<Guid, CourseDetails>,
+        planned: &HashSet<Guid>,
+    ) {
+        for entry in entries.iter() {
+            match entry {
+                CourseEntry::And(entries) | CourseEntry::Or(entries) => {
+                    let text = match entry {
+                        CourseEntry::And(_) => "All of:",
+                        _ => "One of:",
+                    };
+
+                    self.push(Line {
+                        indent,
+                        ..Line::new(text, Font::Regular, 10.0)
+                    });
+                    self.entries(entries, indent + INDENT, courses, planned);
+                }
+                CourseEntry::Course(course) => {
+                    let details = courses.get(&course.guid);
+                    let name = details
+                        .map(|details| details.name.as_str())
+                        .or(course.name.as_deref())
+                        .unwrap_or_default();
+                    let credits = details.map_or(course.credits, |details| details.credits);
+
+                    self.push(Line {
+                        indent,
+                        checkbox: Some(planned.contains(&course.guid)),
+                        credits: credits.is_known().then(|| format_credits(credits)),
+                        ..Line::new(
+                            format!("{}  {name}", course.code()).trim_end(),
+                            Font::Regular,
+                            10.0,
+                        )
+                    });
+                }
+                CourseEntry::Label(label) => {
+                    let credits = label.credits;
+
+                    self.push(Line {
+                        indent,
+                        checkbox: Some(false),
+                        credits: credits.is_known().then(|| format_credits(credits)),
+                        ..Line::new(&label.name, Font::Regular, 10.0)
+                    });
+                }
+            }
+        }
+    }
+
+    /// Breaks the lines into pages and writes them with a footer on every page
+    fn render(self, title: &str) -> Vec<u8> {
+        let mut pages: Vec<Vec<(f32, Line)>> = vec![Vec::new()];
+        let mut y = PAGE_HEIGHT - MARGIN;
+        for line in self.lines {
+            let height = line.size * LINE_HEIGHT;
+            if y - line.space_before - height < MARGIN {
+                pages.push(Vec::new());
+                y = PAGE_HEIGHT - MARGIN;
+            } else {
+                y -= line.space_before;
+            }
+
+            y -= height;
+            pages
+                .last_mut()
+                .expect("there's always a page")
+                .push((y, line));
+        }
+
+        let mut pdf = Pdf::new();
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let info_id = Ref::new(3);
+        let regular_id = Ref::new(4);
+        let bold_id = Ref::new(5);
+        let page_ids: Vec<(Ref, Ref)> = (0..pages.len() as i32)
+            .map(|i| (Ref::new(6 + i * 2), Ref::new(7 + i * 2)))
+            .collect();
+
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+            .count(pages.len() as i32);
+        pdf.document_info(info_id)
+            .title(TextStr(title))
+            .creator(TextStr("vislog"));
+        for (id, base_font) in [(regular_id, "Helvetica"), (bold_id, "Helvetica-Bold")] {
+            pdf.type1_font(id)
+                .base_font(Name(base_font.as_bytes()))
+                .encoding_predefined(Name(b"WinAnsiEncoding"));
+        }
+
+        let page_count = pages.len();
+        for (i, (lines, (page_id, content_id))) in pages.into_iter().zip(page_ids).enumerate() {
+            let mut content = Content::new();
+            for (y, line) in &lines {
+                write_line(&mut content, *y, line);
+            }
+
+            let page_number = format!("Page {} of {page_count}", i + 1);
+            let page_number_x =
+                PAGE_WIDTH - MARGIN - text_width(&page_number, Font::Regular, FOOTER_SIZE);
+            content.set_fill_gray(0.4);
+            write_text(
+                &mut content,
+                MARGIN,
+                MARGIN / 2.0,
+                Font::Regular,
+                FOOTER_SIZE,
+                title,
+            );
+            write_text(
+                &mut content,
+                page_number_x,
+                MARGIN / 2.0,
+                Font::Regular,
+                FOOTER_SIZE,
+                &page_number,
+            );
+            pdf.stream(content_id, &content.finish());
+
+            let mut page = pdf.page(page_id);
+            page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+            page.parent(page_tree_id);
+            page.contents(content_id);
+            page.resources()
+                .fonts()
+                .pair(REGULAR, regular_id)
+                .pair(BOLD, bold_id);
+
+            let links: Vec<&(f32, Line)> = lines
+                .iter()
+                .filter(|(_, line)| line.link.is_some())
+                .collect();
+            if !links.is_empty() {
+                let mut annotations = page.annotations();
+                for (y, line) in links {
+                    let x = line.text_x();
+                    let width = text_width(&line.text, line.font, line.size);
+
+                    let mut annotation = annotations.push();
+                    annotation.subtype(AnnotationType::Link);
+                    annotation.rect(Rect::new(x, y - 2.0, x + width, y + line.size));
+                    annotation
+                        .action()
+                        .action_type(ActionType::Uri)
+                        .uri(Str(line.link.as_deref().unwrap_or_default().as_bytes()));
+                    annotation
+                        .border_style()
+                        .width(0.0)
+                        .style(BorderType::Underline);
+                }
+                annotations.finish();
+            }
+            page.finish();
+        }
+
+        pdf.finish()
+    }
+}
+
+fn write_line(content: &mut Content, y: f32, line: &Line) {
+    if let Some(checked) = line.checkbox {
+        let x = MARGIN + line.indent;
+        content.set_stroke_gray(0.0);
+        content.set_line_width(0.6);
+        content.rect(x, y - 1.0, CHECKBOX_SIZE, CHECKBOX_SIZE);
+        content.stroke();
+
+        if checked {
+            content.move_to(x + 1.5, y + 3.0);
+            content.line_to(x + 3.5, y + 0.5);
+            content.line_to(x + 7.0, y + 6.5);
+            content.stroke();
+        }
+    }
+
+    if line.link.is_some() {
+        content.set_fill_rgb(0.1, 0.3, 0.7);
+    } else {
+        content.set_fill_gray(0.0);
+    }
+    write_text(content, line.text_x(), y, line.font, line.size, &line.text);
+
+    if let Some(credits) = &line.credits {
+        let x = PAGE_WIDTH - MARGIN - text_width(credits, line.font, line.size);
+        write_text(content, x, y, line.font, line.size, credits);
+    }
+}
+
+fn write_text(content: &mut Content, x: f32, y: f32, font: Font, size: f32, text: &str) {
+    content.begin_text();
+    content.set_font(font.name(), size);
+    content.next_line(x, y);
+    content.show(Str(&encode(text)));
+    content.end_text();
+}
+
+/// Credit hours with an en dash between the bounds of variable credits. Ex: "3" or "1–4"
+fn format_credits(credits: Credits) -> String {
+    if credits.variable {
+        format!("{}–{}", credits.min, credits.max)
+    } else {
+        credits.min.to_string()
+    }
+}
+
+/// GUIDs of the planned courses along with the ones cross-listed with them, so that planning
+/// SOC 318 checks off PSY 318
+fn planned_courses(plan: Option<&Plan>, courses: &HashMap<Guid, CourseDetails>) -> HashSet<Guid> {
+    let equivalences = Equivalences::new(courses.values());
+
+    plan.into_iter()
+        .flat_map(Plan::courses)
+        .flat_map(|guid| std::iter::once(*guid).chain(equivalences.equivalents(guid)))
+        .collect()
+}
+
+/// Credits of a planned course, from the program if its details are missing
+fn course_credits(
+    guid: &Guid,
+    program: &Program,
+    courses: &HashMap<Guid, CourseDetails>,
+) -> Credits {
+    if let Some(course) = courses.get(guid) {
+        return course.credits;
+    }
+
+    program
+        .courses()
+        .into_iter()
+        .find(|course| &course.guid == guid)
+        .map(|course| course.credits)
+        .unwrap_or_default()
+}
+
+/// Breaks `text` into lines no wider than `width`, splitting words only if they don't fit on a
+/// line of their own
+fn wrap(text: &str, font: Font, size: f32, width: f32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let candidate = if current.is_empty() {
+            word.to_owned()
+        } else {
+            format!("{current} {word}")
+        };
+
+        if text_width(&candidate, font, size) <= width {
+            current = candidate;
+            continue;
+        }
+
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+
+        // Long URLs and the like are split wherever they stop fitting
+        for c in word.chars() {
+            current.push(c);
+            if text_width(&current, font, size) > width && current.chars().count() > 1 {
+                current.pop();
+                lines.push(std::mem::take(&mut current));
+                current.push(c);
+            }
+        }
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Width in points of `text` written in `font` at `size`
+fn text_width(text: &str, font: Font, size: f32) -> f32 {
+    let widths = match font {
+        Font::Regular => &HELVETICA_WIDTHS,
+        Font::Bold => &HELVETICA_BOLD_WIDTHS,
+    };
+
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => widths[c as usize - ' ' as usize] as u32,
+            '—' => 1000,
+            _ => 556,
+        })
+        .sum();
+
+    units as f32 * size / 1000.0
+}
+
+/// Text in the WinAnsiEncoding of the standard fonts, with the characters it's missing replaced
+/// by `?`
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '™' => 0x99,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Advance widths of the printable ASCII characters in thousandths of the font size, from the
+/// Adobe font metrics of Helvetica
+#[rustfmt::skip]
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Same as [HELVETICA_WIDTHS] for Helvetica-Bold
+#[rustfmt::skip]
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        credits::CreditHours,
+        graph::test::{cs_courses, cs_major},
+        plan::PlanTerm,
+        Offering, Term, Years,
+    };
+
+    use super::*;
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+
+    #[test]
+    fn renders_checklist_without_plan() {
+        let program = cs_major();
+        let pdf = to_pdf(&program, &cs_courses(&program), None);
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(contains(&pdf, b"/Helvetica-Bold"));
+        assert!(contains(&pdf, b"(Requirement checklist)"));
+        // Written as a hex string because of the em dash
+        let title: String = encode(&program.title)
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect();
+        assert!(contains(&pdf, format!("<{title}>").as_bytes()));
+        assert!(contains(&pdf, b"/URI"));
+        assert!(!contains(&pdf, b"(Plan)"));
+    }
+
+    #[test]
+    fn renders_plan_with_credit_totals_per_term() {
+        let program = cs_major();
+        let courses = cs_courses(&program);
+        let first = program.courses()[0];
+        let plan = Plan {
+            terms: vec![PlanTerm {
+                name: "Fall 2024".to_owned(),
+                courses: vec![first.guid, first.guid],
+            }],
+        };
+
+        let pdf = to_pdf(&program, &courses, Some(&plan));
+        let credits = courses[&first.guid].credits + courses[&first.guid].credits;
+
+        assert!(contains(&pdf, b"(Plan)"));
+        assert!(contains(&pdf, b"(Fall 2024)"));
+        assert!(contains(
+            &pdf,
+            format!("({})", format_credits(credits)).as_bytes()
+        ));
+        assert!(contains(&pdf, b"(Total credit hours)"));
+    }
+
+    #[test]
+    fn breaks_long_plans_into_pages() {
+        let program = cs_major();
+        let courses = cs_courses(&program);
+        let plan = Plan {
+            terms: (0..40)
+                .map(|i| PlanTerm {
+                    name: format!("Term {i}"),
+                    courses: program.courses().iter().map(|c| c.guid).take(5).collect(),
+                })
+                .collect(),
+        };
+
+        let pdf = to_pdf(&program, &courses, Some(&plan));
+
+        assert!(contains(&pdf, b"Page 3 of "));
+    }
+
+    #[test]
+    fn plans_check_off_cross_listed_courses() {
+        let program = cs_major();
+        let mut courses = cs_courses(&program);
+        let first = program.courses()[0];
+        let cross_listed = CourseDetails {
+            guid: Guid::try_from("00000000-0000-0000-0000-000000000002").unwrap(),
+            subject_code: "XYZ".to_owned(),
+            number: "999".to_owned(),
+            cross_listed: vec![first.code()],
+            ..courses[&first.guid].clone()
+        };
+        courses.insert(cross_listed.guid, cross_listed.clone());
+
+        let plan = Plan {
+            terms: vec![PlanTerm {
+                name: "Fall 2024".to_owned(),
+                courses: vec![cross_listed.guid],
+            }],
+        };
+
+        assert_eq!(
+            planned_courses(Some(&plan), &courses),
+            HashSet::from([cross_listed.guid, first.guid])
+        );
+        assert!(planned_courses(None, &courses).is_empty());
+    }
+
+    #[test]
+    fn marks_courses_planned_when_not_offered() {
+        let program = cs_major();
+        let mut courses = cs_courses(&program);
+        let first = program.courses()[0];
+        courses.get_mut(&first.guid).unwrap().offerings = vec![Offering {
+            term: Term::Fall,
+            years: Years::Odd,
+        }];
+
+        let plan = |name: &str| Plan {
+            terms: vec![PlanTerm {
+                name: name.to_owned(),
+                courses: vec![first.guid],
+            }],
+        };
+
+        let marker = b"not offered this term";
+        assert!(contains(
+            &to_pdf(&program, &courses, Some(&plan("Fall 2024"))),
+            marker
+        ));
+        assert!(contains(
+            &to_pdf(&program, &courses, Some(&plan("Spring 2025"))),
+            marker
+        ));
+        assert!(!contains(
+            &to_pdf(&program, &courses, Some(&plan("Fall 2025"))),
+            marker
+        ));
+        assert!(!contains(
+            &to_pdf(&program, &courses, Some(&plan("Term 1"))),
+            marker
+        ));
+    }
+
+    #[test]
+    fn wraps_text_to_width() {
+        let lines = wrap(
+            "Computer Science II: Algorithms and Data Structures",
+            Font::Regular,
+            10.0,
+            120.0,
+        );
+
+        assert!(lines.len() > 1);
+        assert!(lines
+            .iter()
+            .all(|line| text_width(line, Font::Regular, 10.0) <= 120.0));
+        assert_eq!(
+            lines.join(" "),
+            "Computer Science II: Algorithms and Data Structures"
+        );
+
+        assert_eq!(wrap("", Font::Regular, 10.0, 120.0), vec![String::new()]);
+    }
+
+    #[test]
+    fn encodes_win_ansi() {
+        assert_eq!(encode("A—B–é✓"), b"A\x97B\x96\xe9?");
+    }
+
+    #[test]
+    fn formats_credit_ranges() {
+        let three = Credits::fixed(CreditHours::whole(3));
+        let one_to_three = Credits::range(CreditHours::whole(1), CreditHours::whole(3));
+        let half = Credits::fixed(CreditHours::from_hundredths(50));
+
+        assert_eq!(format_credits(three), "3");
+        assert_eq!(format_credits(one_to_three), "1–3");
+        assert_eq!(format_credits(three + one_to_three), "4–6");
+        assert_eq!(format_credits(three + half), "3.5");
+    }
+}
@@ -0,0 +1,370 @@
+//! Reading items back from the JSON they were serialized into.
+//!
+//! The `Deserialize` implementations of [Program] and [CourseDetails] read the JSON of the catalog
+//! API, which is shaped nothing like what they serialize into. Wrapping them in [Stored] reads
+//! their serialized form instead, so that parsed items can be saved and loaded without parsing
+//! the catalog JSON again.
+//!
+//! ```
+//! # use vislog_core::{stored::Stored, Program};
+//! # let program_json = std::fs::read_to_string("../data/cs_major.json").unwrap();
+//! let program: Program = serde_json::from_str(&program_json).unwrap();
+//! let saved = serde_json::to_string(&program).unwrap();
+//!
+//! let Stored(loaded) = serde_json::from_str::<Stored<Program>>(&saved).unwrap();
+//! assert_eq!(loaded, program);
+//! ```
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::{
+    credits::Credits, parsing::guid::Guid, Course, CourseDetails, CourseEntries, CourseEntry,
+    GenEdCategory, Label, Offering, Program, Requirement, RequirementModule, Requirements,
+};
+
+/// An item deserialized from the JSON it was serialized into
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stored<T>(pub T);
+
+impl<'de> Deserialize<'de> for Stored<Program> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        StoredProgram::deserialize(deserializer).map(|program| Stored(program.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Stored<CourseDetails> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        StoredCourseDetails::deserialize(deserializer).map(|course| Stored(course.into()))
+    }
+}
+
+// The types below mirror the serialized form of the types they are named after
+
+#[derive(Deserialize)]
+struct StoredProgram {
+    url: String,
+    guid: Guid,
+    title: String,
+    content: Option<String>,
+    bottom_content: Option<String>,
+    requirements: Option<StoredRequirements>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data")]
+enum StoredRequirements {
+    Single(StoredRequirementModule),
+    Many(Vec<StoredRequirementModule>),
+    SelectTrack,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data")]
+enum StoredRequirementModule {
+    SingleBasicRequirement {
+        title: Option<String>,
+        requirement: StoredRequirement,
+    },
+    BasicRequirements {
+        title: Option<String>,
+        requirements: Vec<StoredRequirement>,
+    },
+    SelectOneEmphasis {
+        emphases: Vec<StoredRequirement>,
+    },
+    Label {
+        title: String,
+    },
+    Unimplemented(Value),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data")]
+enum StoredRequirement {
+    Courses {
+        title: Option<String>,
+        courses: StoredCourseEntries,
+    },
+    SelectFromCourses {
+        title: String,
+        courses: Option<StoredCourseEntries>,
+    },
+    Label {
+        title: Option<String>,
+        req_narrative: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct StoredCourseEntries(Vec<StoredCourseEntry>);
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data")]
+enum StoredCourseEntry {
+    And(StoredCourseEntries),
+    Or(StoredCourseEntries),
+    Label(StoredLabel),
+    Course(StoredCourse),
+}
+
+#[derive(Deserialize)]
+struct StoredCourse {
+    url: String,
+    path: String,
+    guid: Guid,
+    name: Option<String>,
+    number: String,
+    subject_name: Option<String>,
+    subject_code: String,
+    credits: Credits,
+}
+
+#[derive(Deserialize)]
+struct StoredLabel {
+    url: String,
+    guid: Guid,
+    name: String,
+    number: Option<String>,
+    subject_code: Option<String>,
+    credits: Credits,
+}
+
+#[derive(Deserialize)]
+struct StoredCourseDetails {
+    url: String,
+    guid: Guid,
+    path: String,
+    subject_code: String,
+    subject_name: Option<String>,
+    number: String,
+    name: String,
+    credits: Credits,
+    description: String,
+    prerequisite_narrative: Option<String>,
+    prerequisite: Option<Guid>,
+    corequisite_narrative: Option<String>,
+    corequisite: Option<Guid>,
+    crosslist_narrative: Option<String>,
+    cross_listed: Vec<String>,
+    credits_narrative: Option<String>,
+    offered: Option<String>,
+    offerings: Vec<Offering>,
+    distribution: Option<String>,
+    gen_ed: Vec<GenEdCategory>,
+    instructor: Option<String>,
+    notes: Option<String>,
+}
+
+impl From<StoredProgram> for Program {
+    fn from(program: StoredProgram) -> Self {
+        Self {
+            url: program.url,
+            guid: program.guid,
+            title: program.title,
+            content: program.content,
+            bottom_content: program.bottom_content,
+            requirements: program.requirements.map(Requirements::from),
+        }
+    }
+}
+
+impl From<StoredRequirements> for Requirements {
+    fn from(requirements: StoredRequirements) -> Self {
+        match requirements {
+            StoredRequirements::Single(module) => Self::Single(module.into()),
+            StoredRequirements::Many(modules) => {
+                Self::Many(modules.into_iter().map(RequirementModule::from).collect())
+            }
+            StoredRequirements::SelectTrack => Self::SelectTrack,
+        }
+    }
+}
+
+impl From<StoredRequirementModule> for RequirementModule {
+    fn from(module: StoredRequirementModule) -> Self {
+        let requirements = |requirements: Vec<StoredRequirement>| {
+            requirements.into_iter().map(Requirement::from).collect()
+        };
+
+        match module {
+            StoredRequirementModule::SingleBasicRequirement { title, requirement } => {
+                Self::SingleBasicRequirement {
+                    title,
+                    requirement: requirement.into(),
+                }
+            }
+            StoredRequirementModule::BasicRequirements {
+                title,
+                requirements: reqs,
+            } => Self::BasicRequirements {
+                title,
+                requirements: requirements(reqs),
+            },
+            StoredRequirementModule::SelectOneEmphasis { emphases } => Self::SelectOneEmphasis {
+                emphases: requirements(emphases),
+            },
+            StoredRequirementModule::Label { title } => Self::Label { title },
+            StoredRequirementModule::Unimplemented(value) => Self::Unimplemented(value),
+        }
+    }
+}
+
+impl From<StoredRequirement> for Requirement {
+    fn from(requirement: StoredRequirement) -> Self {
+        match requirement {
+            StoredRequirement::Courses { title, courses } => Self::Courses {
+                title,
+                courses: courses.into(),
+            },
+            StoredRequirement::SelectFromCourses { title, courses } => Self::SelectFromCourses {
+                title,
+                courses: courses.map(CourseEntries::from),
+            },
+            StoredRequirement::Label {
+                title,
+                req_narrative,
+            } => Self::Label {
+                title,
+                req_narrative,
+            },
+        }
+    }
+}
+
+impl From<StoredCourseEntries> for CourseEntries {
+    fn from(entries: StoredCourseEntries) -> Self {
+        Self(entries.0.into_iter().map(CourseEntry::from).collect())
+    }
+}
+
+impl From<StoredCourseEntry> for CourseEntry {
+    fn from(entry: StoredCourseEntry) -> Self {
+        match entry {
+            StoredCourseEntry::And(entries) => Self::And(entries.into()),
+            StoredCourseEntry::Or(entries) => Self::Or(entries.into()),
+            StoredCourseEntry::Label(label) => Self::Label(label.into()),
+            StoredCourseEntry::Course(course) => Self::Course(course.into()),
+        }
+    }
+}
+
+impl From<StoredCourse> for Course {
+    fn from(course: StoredCourse) -> Self {
+        Self {
+            url: course.url,
+            path: course.path,
+            guid: course.guid,
+            name: course.name,
+            number: course.number,
+            subject_name: course.subject_name,
+            subject_code: course.subject_code,
+            credits: course.credits,
+        }
+    }
+}
+
+impl From<StoredLabel> for Label {
+    fn from(label: StoredLabel) -> Self {
+        Self {
+            url: label.url,
+            guid: label.guid,
+            name: label.name,
+            number: label.number,
+            subject_code: label.subject_code,
+            credits: label.credits,
+        }
+    }
+}
+
+impl From<StoredCourseDetails> for CourseDetails {
+    fn from(course: StoredCourseDetails) -> Self {
+        Self {
+            url: course.url,
+            guid: course.guid,
+            path: course.path,
+            subject_code: course.subject_code,
+            subject_name: course.subject_name,
+            number: course.number,
+            name: course.name,
+            credits: course.credits,
+            description: course.description,
+            prerequisite_narrative: course.prerequisite_narrative,
+            prerequisite: course.prerequisite,
+            corequisite_narrative: course.corequisite_narrative,
+            corequisite: course.corequisite,
+            crosslist_narrative: course.crosslist_narrative,
+            cross_listed: course.cross_listed,
+            credits_narrative: course.credits_narrative,
+            offered: course.offered,
+            offerings: course.offerings,
+            distribution: course.distribution,
+            gen_ed: course.gen_ed,
+            instructor: course.instructor,
+            notes: course.notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+
+    /// Every item under `outer.inner` in the data file `file` that parses
+    fn catalog_items<T: DeserializeOwned>(file: &str, outer: &str, inner: &str) -> Vec<T> {
+        let json: Value = serde_json::from_str(&std::fs::read_to_string(file).unwrap()).unwrap();
+
+        json[outer][inner]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|item| serde_json::from_str(&item.to_string()).ok())
+            .collect()
+    }
+
+    fn assert_round_trips<T>(items: Vec<T>)
+    where
+        T: serde::Serialize + PartialEq + std::fmt::Debug,
+        Stored<T>: DeserializeOwned,
+    {
+        assert!(!items.is_empty());
+
+        for item in items {
+            let json = serde_json::to_string(&item).unwrap();
+            let Stored(stored) = serde_json::from_str::<Stored<T>>(&json).unwrap();
+
+            assert_eq!(stored, item);
+        }
+    }
+
+    #[test]
+    fn reads_back_serialized_programs() {
+        let programs: Vec<Program> = catalog_items("../data/programs.json", "programs", "program");
+
+        assert_round_trips(programs);
+    }
+
+    #[test]
+    fn reads_back_serialized_courses() {
+        let courses: Vec<CourseDetails> =
+            catalog_items("../data/courses.json", "courses", "course");
+
+        assert_round_trips(courses);
+    }
+
+    #[test]
+    fn rejects_catalog_json() {
+        let program_json = std::fs::read_to_string("../data/cs_major.json").unwrap();
+
+        assert!(serde_json::from_str::<Stored<Program>>(&program_json).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! TypeScript declarations of the types served to the frontend, generated from their serde
+//! representation so the frontend can't drift from it.
+//!
+//! `bindings/vislog-core.d.ts` holds the current declarations. Regenerate it after changing any
+//! of the types with `UPDATE_TYPESCRIPT=1 cargo test -p vislog-core --features typescript`, or
+//! write them anywhere with `cargo run -p vislog-core --features typescript --bin vislog-ts`.
+
+use std::collections::BTreeMap;
+
+use ts_rs::{TypeVisitor, TS};
+
+use crate::{graph::ProgramGraph, plan::Plan, CourseDetails, Program};
+
+const HEADER: &str =
+    "// Generated by vislog-core from its serialized types, do not edit by hand.\n";
+
+/// Declarations of [Program], [CourseDetails], [ProgramGraph], [Plan] and every type they are made of, sorted by name
+pub fn declarations() -> String {
+    let mut declarations = Declarations::default();
+    declarations.visit::<Program>();
+    declarations.visit::<CourseDetails>();
+    declarations.visit::<ProgramGraph>();
+    declarations.visit::<Plan>();
+
+    declarations
+        .0
+        .values()
+        .fold(HEADER.to_owned(), |mut out, declaration| {
+            out.push('\n');
+            out.push_str(declaration);
+            out.push('\n');
+            out
+        })
+}
+
+/// Collects the declarations of the visited types and their dependencies by name
+#[derive(Default)]
+struct Declarations(BTreeMap<String, String>);
+
+impl TypeVisitor for Declarations {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        // Types without an output path like `Vec<T>` and `Option<T>` are written inline, only
+        // the types they hold need to be declared
+        if T::output_path().is_none() {
+            T::visit_generics(self);
+            return;
+        }
+
+        let name = T::ident();
+        if self.0.contains_key(&name) {
+            return;
+        }
+
+        let declaration = format!("{}export {}", T::DOCS.unwrap_or_default(), T::decl());
+        self.0.insert(name, declaration);
+
+        T::visit_dependencies(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Fails when the serialized types change without the bindings being regenerated
+    #[test]
+    fn committed_bindings_match_types() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("bindings/vislog-core.d.ts");
+        let declarations = declarations();
+
+        if std::env::var_os("UPDATE_TYPESCRIPT").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &declarations).unwrap();
+            return;
+        }
+
+        let committed = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            committed == declarations,
+            "{path:?} is out of date, regenerate it with \
+            `UPDATE_TYPESCRIPT=1 cargo test -p vislog-core --features typescript`"
+        );
+    }
+
+    #[test]
+    fn declares_adjacently_tagged_enums() {
+        let declarations = declarations();
+
+        for name in [
+            "Program",
+            "Requirements",
+            "RequirementModule",
+            "Requirement",
+            "CourseEntries",
+            "CourseEntry",
+            "Course",
+            "Label",
+            "CourseDetails",
+            "Guid",
+        ] {
+            assert!(
+                declarations.contains(&format!("export type {name} = ")),
+                "Missing {name}"
+            );
+        }
+
+        assert!(declarations.contains(r#"{ "type": "And", "data": CourseEntries }"#));
+        assert!(declarations.contains(r#"{ "type": "SelectTrack" }"#));
+    }
+}
@@ -0,0 +1,67 @@
+//! Prints the changes between two catalogs to stdout.
+//!
+//! Each catalog directory is expected to contain a `programs.json` and a `courses.json` in the
+//! format returned by the SmartCatalog API.
+//!
+//! ```sh
+//! cargo run -p vislog-parser --example diff_catalogs -- <old-catalog-dir> <new-catalog-dir> [--json]
+//! ```
+use std::{error::Error, path::Path, process::ExitCode};
+
+use serde_json::Value;
+use vislog_core::diff::CatalogDiff;
+use vislog_parser::{parse_courses, parse_programs};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (Some(from), Some(to)) = (args.first(), args.get(1)) else {
+        eprintln!("usage: diff_catalogs <old-catalog-dir> <new-catalog-dir> [--json]");
+        return ExitCode::FAILURE;
+    };
+    let as_json = args.iter().any(|arg| arg == "--json");
+
+    match diff(from, to, as_json) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn diff(from: &str, to: &str, as_json: bool) -> Result<(), Box<dyn Error>> {
+    let (old_programs, _) =
+        parse_programs(read_items(from, "programs.json", "programs", "program")?);
+    let (new_programs, _) = parse_programs(read_items(to, "programs.json", "programs", "program")?);
+    let (old_courses, _) = parse_courses(read_items(from, "courses.json", "courses", "course")?);
+    let (new_courses, _) = parse_courses(read_items(to, "courses.json", "courses", "course")?);
+
+    let diff = CatalogDiff::new(&old_programs, &new_programs, &old_courses, &new_courses);
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+
+    Ok(())
+}
+
+/// Reads the array nested at `obj.{outer}.{inner}` in the file `dir/file`
+fn read_items(
+    dir: &str,
+    file: &str,
+    outer: &str,
+    inner: &str,
+) -> Result<Vec<Value>, Box<dyn Error>> {
+    let path = Path::new(dir).join(file);
+    let json_str =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {path:?}: {e}"))?;
+    let mut json: Value =
+        serde_json::from_str(&json_str).map_err(|e| format!("{path:?} is not valid JSON: {e}"))?;
+
+    match json[outer][inner].take() {
+        Value::Array(items) => Ok(items),
+        _ => Err(format!("Expected field `{outer}.{inner}` to be a JSON array in {path:?}").into()),
+    }
+}
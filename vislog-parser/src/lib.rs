@@ -1,17 +1,22 @@
+use serde::Serialize;
 use serde_json::{self, Value};
 use thiserror::Error;
 use vislog_core::{CourseDetails, Program};
 
-#[derive(Debug, Clone, Error)]
+#[derive(Debug, Clone, Error, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "stage", rename_all = "lowercase")]
 pub enum ParsingError {
     #[error("failed to convert {:?} from value to string because {}", .title, .err_msg)]
     Serialization {
         title: Option<String>,
+        #[serde(rename = "message")]
         err_msg: String,
     },
     #[error("failed to convert {:?} from value to string because {}", .title, .err_msg)]
     Deserialization {
         title: Option<String>,
+        #[serde(rename = "message")]
         err_msg: String,
     },
 }
@@ -26,31 +31,31 @@ where
     let mut programs = Vec::with_capacity(program_jsons.size_hint().0);
 
     for value in program_jsons {
-        let program_title = get_program_title(&value);
-
-        let json_str = match serde_json::to_string_pretty(&value) {
-            Ok(json_str) => json_str,
-            Err(err) => {
-                errors.push(ParsingError::Serialization {
-                    title: program_title,
-                    err_msg: err.to_string(),
-                });
-                // Skip to next program JSON
-                continue;
-            }
-        };
-        match serde_json::from_str::<Program>(&json_str) {
+        match parse_program(value) {
             Ok(program) => programs.push(program),
-            Err(err) => errors.push(ParsingError::Deserialization {
-                title: program_title,
-                err_msg: err.to_string(),
-            }),
+            Err(err) => errors.push(err),
         }
     }
 
     (programs, errors)
 }
 
+/// Parses the JSON of a single program
+pub fn parse_program(program_json: Value) -> Result<Program, ParsingError> {
+    let program_title = get_program_title(&program_json);
+
+    let json_str =
+        serde_json::to_string_pretty(&program_json).map_err(|err| ParsingError::Serialization {
+            title: program_title.clone(),
+            err_msg: err.to_string(),
+        })?;
+
+    serde_json::from_str::<Program>(&json_str).map_err(|err| ParsingError::Deserialization {
+        title: program_title,
+        err_msg: err.to_string(),
+    })
+}
+
 fn get_program_title(program_json: &Value) -> Option<String> {
     let title_option = if let Value::Object(obj) = program_json {
         if let Some(Value::String(title)) = obj.get("title") {
@@ -75,32 +80,31 @@ where
     let mut courses = Vec::with_capacity(course_jsons.size_hint().0);
 
     for value in course_jsons {
-        let course_name = get_course_name(&value);
-
-        let json_str = match serde_json::to_string_pretty(&value) {
-            Ok(json_str) => json_str,
-            Err(err) => {
-                errors.push(ParsingError::Serialization {
-                    title: course_name,
-                    err_msg: err.to_string(),
-                });
-                // Skip to next program JSON
-                continue;
-            }
-        };
-
-        match serde_json::from_str::<CourseDetails>(&json_str) {
+        match parse_course(value) {
             Ok(course) => courses.push(course),
-            Err(err) => errors.push(ParsingError::Deserialization {
-                title: course_name,
-                err_msg: err.to_string(),
-            }),
+            Err(err) => errors.push(err),
         }
     }
 
     (courses, errors)
 }
 
+/// Parses the JSON of a single course
+pub fn parse_course(course_json: Value) -> Result<CourseDetails, ParsingError> {
+    let course_name = get_course_name(&course_json);
+
+    let json_str =
+        serde_json::to_string_pretty(&course_json).map_err(|err| ParsingError::Serialization {
+            title: course_name.clone(),
+            err_msg: err.to_string(),
+        })?;
+
+    serde_json::from_str::<CourseDetails>(&json_str).map_err(|err| ParsingError::Deserialization {
+        title: course_name,
+        err_msg: err.to_string(),
+    })
+}
+
 fn get_course_name(course_json: &Value) -> Option<String> {
     let name_option = if let Value::Object(obj) = course_json {
         if let Some(Value::String(title)) = obj.get("name") {
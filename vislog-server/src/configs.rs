@@ -1,5 +1,6 @@
-use std::{net::Ipv4Addr, ops::Deref, path::PathBuf, str::FromStr};
+use std::{collections::HashMap, net::Ipv4Addr, path::PathBuf, str::FromStr};
 
+use axum::http::{header::InvalidHeaderValue, HeaderValue};
 use config::{Config, ConfigError, File, FileFormat};
 use serde::Deserialize;
 
@@ -12,7 +13,12 @@ pub struct ServerConfig {
     pub data: Data,
     pub fetching: Fetching,
     pub cors: Option<Cors>,
-    pub static_assets: Option<StaticAssets>
+    pub static_assets: Option<StaticAssets>,
+    /// The admin API rejects every request if omitted
+    pub admin: Option<Admin>,
+    /// The catalog is only refreshed on request if omitted
+    pub scheduler: Option<Scheduler>,
+    pub http_cache: Option<HttpCache>,
 }
 
 impl ServerConfig {
@@ -21,7 +27,7 @@ impl ServerConfig {
             .add_source(File::new(CONFIG_FILE_PATH, FileFormat::Toml))
             .build()?;
 
-        Ok(s.try_deserialize()?)
+        s.try_deserialize()
     }
 }
 
@@ -36,6 +42,10 @@ impl Default for ServerConfig {
             storage: PathBuf::from_str("./").expect("should be valid"),
             all_programs_file: PathBuf::from_str("programs.json").expect("should be valid"),
             all_courses_file: PathBuf::from_str("courses.json").expect("should be valid"),
+            max_snapshots: Some(5),
+            provider: Some(JsonProviderKind::File),
+            web: None,
+            sqlite: None,
         };
 
         let log = Log {
@@ -43,7 +53,7 @@ impl Default for ServerConfig {
             with_target: Some(true),
         };
 
-        let fetching = Fetching { 
+        let fetching = Fetching {
             programs_url: "https://iq5prod1.smartcatalogiq.com/apis/progAPI?path=/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue&format=json".to_owned() ,
             courses_url: "https://iq5prod1.smartcatalogiq.com/APIs/courseAPI?path=/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue&format=json".to_owned(),
         };
@@ -52,6 +62,12 @@ impl Default for ServerConfig {
 
         let static_assets = None;
 
+        let admin = None;
+
+        let scheduler = None;
+
+        let http_cache = None;
+
         Self {
             server,
             data,
@@ -59,6 +75,9 @@ impl Default for ServerConfig {
             fetching,
             cors,
             static_assets,
+            admin,
+            scheduler,
+            http_cache,
         }
     }
 }
@@ -74,6 +93,47 @@ pub struct Data {
     pub storage: PathBuf,
     pub all_programs_file: PathBuf,
     pub all_courses_file: PathBuf,
+    /// Number of fetched snapshots of each data file to keep around for rollbacks
+    pub max_snapshots: Option<usize>,
+    /// Where the raw catalog JSON is read from. Defaults to the files in `storage`
+    pub provider: Option<JsonProviderKind>,
+    /// Required when `provider` is `web`
+    pub web: Option<WebProvider>,
+    /// Required when `provider` is `sqlite`
+    pub sqlite: Option<SqliteProvider>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonProviderKind {
+    #[default]
+    File,
+    Web,
+    Sqlite,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebProvider {
+    /// Ex: "https://iq5prod1.smartcatalogiq.com"
+    pub base_url: String,
+    /// Path of the catalog in the API. Ex:
+    /// "/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue"
+    pub catalog_path: String,
+    pub timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    /// Delay before the first retry, doubled after every failed attempt
+    pub retry_backoff_millis: Option<u64>,
+    /// Directory where responses are cached for conditional requests. Caching is disabled if
+    /// omitted
+    pub cache_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SqliteProvider {
+    /// Database file. Created along with its parent directories if missing
+    pub path: PathBuf,
+    /// The catalog year the data files get imported as. Ex: "2023"
+    pub catalog_year: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -111,34 +171,99 @@ pub struct Fetching {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Cors {
+    /// Exact origins (Ex: "https://vislog.example.com"), origins with a wildcard subdomain
+    /// (Ex: "https://*.example.com") or "*" to allow all of them
     pub origins: Vec<String>,
-}
-
-#[derive(Debug, Deserialize, Clone)]
-pub struct Origins(Vec<String>);
-
-impl Deref for Origins {
-    type Target = Vec<String>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
+    /// Methods allowed in cross origin requests. Defaults to GET, POST and OPTIONS
+    pub methods: Option<Vec<String>>,
+    /// Request headers allowed in cross origin requests. Defaults to Authorization and Content-Type
+    pub headers: Option<Vec<String>>,
+    /// How long browsers can cache the response to a preflight request. Defaults to 600
+    pub max_age_secs: Option<u64>,
+    /// Whether cross origin requests can include cookies and the Authorization header. Defaults
+    /// to false, and is ignored when `origins` has "*"
+    pub allow_credentials: Option<bool>,
 }
 
 impl Cors {
     pub fn origins_to_string(&self) -> String {
         let len = self.origins.len();
-        self.origins.iter().enumerate().fold(String::new(), |mut acc, (idx, origin)| {
-            acc.push_str(&origin);
-            if idx < len - 1 {
-                acc.push_str(", ");
-            }
-            acc
-        })
+        self.origins
+            .iter()
+            .enumerate()
+            .fold(String::new(), |mut acc, (idx, origin)| {
+                acc.push_str(origin);
+                if idx < len - 1 {
+                    acc.push_str(", ");
+                }
+                acc
+            })
     }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct StaticAssets {
-    pub dir: PathBuf
+    pub dir: PathBuf,
+    /// Directory in `dir` where the SVG map of every program is generated on refresh. Defaults to
+    /// "maps"
+    pub program_maps_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Admin {
+    /// Tokens allowed to use the admin API
+    pub tokens: Vec<AdminToken>,
+    /// Max number of requests each client can make to the admin API per `rate_limit_window_secs`.
+    /// Defaults to 30
+    pub rate_limit: Option<u32>,
+    /// Defaults to 60
+    pub rate_limit_window_secs: Option<u64>,
+    /// File every admin request gets appended to as a line of JSON. Only logged if omitted
+    pub audit_log: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdminToken {
+    /// Who the token belongs to, recorded in the audit log
+    pub name: String,
+    /// Hex encoded SHA-256 of the token. Ex: `echo -n "<token>" | sha256sum`
+    pub sha256: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Scheduler {
+    /// Defaults to true
+    pub enabled: Option<bool>,
+    /// Refresh every this many seconds. Can't be used along with `cron`
+    pub interval_secs: Option<u64>,
+    /// Refresh whenever this cron expression fires, in UTC. Ex: "0 3 * * *"
+    pub cron: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HttpCache {
+    /// `Cache-Control` of the catalog responses that can be revalidated with their `ETag` or
+    /// `Last-Modified`. Defaults to "no-cache"
+    pub cache_control: Option<CacheControl>,
+    /// `Cache-Control` by path prefix (Ex: "/api/programs"), the longest matching prefix wins
+    pub routes: Option<HashMap<String, CacheControl>>,
+}
+
+/// A `Cache-Control` header value, rejected along with the config file if it isn't a valid one
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct CacheControl(HeaderValue);
+
+impl CacheControl {
+    pub fn header_value(&self) -> &HeaderValue {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for CacheControl {
+    type Error = InvalidHeaderValue;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        HeaderValue::try_from(value).map(Self)
+    }
 }
@@ -0,0 +1,158 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use lazy_static::lazy_static;
+use tokio::sync::Mutex;
+use tracing::debug;
+use vislog_core::diff::CatalogDiff;
+use vislog_parser::{parse_course, parse_program};
+
+use crate::CONFIGS;
+
+use self::error::{Error, Result};
+
+use super::providers::json_providers::{FileJsonProvider, JsonProvider};
+
+pub mod error {
+    use thiserror::Error;
+
+    use crate::data::providers::json_providers;
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        /// The catalog given is not a relative path inside of the storage directory
+        #[error("Catalog {0:?} is not a directory inside of the storage directory")]
+        InvalidCatalog(String),
+
+        #[error("Failed to read the catalog: {0}")]
+        JsonProvider(#[from] json_providers::Error),
+
+        /// Comparing the catalogs panicked or got cancelled
+        #[error("Diffing the catalogs did not finish: {0}")]
+        Join(#[from] tokio::task::JoinError),
+    }
+}
+
+/// How many diffs are kept around. Diffing parses two whole catalogs, so a repeated request for
+/// the same catalogs is answered from here until one of their files changes
+const MAX_CACHED_DIFFS: usize = 8;
+
+lazy_static! {
+    /// Least recently used diffs first. Held for the whole diff so that only one pair of catalogs
+    /// gets parsed at a time
+    static ref DIFF_CACHE: Mutex<Vec<CachedDiff>> = Mutex::new(Vec::new());
+}
+
+struct CachedDiff {
+    from: PathBuf,
+    to: PathBuf,
+    /// When each of the catalog files the diff was made from was last modified
+    modified: Vec<Option<SystemTime>>,
+    diff: Arc<CatalogDiff>,
+}
+
+/// Compares two catalogs stored inside of the storage directory.
+///
+/// `from` and `to` are paths relative to `data.storage` of directories each holding their own
+/// copies of `data.all_programs_file` and `data.all_courses_file` (Ex: "2023" and "2024"). Pass in
+/// "." to refer to the catalog currently being served.
+pub async fn diff_catalogs(from: &str, to: &str) -> Result<Arc<CatalogDiff>> {
+    let from = catalog_dir(from)?;
+    let to = catalog_dir(to)?;
+    let modified = modification_times(&[&from, &to]).await;
+
+    let mut cache = DIFF_CACHE.lock().await;
+    if let Some(i) = cache
+        .iter()
+        .position(|cached| cached.from == from && cached.to == to && cached.modified == modified)
+    {
+        debug!("Using the cached diff of {from:?} against {to:?}");
+        let cached = cache.remove(i);
+        let diff = cached.diff.clone();
+        cache.push(cached);
+        return Ok(diff);
+    }
+
+    let (old_programs, _errors) = FileJsonProvider::init(&from, &CONFIGS.data.all_programs_file)?
+        .stream_program_jsons()
+        .await?
+        .parse_each(parse_program)
+        .await?;
+    let (new_programs, _errors) = FileJsonProvider::init(&to, &CONFIGS.data.all_programs_file)?
+        .stream_program_jsons()
+        .await?
+        .parse_each(parse_program)
+        .await?;
+    let (old_courses, _errors) = FileJsonProvider::init(&from, &CONFIGS.data.all_courses_file)?
+        .stream_course_jsons()
+        .await?
+        .parse_each(parse_course)
+        .await?;
+    let (new_courses, _errors) = FileJsonProvider::init(&to, &CONFIGS.data.all_courses_file)?
+        .stream_course_jsons()
+        .await?
+        .parse_each(parse_course)
+        .await?;
+
+    let diff = tokio::task::spawn_blocking(move || {
+        CatalogDiff::new(&old_programs, &new_programs, &old_courses, &new_courses)
+    })
+    .await?;
+    let diff = Arc::new(diff);
+
+    cache.retain(|cached| cached.from != from || cached.to != to);
+    if cache.len() >= MAX_CACHED_DIFFS {
+        cache.remove(0);
+    }
+    cache.push(CachedDiff {
+        from,
+        to,
+        modified,
+        diff: diff.clone(),
+    });
+
+    Ok(diff)
+}
+
+/// Modification times of the program and course files of every catalog, `None` for the ones that
+/// can't be read
+async fn modification_times(catalogs: &[&Path]) -> Vec<Option<SystemTime>> {
+    let mut modified = Vec::new();
+    for catalog in catalogs {
+        for file in [
+            &CONFIGS.data.all_programs_file,
+            &CONFIGS.data.all_courses_file,
+        ] {
+            let metadata = tokio::fs::metadata(catalog.join(file)).await;
+            modified.push(metadata.and_then(|metadata| metadata.modified()).ok());
+        }
+    }
+
+    modified
+}
+
+/// Resolves `catalog` against the storage directory while rejecting paths that escape it. The "."
+/// components are dropped so that every way of writing a catalog resolves to the same path
+fn catalog_dir(catalog: &str) -> Result<PathBuf> {
+    let is_contained = Path::new(catalog)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+
+    if catalog.is_empty() || !is_contained {
+        return Err(Error::InvalidCatalog(catalog.to_owned()));
+    }
+
+    let mut dir = CONFIGS.data.storage.clone();
+    dir.extend(
+        Path::new(catalog)
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_))),
+    );
+
+    Ok(dir)
+}
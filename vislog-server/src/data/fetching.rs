@@ -1,37 +1,160 @@
 use serde_json::Value;
 
-use tokio::{fs::File, io::AsyncWriteExt};
 use vislog_core::{CourseDetails, Program};
 
-use crate::{data::providers::programs::ProgramsProvider, CONFIGS};
+use crate::{configs::JsonProviderKind, data::providers::programs::ProgramsProvider, CONFIGS};
 
-use self::error::Result;
+use self::error::{RefreshError, Result};
 
-use super::providers::courses::CoursesProvider;
+use super::{
+    maps,
+    providers::courses::CoursesProvider,
+    snapshots::{self, CatalogKind},
+};
 
 pub mod error {
     use std::fmt::Display;
 
     use thiserror::Error;
 
-    use crate::data::providers;
+    use crate::data::{providers, scheduler::JobError, snapshots, snapshots::CatalogKind};
 
     pub type Result<T> = std::result::Result<T, Error>;
 
     #[derive(Debug, Error)]
     pub enum Error {
+        #[error("Failed to write the catalog: {0}")]
         Io(#[from] std::io::Error),
+        #[error("Failed to save the snapshot: {0}")]
+        Snapshot(#[from] snapshots::error::Error),
+        #[error("Failed to load the programs: {0}")]
         ParsingProgram(#[from] providers::programs::Error),
+        #[error("Failed to load the courses: {0}")]
         ParsingCourse(#[from] providers::courses::Error),
+        #[error("Failed to download the catalog: {0}")]
         Reqwest(#[from] reqwest::Error),
+        #[error("Failed to serialize the catalog: {0}")]
         SerdeJson(#[from] serde_json::Error),
     }
 
-    impl Display for Error {
+    impl Error {
+        /// Same as the [Display] message but without the underlying error, which can hold
+        /// urls, paths or parts of the upstream response
+        pub fn public_message(&self) -> &'static str {
+            match self {
+                Error::Io(_) => "Failed to write the catalog",
+                Error::Snapshot(_) => "Failed to save the snapshot",
+                Error::ParsingProgram(_) => "Failed to load the programs",
+                Error::ParsingCourse(_) => "Failed to load the courses",
+                Error::Reqwest(_) => "Failed to download the catalog",
+                Error::SerdeJson(_) => "Failed to serialize the catalog",
+            }
+        }
+    }
+
+    /// The programs, the courses or both failed to refresh
+    #[derive(Debug, Error)]
+    pub struct RefreshError {
+        pub programs: Option<Box<Error>>,
+        pub courses: Option<Box<Error>>,
+    }
+
+    impl RefreshError {
+        /// `Ok` only if neither refresh failed
+        pub fn check(programs: Result<()>, courses: Result<()>) -> std::result::Result<(), Self> {
+            match (programs.err().map(Box::new), courses.err().map(Box::new)) {
+                (None, None) => Ok(()),
+                (programs, courses) => Err(Self { programs, courses }),
+            }
+        }
+
+        fn failures(&self) -> impl Iterator<Item = (CatalogKind, &Error)> {
+            [
+                (CatalogKind::Programs, self.programs.as_deref()),
+                (CatalogKind::Courses, self.courses.as_deref()),
+            ]
+            .into_iter()
+            .filter_map(|(kind, err)| Some((kind, err?)))
+        }
+    }
+
+    impl Display for RefreshError {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            write!(f, "{self:?}")
+            for (i, (kind, err)) in self.failures().enumerate() {
+                let separator = if i == 0 { "" } else { "; " };
+                write!(
+                    f,
+                    "{separator}Failed to refresh the {}: {err}",
+                    kind.as_str()
+                )?;
+            }
+            Ok(())
         }
     }
+
+    impl JobError for RefreshError {
+        fn public_message(&self) -> String {
+            self.failures()
+                .map(|(kind, err)| {
+                    format!(
+                        "Failed to refresh the {}: {}",
+                        kind.as_str(),
+                        err.public_message()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("; ")
+        }
+    }
+}
+
+/// Refreshes both programs and courses, run by the [Scheduler](super::scheduler::Scheduler).
+/// Both are refreshed even if the other one fails, see [refresh_programs] and [refresh_courses].
+pub async fn refresh_catalog(
+    programs_provider: &ProgramsProvider,
+    courses_provider: &CoursesProvider,
+) -> std::result::Result<(), RefreshError> {
+    let (programs, courses) = tokio::join!(
+        async { refresh_programs(programs_provider).await.map(drop) },
+        async { refresh_courses(courses_provider).await.map(drop) },
+    );
+
+    if programs.is_ok() || courses.is_ok() {
+        maps::spawn_generate(programs_provider, courses_provider);
+    }
+
+    RefreshError::check(programs, courses)
+}
+
+/// Whether the data is read straight from the catalog API, so there's nothing to fetch and save
+fn reads_catalog_api() -> bool {
+    CONFIGS.data.provider.unwrap_or_default() == JsonProviderKind::Web
+}
+
+/// Fetches the programs again and saves them as a new snapshot, unless they are read straight
+/// from the catalog API in which case only the cache is refreshed
+pub async fn refresh_programs(programs_provider: &ProgramsProvider) -> Result<Vec<Program>> {
+    if !reads_catalog_api() {
+        return fetch_all_programs(programs_provider).await;
+    }
+
+    programs_provider.refresh_cache().await?;
+    let (programs, _errors) = programs_provider.get_all_programs().await?;
+
+    Ok(programs)
+}
+
+/// Fetches the courses again and saves them as a new snapshot, unless they are read straight
+/// from the catalog API in which case only the cache is refreshed
+pub async fn refresh_courses(courses_provider: &CoursesProvider) -> Result<Vec<CourseDetails>> {
+    if !reads_catalog_api() {
+        return fetch_all_courses(courses_provider).await;
+    }
+
+    courses_provider.refresh_cache().await?;
+    let (courses, _errors) = courses_provider.get_all_courses().await?;
+
+    Ok(courses)
 }
 
 // TODO: Remove programs_provider dependency and refresh it's cache elsewhere
@@ -41,13 +164,9 @@ pub async fn fetch_all_programs(programs_provider: &ProgramsProvider) -> Result<
     let data_url = &CONFIGS.fetching.programs_url;
     let body: Value = reqwest::get(data_url).await?.json().await?;
 
-    // Write fetched data to storage
-    let mut storage_path = CONFIGS.data.storage.clone();
-    storage_path.push(&CONFIGS.data.all_programs_file);
-    let mut f = File::create(storage_path).await.unwrap();
-    f.write_all(serde_json::to_string_pretty(&body)?.as_bytes())
-        .await?;
-    f.flush().await?;
+    // Write fetched data to a new snapshot and only replace the current data if it is valid
+    let contents = serde_json::to_string_pretty(&body)?;
+    snapshots::save_and_promote(&CONFIGS.data, CatalogKind::Programs, contents.as_bytes()).await?;
 
     // Refresh cache and fetch new results from cache
     programs_provider.refresh_cache().await?;
@@ -63,13 +182,9 @@ pub async fn fetch_all_courses(courses_provider: &CoursesProvider) -> Result<Vec
     let data_url = &CONFIGS.fetching.courses_url;
     let body: Value = reqwest::get(data_url).await?.json().await?;
 
-    // Write fetched data to storage
-    let mut storage_path = CONFIGS.data.storage.clone();
-    storage_path.push(&CONFIGS.data.all_courses_file);
-    let mut f = File::create(storage_path).await.unwrap();
-    f.write_all(serde_json::to_string_pretty(&body)?.as_bytes())
-        .await?;
-    f.flush().await?;
+    // Write fetched data to a new snapshot and only replace the current data if it is valid
+    let contents = serde_json::to_string_pretty(&body)?;
+    snapshots::save_and_promote(&CONFIGS.data, CatalogKind::Courses, contents.as_bytes()).await?;
 
     // Refresh cache and fetch new results from cache
     courses_provider.refresh_cache().await?;
@@ -77,3 +192,33 @@ pub async fn fetch_all_courses(courses_provider: &CoursesProvider) -> Result<Vec
 
     Ok(courses)
 }
+
+#[cfg(test)]
+mod test {
+    use crate::data::scheduler::JobError;
+
+    use super::*;
+
+    #[test]
+    fn reports_every_failed_refresh() {
+        let io = std::io::Error::other("/srv/vislog/data/programs.json is read only");
+        let json = serde_json::from_str::<Value>("<html>").unwrap_err();
+
+        assert!(RefreshError::check(Ok(()), Ok(())).is_ok());
+
+        let err = RefreshError::check(Err(io.into()), Err(json.into())).unwrap_err();
+        assert!(err.to_string().contains("/srv/vislog/data/programs.json"));
+        assert_eq!(
+            err.public_message(),
+            "Failed to refresh the programs: Failed to write the catalog; \
+             Failed to refresh the courses: Failed to serialize the catalog"
+        );
+
+        let json = serde_json::from_str::<Value>("<html>").unwrap_err();
+        let err = RefreshError::check(Ok(()), Err(json.into())).unwrap_err();
+        assert_eq!(
+            err.public_message(),
+            "Failed to refresh the courses: Failed to serialize the catalog"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Program maps rendered to SVG ahead of time into the static assets, so that they can be linked
+//! from emails and LMS pages without rendering them on every request
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use tokio::{fs, sync::Mutex};
+use tracing::{debug, error, info, warn};
+use vislog_core::{
+    graph::{svg::to_svg, ProgramGraph},
+    Program,
+};
+
+use crate::CONFIGS;
+
+use self::error::Result;
+
+use super::providers::{courses::CoursesProvider, programs::ProgramsProvider};
+
+pub mod error {
+    use thiserror::Error;
+
+    use crate::data::providers;
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("Failed to write the maps: {0}")]
+        Io(#[from] std::io::Error),
+
+        #[error("Failed to get the programs: {0}")]
+        Programs(#[from] providers::programs::Error),
+
+        #[error("Failed to get the courses: {0}")]
+        Courses(#[from] providers::courses::Error),
+
+        /// Rendering panicked or was cancelled
+        #[error("Rendering a map did not finish: {0}")]
+        Render(#[from] tokio::task::JoinError),
+    }
+}
+
+const DEFAULT_PROGRAM_MAPS_DIR: &str = "maps";
+
+/// Makes sure that only one generation writes to the maps directory at a time
+static GENERATION_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MapsReport {
+    pub written: usize,
+    pub removed: usize,
+    /// Programs whose map failed to render, their previous map is kept if there is one
+    pub failed: usize,
+}
+
+/// Where the maps are written, `None` if no static assets are served
+pub fn maps_dir() -> Option<PathBuf> {
+    CONFIGS.static_assets.as_ref().map(|config| {
+        config.dir.join(
+            config
+                .program_maps_dir
+                .as_deref()
+                .unwrap_or(Path::new(DEFAULT_PROGRAM_MAPS_DIR)),
+        )
+    })
+}
+
+/// Regenerates the maps in the background after the catalog changed
+pub fn spawn_generate(programs_provider: &ProgramsProvider, courses_provider: &CoursesProvider) {
+    let Some(dir) = maps_dir() else {
+        return;
+    };
+
+    let (programs_provider, courses_provider) =
+        (programs_provider.clone(), courses_provider.clone());
+    tokio::spawn(async move {
+        match generate(&dir, &programs_provider, &courses_provider).await {
+            Ok(report) => info!(
+                "Generated program maps in {dir:?}: {} written, {} removed, {} failed",
+                report.written, report.removed, report.failed
+            ),
+            Err(err) => error!("Failed to generate program maps in {dir:?}: {err}"),
+        }
+    });
+}
+
+/// Writes the map of every program to `<dir>/<GUID>.svg` and removes the maps of programs that no
+/// longer exist. Programs whose map fails to render are skipped
+pub async fn generate(
+    dir: &Path,
+    programs_provider: &ProgramsProvider,
+    courses_provider: &CoursesProvider,
+) -> Result<MapsReport> {
+    let _generation_guard = GENERATION_LOCK.lock().await;
+
+    fs::create_dir_all(dir).await?;
+
+    let (programs, _errors) = programs_provider.get_all_programs().await?;
+
+    let mut report = MapsReport::default();
+    let mut file_names = HashSet::new();
+    for program in programs {
+        let file_name = format!("{}.svg", program.guid);
+        let guid = program.guid;
+        let svg = match render_map(program, courses_provider).await {
+            Ok(svg) => svg,
+            Err(err) => {
+                warn!("Failed to render the map of program {guid}: {err}");
+                report.failed += 1;
+                file_names.insert(file_name);
+                continue;
+            }
+        };
+
+        // Written next to the map first so that it's never served half written
+        let partial_path = dir.join(format!("{file_name}.partial"));
+        fs::write(&partial_path, svg).await?;
+        fs::rename(&partial_path, dir.join(&file_name)).await?;
+
+        report.written += 1;
+        file_names.insert(file_name);
+    }
+
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let is_stale = path.extension() == Some(OsStr::new("svg"))
+            && !entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| file_names.contains(name));
+
+        if is_stale {
+            debug!("Removing stale program map {path:?}");
+            match fs::remove_file(&path).await {
+                Ok(()) => report.removed += 1,
+                Err(err) => warn!("Failed to remove stale program map {path:?}: {err}"),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Lays out and renders the map of a program, which is CPU bound so it's done off the runtime
+pub async fn render_map(program: Program, courses_provider: &CoursesProvider) -> Result<String> {
+    let courses = courses_provider
+        .get_courses_with_requisites(program.courses().iter().map(|course| &course.guid))
+        .await?;
+
+    let svg =
+        tokio::task::spawn_blocking(move || to_svg(&ProgramGraph::new(&program, &courses))).await?;
+
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use crate::data::providers::json_providers::FileJsonProvider;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn writes_maps_and_removes_stale_ones() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let cs_major: Value =
+            serde_json::from_str(&std::fs::read_to_string("../data/cs_major.json").unwrap())
+                .unwrap();
+        std::fs::write(
+            data_dir.path().join("programs.json"),
+            json!({ "programs": { "program": [cs_major] } }).to_string(),
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let programs_provider = ProgramsProvider::with(Box::new(
+            FileJsonProvider::init(data_dir.path(), Path::new("programs.json")).unwrap(),
+        ));
+        let courses_provider = CoursesProvider::with(Box::new(
+            FileJsonProvider::init("../data", "courses.json").unwrap(),
+        ));
+
+        let stale = dir.path().join("00000000-0000-0000-0000-000000000000.svg");
+        std::fs::write(&stale, "<svg/>").unwrap();
+        let unrelated = dir.path().join("README.md");
+        std::fs::write(&unrelated, "").unwrap();
+
+        let report = generate(dir.path(), &programs_provider, &courses_provider)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            report,
+            MapsReport {
+                written: 1,
+                removed: 1,
+                failed: 0
+            }
+        );
+        assert!(!stale.exists());
+        assert!(unrelated.exists());
+
+        let map =
+            std::fs::read_to_string(dir.path().join("5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5.svg"))
+                .unwrap();
+        assert!(map.contains("<title>Major in Computer Science"));
+    }
+
+    #[tokio::test]
+    async fn skips_maps_that_fail_to_render() {
+        let data_dir = tempfile::tempdir().unwrap();
+        std::fs::write(data_dir.path().join("courses.json"), "[]").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let programs_provider = ProgramsProvider::with(Box::new(
+            FileJsonProvider::init("../data", "programs.json").unwrap(),
+        ));
+        let courses_provider = CoursesProvider::with(Box::new(
+            FileJsonProvider::init(data_dir.path(), Path::new("courses.json")).unwrap(),
+        ));
+
+        let previous = dir.path().join("5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5.svg");
+        std::fs::write(&previous, "<svg/>").unwrap();
+
+        let report = generate(dir.path(), &programs_provider, &courses_provider)
+            .await
+            .unwrap();
+
+        let (programs, _errors) = programs_provider.get_all_programs().await.unwrap();
+        assert_eq!(
+            report,
+            MapsReport {
+                written: 0,
+                removed: 0,
+                failed: programs.len()
+            }
+        );
+        assert_eq!(std::fs::read_to_string(previous).unwrap(), "<svg/>");
+    }
+}
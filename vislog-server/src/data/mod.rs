@@ -1,2 +1,6 @@
+pub mod diffing;
 pub mod fetching;
+pub mod maps;
 pub mod providers;
+pub mod scheduler;
+pub mod snapshots;
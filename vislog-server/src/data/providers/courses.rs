@@ -1,131 +1,577 @@
-use std::{collections::HashMap, fmt::Display, io::Write, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
 
+use serde::Serialize;
+use serde_json::Value;
 use thiserror::Error;
-use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use tracing::{debug, instrument, Level};
-use vislog_core::{parsing::guid::Guid, CourseDetails};
-use vislog_parser::{parse_courses, ParsingError};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, instrument, warn, Level};
+use utoipa::ToSchema;
+use vislog_core::{equivalence::Equivalences, parsing::guid::Guid, CourseDetails};
+use vislog_parser::{parse_course, ParsingError};
+
+use crate::data::snapshots::CatalogKind;
 
 use super::{
+    json_hash,
     json_providers::{self, JsonProvider},
-    ProviderCache,
+    CacheStats, CacheVersion, ProviderCache, RefreshReport,
 };
 
 #[derive(Clone)]
 pub struct CoursesProvider {
     json_provider: Arc<RwLock<Box<dyn JsonProvider>>>,
-    cache: Arc<RwLock<ProviderCache<Guid, CourseDetails, ParsingError>>>,
+    cache: Arc<ArcSwap<CourseCache>>,
+    /// Makes sure that only one refresh builds a new cache at a time
+    refresh_lock: Arc<Mutex<()>>,
 }
 
 impl CoursesProvider {
     pub fn with(json_provider: Box<dyn JsonProvider>) -> Self {
-        let json_provider = Arc::new(RwLock::new(json_provider));
-        let cache = ProviderCache {
-            items: HashMap::new(),
-            errors: Vec::new(),
-        };
-        let cache = Arc::new(RwLock::new(cache));
         Self {
-            json_provider,
-            cache,
+            json_provider: Arc::new(RwLock::new(json_provider)),
+            cache: Arc::new(ArcSwap::from_pointee(CourseCache::default())),
+            refresh_lock: Arc::new(Mutex::new(())),
         }
     }
 
     #[instrument(skip(self))]
     pub async fn get_all_courses(&self) -> Result<(Vec<CourseDetails>, Vec<ParsingError>)> {
-        let cache = {
-            let read_cache_guard = self.cache.read().await;
-
-            if read_cache_guard.items.is_empty() && read_cache_guard.errors.is_empty() {
-                debug!("cache empty");
-                drop(read_cache_guard);
-                let json_provider_read_guard = self.json_provider.read().await;
-                let write_cache_guard = self.cache.write().await;
-                Self::_refresh_cache(json_provider_read_guard, write_cache_guard).await?;
-
-                // Reacquire read lock
-                self.cache.read().await
-            } else {
-                debug!("cache populated");
-                read_cache_guard
-            }
-        };
+        let cache = self.loaded_cache().await?;
 
-        let courses: Vec<CourseDetails> = cache.items.values().cloned().collect();
-        let errors = cache.errors.to_vec();
+        let courses: Vec<CourseDetails> = cache.courses.items.values().cloned().collect();
+        let errors = cache.courses.errors.to_vec();
 
         Ok((courses, errors))
     }
 
     #[instrument(level = Level::DEBUG, skip(self))]
     pub async fn get_course(&self, guid: &Guid) -> Result<Option<CourseDetails>> {
-        let cache = {
-            let read_cache_guard = self.cache.read().await;
-
-            if read_cache_guard.items.is_empty() && read_cache_guard.errors.is_empty() {
-                debug!("cache empty");
-
-                drop(read_cache_guard);
-                let json_provider_read_guard = self.json_provider.read().await;
-                let write_cache_guard = self.cache.write().await;
-                Self::_refresh_cache(json_provider_read_guard, write_cache_guard).await?;
-
-                // Reacquire read lock
-                self.cache.read().await
-            } else {
-                debug!("cache populated");
-                read_cache_guard
+        let cache = self.loaded_cache().await?;
+
+        Ok(cache.courses.items.get(guid).cloned())
+    }
+
+    /// Gets the courses with the subject code and number humans refer to them by, ignoring case.
+    /// Ex: "CSC" and "215". There's usually only one, but nothing stops the catalog from giving
+    /// the same code to several courses
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn get_courses_by_code(
+        &self,
+        subject_code: &str,
+        number: &str,
+    ) -> Result<Vec<CourseDetails>> {
+        let cache = self.loaded_cache().await?;
+
+        Ok(cache
+            .indexes
+            .by_code
+            .get(&(subject_code.to_uppercase(), number.to_uppercase()))
+            .into_iter()
+            .flatten()
+            .filter_map(|guid| cache.courses.items.get(guid))
+            .cloned()
+            .collect())
+    }
+
+    /// Every subject with at least one course, sorted by code
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn get_subjects(&self) -> Result<Vec<Subject>> {
+        let cache = self.loaded_cache().await?;
+
+        Ok(cache
+            .indexes
+            .by_subject
+            .values()
+            .map(|subject| Subject {
+                code: subject.code.clone(),
+                name: subject.name.clone(),
+                course_count: subject.courses.len(),
+            })
+            .collect())
+    }
+
+    /// The courses of a subject sorted by number, `None` if no course has the subject code
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn get_subject_courses(
+        &self,
+        subject_code: &str,
+    ) -> Result<Option<Vec<CourseDetails>>> {
+        let cache = self.loaded_cache().await?;
+
+        Ok(cache
+            .indexes
+            .by_subject
+            .get(&subject_code.to_uppercase())
+            .map(|subject| {
+                subject
+                    .courses
+                    .iter()
+                    .filter_map(|guid| cache.courses.items.get(guid))
+                    .cloned()
+                    .collect()
+            }))
+    }
+
+    /// The courses cross-listed with a course, sorted by code, `None` if there is no course with
+    /// the GUID
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn get_equivalent_courses(&self, guid: &Guid) -> Result<Option<Vec<CourseDetails>>> {
+        let cache = self.loaded_cache().await?;
+        if !cache.courses.items.contains_key(guid) {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            cache
+                .indexes
+                .equivalences
+                .equivalents(guid)
+                .iter()
+                .filter_map(|guid| cache.courses.items.get(guid))
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    /// Details of the courses with the given GUIDs and of their prerequisites and corequisites.
+    /// GUIDs of unknown courses are skipped
+    #[instrument(level = Level::DEBUG, skip_all)]
+    pub async fn get_courses_with_requisites<'a>(
+        &self,
+        guids: impl IntoIterator<Item = &'a Guid>,
+    ) -> Result<HashMap<Guid, CourseDetails>> {
+        let cache = self.loaded_cache().await?;
+
+        let mut courses = HashMap::new();
+        for guid in guids {
+            let Some(course) = cache.courses.items.get(guid) else {
+                continue;
+            };
+
+            for requisite in [course.prerequisite, course.corequisite]
+                .into_iter()
+                .flatten()
+            {
+                if let Some(requisite) = cache.courses.items.get(&requisite) {
+                    courses.insert(requisite.guid, requisite.clone());
+                }
             }
-        };
+            courses.insert(course.guid, course.clone());
+        }
 
-        Ok(cache.items.get(guid).map(|p| p.clone()))
+        Ok(courses)
     }
 
-    pub async fn refresh_cache(&self) -> Result<()> {
+    /// Gets the unparsed JSON of a single course by its catalog URL, catalog path or GUID
+    pub async fn get_course_json(&self, id: &str) -> Result<Value> {
         let json_provider_read_guard = self.json_provider.read().await;
-        let cache_write_guard = self.cache.write().await;
 
-        Self::_refresh_cache(json_provider_read_guard, cache_write_guard).await
+        Ok(json_provider_read_guard.get_course_json(id).await?)
     }
 
-    /// SAFETY: There must not be a another read guard for `RwLockReadGuard<'a, ProviderCache>` in
-    /// the same execution "thread" to avoid deadlocks
-    async fn _refresh_cache<'a>(
-        json_provider_read_guard: RwLockReadGuard<'a, Box<dyn JsonProvider>>,
-        mut cache_write_guard: RwLockWriteGuard<
-            'a,
-            ProviderCache<Guid, CourseDetails, ParsingError>,
-        >,
-    ) -> Result<()> {
-        let course_jsons = json_provider_read_guard.get_all_course_jsons()?;
+    /// Reparses a single course and replaces it in the cache without reparsing all the others
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn refresh_course(&self, id: &str) -> Result<CourseDetails> {
+        let course_json = self.get_course_json(id).await?;
+        let hash = json_hash(&course_json).map_err(json_providers::Error::from)?;
+        let course = parse_course(course_json).map_err(|err| Error::Parsing(vec![err]))?;
 
-        std::io::stdout().lock().flush().unwrap();
-        let (courses, errors) = parse_courses(course_jsons);
+        let _refresh_guard = self.refresh_lock.lock().await;
+        let mut cache = ProviderCache::clone(&self.cache.load().courses);
+        cache.replace(course.guid, course.clone(), hash);
+        self.store_cache(cache);
 
-        let programs = courses
-            .into_iter()
-            .map(|course| (course.guid.clone(), course))
-            .collect::<Vec<(Guid, CourseDetails)>>();
+        Ok(course)
+    }
+
+    /// Version of the cached courses, filling the cache if it hasn't been loaded yet
+    pub async fn cache_version(&self) -> Result<CacheVersion> {
+        Ok(self.loaded_cache().await?.courses.version)
+    }
+
+    /// Size of the cache without filling it if it hasn't been loaded yet
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.load().courses.stats()
+    }
 
-        cache_write_guard.items.clear();
-        cache_write_guard.errors.clear();
+    /// Reloads the data from the `JsonProvider` and reparses the courses that changed
+    pub async fn refresh_cache(&self) -> Result<RefreshReport<Guid>> {
+        let _refresh_guard = self.refresh_lock.lock().await;
 
-        cache_write_guard.items.extend(programs);
-        cache_write_guard.errors.extend(errors);
+        self.json_provider
+            .read()
+            .await
+            .reload(CatalogKind::Courses)
+            .await?;
 
-        Ok(())
+        self._refresh_cache().await
+    }
+
+    /// Fills the cache the first time it's needed
+    async fn loaded_cache(&self) -> Result<Arc<CourseCache>> {
+        let cache = self.cache.load_full();
+        if cache.courses.loaded {
+            debug!("cache populated");
+            return Ok(cache);
+        }
+
+        debug!("cache empty");
+        // Someone else is already filling the cache, so let the caller retry instead of piling up
+        // behind the lock
+        let Ok(_refresh_guard) = self.refresh_lock.try_lock() else {
+            return Err(Error::CacheLoading);
+        };
+
+        // Another task might have filled the cache right before the lock was taken
+        if !self.cache.load().courses.loaded {
+            self._refresh_cache().await?;
+        }
+
+        Ok(self.cache.load_full())
+    }
+
+    /// Builds a new cache while readers keep using the current one, then swaps it in.
+    ///
+    /// NOTE: `refresh_lock` must be held by the caller
+    async fn _refresh_cache(&self) -> Result<RefreshReport<Guid>> {
+        let json_provider = self.json_provider.read().await;
+        let current = self.cache.load_full();
+        let seeded = if current.courses.loaded {
+            None
+        } else {
+            // Start from the courses the provider kept parsed from an earlier run
+            let stored = json_provider.stored_courses().await?;
+            Some(ProviderCache::seeded(stored, |c| c.guid))
+        };
+        let course_jsons = json_provider.stream_course_jsons().await?;
+
+        let (next, report) = tokio::task::spawn_blocking(move || {
+            seeded
+                .as_ref()
+                .unwrap_or(&current.courses)
+                .refreshed(course_jsons, parse_course, |c| c.guid)
+        })
+        .await
+        .map_err(json_providers::Error::from)??;
+        self.store_cache(next);
+
+        if report.is_unchanged() {
+            debug!("Courses unchanged after refresh");
+            return Ok(report);
+        }
+
+        info!(
+            "Refreshed courses: {} added, {} changed, {} removed, {} reparsed, {} errors",
+            report.added.len(),
+            report.changed.len(),
+            report.removed.len(),
+            report.reparsed,
+            report.errors
+        );
+
+        Ok(report)
+    }
+
+    /// Swaps in a new cache along with its indexes.
+    ///
+    /// NOTE: `refresh_lock` must be held by the caller
+    fn store_cache(&self, courses: ProviderCache<Guid, CourseDetails, ParsingError>) {
+        let indexes = CourseIndexes::new(courses.items.values());
+        self.cache.store(Arc::new(CourseCache { courses, indexes }));
+    }
+}
+
+/// The cached courses along with their indexes, swapped in together so that readers never look
+/// up the courses of one version with the indexes of another
+#[derive(Default)]
+struct CourseCache {
+    courses: ProviderCache<Guid, CourseDetails, ParsingError>,
+    indexes: CourseIndexes,
+}
+
+/// A subject of the catalog and how many courses it has
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Subject {
+    /// Ex: "CSC"
+    pub code: String,
+    /// Ex: "Computer Science"
+    pub name: Option<String>,
+    pub course_count: usize,
+}
+
+/// Lookups of the cached courses by something else than their GUID
+#[derive(Debug, Default)]
+struct CourseIndexes {
+    /// Keyed on the uppercased subject code and number. Ex: ("CSC", "215"). Holds several
+    /// courses when the catalog gives them the same code
+    by_code: HashMap<(String, String), Vec<Guid>>,
+    /// Keyed on the uppercased subject code
+    by_subject: BTreeMap<String, SubjectIndex>,
+    equivalences: Equivalences,
+}
+
+#[derive(Debug)]
+struct SubjectIndex {
+    code: String,
+    name: Option<String>,
+    /// Sorted by number
+    courses: Vec<Guid>,
+}
+
+impl CourseIndexes {
+    fn new<'a>(courses: impl Iterator<Item = &'a CourseDetails>) -> Self {
+        let mut courses: Vec<&CourseDetails> = courses.collect();
+        // Ties broken by GUID so that the order doesn't depend on the order of the cache
+        courses.sort_by(|a, b| {
+            (&a.number, &a.name)
+                .cmp(&(&b.number, &b.name))
+                .then_with(|| a.guid.to_string().cmp(&b.guid.to_string()))
+        });
+
+        let mut indexes = Self {
+            equivalences: Equivalences::new(courses.iter().copied()),
+            ..Self::default()
+        };
+        for course in courses {
+            let subject_code = course.subject_code.to_uppercase();
+            let code = (subject_code.clone(), course.number.to_uppercase());
+            let same_code = indexes.by_code.entry(code).or_default();
+            if let Some(other) = same_code.first() {
+                warn!(
+                    "Courses {other} and {} are both {}",
+                    course.guid,
+                    course.code()
+                );
+            }
+            same_code.push(course.guid);
+
+            let subject = indexes
+                .by_subject
+                .entry(subject_code)
+                .or_insert_with(|| SubjectIndex {
+                    code: course.subject_code.clone(),
+                    name: None,
+                    courses: Vec::new(),
+                });
+            if subject.name.is_none() {
+                subject.name.clone_from(&course.subject_name);
+            }
+            subject.courses.push(course.guid);
+        }
+
+        indexes
     }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("Failed to get the course JSON: {0}")]
     JsonProvider(#[from] json_providers::Error),
+
+    /// The JSON was found but could not be parsed
+    #[error("Failed to parse the course JSON ({} errors)", .0.len())]
+    Parsing(Vec<ParsingError>),
+
+    /// The cache is being filled for the first time and can't serve requests yet
+    #[error("The courses are still being loaded")]
+    CacheLoading,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::data::providers::json_providers::FileJsonProvider;
+
+    use super::*;
+
+    fn course(guid: &str, subject_code: &str, number: &str) -> Value {
+        json!({
+            "url": format!("https://catalog/{subject_code}-{number}"),
+            "path": format!("/{subject_code}-{number}"),
+            "GUID": format!("{{{guid}}}"),
+            "subject_code": subject_code,
+            "subject_name": format!("Subject {subject_code}"),
+            "number": number,
+            "name": format!("Course {subject_code} {number}"),
+            "credits_min": "3.0",
+            "credits_max": null,
+            "description": "",
+            "prerequisite_narrative": null,
+            "corequisite_narrative": null,
+        })
+    }
+
+    fn provider(dir: &Path, courses: Value) -> CoursesProvider {
+        std::fs::write(
+            dir.join("courses.json"),
+            json!({ "courses": { "course": courses } }).to_string(),
+        )
+        .unwrap();
+
+        CoursesProvider::with(Box::new(
+            FileJsonProvider::init(dir, Path::new("courses.json")).unwrap(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn looks_up_courses_by_code_and_subject() {
+        let dir = tempfile::tempdir().unwrap();
+        let courses_provider = provider(
+            dir.path(),
+            json!([
+                course("00000000-0000-0000-0000-000000000002", "CSC", "215"),
+                course("00000000-0000-0000-0000-000000000001", "CSC", "105"),
+                course("00000000-0000-0000-0000-000000000003", "MAT", "150C/P"),
+            ]),
+        );
+
+        let csc_215 = courses_provider
+            .get_courses_by_code("csc", "215")
+            .await
+            .unwrap();
+        assert_eq!(csc_215.len(), 1);
+        assert_eq!(csc_215[0].code(), "CSC 215");
+        let mat_150 = courses_provider
+            .get_courses_by_code("MAT", "150c/p")
+            .await
+            .unwrap();
+        assert_eq!(mat_150.len(), 1);
+        assert!(courses_provider
+            .get_courses_by_code("CSC", "999")
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            courses_provider.get_subjects().await.unwrap(),
+            vec![
+                Subject {
+                    code: "CSC".to_owned(),
+                    name: Some("Subject CSC".to_owned()),
+                    course_count: 2,
+                },
+                Subject {
+                    code: "MAT".to_owned(),
+                    name: Some("Subject MAT".to_owned()),
+                    course_count: 1,
+                },
+            ]
+        );
+
+        let csc_courses = courses_provider
+            .get_subject_courses("csc")
+            .await
+            .unwrap()
+            .unwrap();
+        let numbers: Vec<&str> = csc_courses.iter().map(|c| c.number.as_str()).collect();
+        assert_eq!(numbers, vec!["105", "215"]);
+        assert!(courses_provider
+            .get_subject_courses("XYZ")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn finds_cross_listed_courses() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut psy_318 = course("00000000-0000-0000-0000-000000000001", "PSY", "318");
+        psy_318["crosslist_narrative"] = json!("Also listed as SOC 318.");
+        let courses_provider = provider(
+            dir.path(),
+            json!([
+                psy_318,
+                course("00000000-0000-0000-0000-000000000002", "SOC", "318"),
+                course("00000000-0000-0000-0000-000000000003", "MAT", "111"),
+            ]),
+        );
+
+        let guid = |s| Guid::try_from(s).unwrap();
+        let soc_equivalents = courses_provider
+            .get_equivalent_courses(&guid("00000000-0000-0000-0000-000000000002"))
+            .await
+            .unwrap()
+            .unwrap();
+        let codes: Vec<String> = soc_equivalents.iter().map(CourseDetails::code).collect();
+        assert_eq!(codes, vec!["PSY 318"]);
+
+        let mat_equivalents = courses_provider
+            .get_equivalent_courses(&guid("00000000-0000-0000-0000-000000000003"))
+            .await
+            .unwrap();
+        assert_eq!(mat_equivalents, Some(vec![]));
+        assert!(courses_provider
+            .get_equivalent_courses(&guid("00000000-0000-0000-0000-000000000009"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn rebuilds_indexes_on_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let courses_provider = provider(
+            dir.path(),
+            json!([course("00000000-0000-0000-0000-000000000001", "CSC", "105")]),
+        );
+        assert_eq!(courses_provider.get_subjects().await.unwrap().len(), 1);
+
+        provider(
+            dir.path(),
+            json!([course("00000000-0000-0000-0000-000000000001", "CSC", "106")]),
+        );
+        courses_provider.refresh_cache().await.unwrap();
+
+        assert!(courses_provider
+            .get_courses_by_code("CSC", "105")
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            courses_provider
+                .get_courses_by_code("CSC", "106")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_every_course_sharing_a_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let courses_provider = provider(
+            dir.path(),
+            json!([
+                course("00000000-0000-0000-0000-000000000002", "CSC", "105"),
+                course("00000000-0000-0000-0000-000000000001", "CSC", "105"),
+            ]),
+        );
+
+        let mut guids: Vec<String> = courses_provider
+            .get_courses_by_code("CSC", "105")
+            .await
+            .unwrap()
+            .iter()
+            .map(|course| course.guid.to_string())
+            .collect();
+        guids.sort();
+        assert_eq!(
+            guids,
+            vec![
+                "00000000-0000-0000-0000-000000000001",
+                "00000000-0000-0000-0000-000000000002"
+            ]
+        );
     }
 }
@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use vislog_core::parsing::guid::Guid;
+
+use super::{json_providers::Error, json_stream::JsonStream};
+
+/// The fields of a program or course JSON that it can be looked up by
+const KEY_FIELDS: [&str; 3] = ["GUID", "url", "path"];
+
+/// Lookup of single program or course JSONs by their catalog URL, catalog path or GUID.
+///
+/// Only where each item is in the file is kept, the item itself is read back out of the file
+/// when it gets looked up.
+#[derive(Debug, Default)]
+pub struct JsonIndex {
+    locations: Vec<Location>,
+    positions: HashMap<String, usize>,
+}
+
+#[derive(Debug)]
+enum Location {
+    /// Bytes of the file holding the item
+    Span(Range<u64>),
+    /// Items that aren't in an array, like the single object of a one item file, are kept as is
+    Inline(Value),
+}
+
+impl JsonIndex {
+    /// Consumes `stream` and indexes every item by each of its keys
+    pub async fn build(mut stream: JsonStream) -> Result<Self, Error> {
+        let mut index = Self::default();
+
+        while let Some(item) = stream.next_item().await {
+            let item = item?;
+            let position = index.locations.len();
+
+            for key in item_keys(&item.json) {
+                index.positions.entry(key).or_insert(position);
+            }
+            index.locations.push(match item.span {
+                Some(span) => Location::Span(span),
+                None => Location::Inline(item.json),
+            });
+        }
+
+        Ok(index)
+    }
+
+    /// Reads the item matching `id` out of `file`, which has to be the file the index was built
+    /// from
+    pub async fn get(&self, file: &Path, id: &str) -> Result<Option<Value>, Error> {
+        let Some(&position) = self.positions.get(&lookup_key(id)) else {
+            return Ok(None);
+        };
+
+        match &self.locations[position] {
+            Location::Span(span) => {
+                let (file, span) = (file.to_owned(), span.clone());
+                let item = tokio::task::spawn_blocking(move || read_span(file, span)).await??;
+                Ok(Some(item))
+            }
+            Location::Inline(item) => Ok(Some(item.clone())),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+}
+
+/// Reads the item in the bytes `span` of `file`
+fn read_span(file: PathBuf, span: Range<u64>) -> Result<Value, Error> {
+    let mut file = File::open(file)?;
+    file.seek(SeekFrom::Start(span.start))?;
+
+    let mut bytes = vec![0; (span.end - span.start) as usize];
+    file.read_exact(&mut bytes)?;
+
+    // Spans may start with what separates the item from the one before it
+    let bytes = bytes.trim_ascii_start();
+    let bytes = bytes.strip_prefix(b",").unwrap_or(bytes);
+
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// Consumes `stream` until an item matching `id` is found
+pub async fn find(mut stream: JsonStream, id: &str) -> Result<Value, Error> {
+    let key = lookup_key(id);
+
+    while let Some(item) = stream.next().await {
+        let item = item?;
+        if item_keys(&item).any(|k| k == key) {
+            return Ok(item);
+        }
+    }
+
+    Err(Error::NotFound(id.to_owned()))
+}
+
+/// Normalizes `id` so that the different spellings of the same item end up with the same key.
+///
+/// GUIDs are accepted with or without braces and in any case (Ex: `{D3561BF9-...}` and
+/// `d3561bf9-...`). URLs and paths are compared case insensitively since the catalog URLs are a
+/// lowercased copy of the catalog paths.
+pub fn lookup_key(id: &str) -> String {
+    let id = id.trim();
+    let unbraced = id.trim_start_matches('{').trim_end_matches('}');
+
+    match Guid::try_from(unbraced) {
+        Ok(guid) => guid.to_string(),
+        Err(_) => id.trim_end_matches('/').to_lowercase(),
+    }
+}
+
+fn item_keys(item: &Value) -> impl Iterator<Item = String> + '_ {
+    KEY_FIELDS
+        .iter()
+        .filter_map(|field| item.get(field)?.as_str())
+        .map(lookup_key)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use serde_json::json;
+
+    use super::*;
+
+    const GUID: &str = "{D3561BF9-40FA-4890-B4C4-F7EB0E9981CC}";
+    const PATH: &str = "/sitecore/content/Catalogs/Test/Courses/ACC-Accounting/100/ACC-180";
+    const URL: &str =
+        "https://catalog.example.com/en/catalogs/test/courses/acc-accounting/100/acc-180";
+
+    fn courses_json() -> Value {
+        json!({
+            "courses": {
+                "course": [
+                    { "GUID": "{0821AE73-3C32-45BB-824F-22B76A7602B6}", "number": "101" },
+                    { "GUID": GUID, "path": PATH, "url": URL, "number": "180" }
+                ]
+            }
+        })
+    }
+
+    fn courses_stream() -> JsonStream {
+        JsonStream::courses(Cursor::new(serde_json::to_vec(&courses_json()).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn looks_up_items_by_any_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("courses.json");
+        std::fs::write(&file, serde_json::to_vec_pretty(&courses_json()).unwrap()).unwrap();
+
+        let index = JsonIndex::build(JsonStream::courses(File::open(&file).unwrap()))
+            .await
+            .unwrap();
+
+        assert_eq!(index.len(), 2);
+        for id in [
+            GUID,
+            "d3561bf9-40fa-4890-b4c4-f7eb0e9981cc",
+            PATH,
+            &PATH.to_uppercase(),
+            URL,
+            &format!("{URL}/"),
+        ] {
+            let item = index.get(&file, id).await.unwrap();
+            assert_eq!(item.unwrap()["number"], "180", "{id}");
+        }
+        assert!(index
+            .get(&file, "/sitecore/content/Catalogs/Test/Courses")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn reads_back_every_item_of_the_catalog() {
+        let file = Path::new("../data/courses.json");
+        let index = JsonIndex::build(JsonStream::courses(File::open(file).unwrap()))
+            .await
+            .unwrap();
+
+        let mut courses = JsonStream::courses(File::open(file).unwrap());
+        while let Some(course) = courses.next().await {
+            let course = course.unwrap();
+            let guid = course["GUID"].as_str().unwrap();
+
+            assert_eq!(index.get(file, guid).await.unwrap(), Some(course));
+        }
+    }
+
+    #[tokio::test]
+    async fn finds_items_in_stream() {
+        let found = find(courses_stream(), URL).await.unwrap();
+        let not_found = find(courses_stream(), "ACC-999").await;
+
+        assert_eq!(found["number"], "180");
+        assert!(matches!(not_found, Err(Error::NotFound(id)) if id == "ACC-999"));
+    }
+}
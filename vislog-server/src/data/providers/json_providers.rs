@@ -1,52 +1,324 @@
-use std::path::PathBuf;
-
+use std::{
+    io::Cursor,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use reqwest::{
+    header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use thiserror::{self, Error};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use vislog_core::{CourseDetails, Program};
+use vislog_parser::ParsingError;
+
+use crate::{configs, data::snapshots::CatalogKind};
 
+use super::{
+    json_index::{self, JsonIndex},
+    json_stream::{JsonStream, Layout, COURSES_LAYOUT, PROGRAMS_LAYOUT},
+    JsonHash,
+};
+
+#[async_trait]
 pub trait JsonProvider: Send + Sync {
-    fn get_all_program_jsons(&self) -> Result<Vec<Value>, Error>;
-    fn get_program_json(&self, url: &str) -> Result<Value, Error>;
-    fn get_all_course_jsons(&self) -> Result<Vec<Value>, Error>;
-    fn get_course_json(&self, url: &str) -> Result<Value, Error>;
+    /// Streams the program JSONs one at a time without loading the whole catalog into memory
+    async fn stream_program_jsons(&self) -> Result<JsonStream, Error>;
+    /// Looks up a single program JSON by its catalog URL, catalog path or GUID
+    async fn get_program_json(&self, id: &str) -> Result<Value, Error>;
+    /// Streams the course JSONs one at a time without loading the whole catalog into memory
+    async fn stream_course_jsons(&self) -> Result<JsonStream, Error>;
+    /// Looks up a single course JSON by its catalog URL, catalog path or GUID
+    async fn get_course_json(&self, id: &str) -> Result<Value, Error>;
+
+    /// Picks up changes made to the data of `kind` at its source, like a freshly fetched data
+    /// file. Called before the cached items of `kind` are refreshed.
+    async fn reload(&self, _kind: CatalogKind) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Parse results of the program JSONs kept by the provider from an earlier run, along with
+    /// the hash of the raw JSON each was parsed from, so that they don't need to be parsed again
+    async fn stored_programs(
+        &self,
+    ) -> Result<Vec<(JsonHash, Result<Program, ParsingError>)>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Parse results of the course JSONs kept by the provider from an earlier run, along with the
+    /// hash of the raw JSON each was parsed from, so that they don't need to be parsed again
+    async fn stored_courses(
+        &self,
+    ) -> Result<Vec<(JsonHash, Result<CourseDetails, ParsingError>)>, Error> {
+        Ok(Vec::new())
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
     /// Error happened when reading from the file specified by the `path` when
     /// initializing the (FileJsonProvider)[FileJsonProvider]
+    #[error("Failed to read the catalog data: {0}")]
     Io(#[from] std::io::Error),
+
     /// Error happened when deserializing into (Value)[serde_json::Value]
+    #[error("The catalog data is not valid JSON: {0}")]
     DeserializeFromStr(#[from] serde_json::Error),
+
     /// Error happened because the format of given JSON didn't fit the expected layout
+    #[error("The catalog data has an unexpected layout: {0}")]
     Format(&'static str),
+
     /// File given doesn't exist
+    #[error("The catalog file {0:?} doesn't exist")]
     FileNotFound(PathBuf),
+
+    /// Error happened when sending a request to or reading a response from the catalog API
+    #[error("Request to the catalog API failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The catalog API responded with an unexpected status code
+    #[error("The catalog API responded with {0}")]
+    HttpStatus(StatusCode),
+
+    /// The configured base URL of the catalog API is not a valid URL
+    #[error("Invalid catalog API URL {0:?}")]
+    InvalidUrl(String),
+
+    /// No item matches the URL, path or GUID given
+    #[error("No item matches {0:?}")]
+    NotFound(String),
+
+    /// Error happened when reading from or writing to the SQLite database
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// A blocking task reading or writing the data panicked or got cancelled
+    #[error("Reading or writing the catalog data did not finish: {0}")]
+    Join(#[from] tokio::task::JoinError),
+}
+
+/// Fetches catalog JSON straight from the SmartCatalog API.
+///
+/// Responses are cached in `cache_dir` (when configured) along with their `ETag` and
+/// `Last-Modified` headers so that later requests are conditional and a cached copy can still be
+/// served when the API is unreachable.
+#[derive(Debug, Clone)]
+pub struct WebJsonProvider {
+    client: reqwest::Client,
+    base_url: String,
+    catalog_path: String,
+    max_retries: u32,
+    retry_backoff: Duration,
+    cache_dir: Option<PathBuf>,
+}
+
+/// Validators from the response headers saved next to a cached response body
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+const PROGRAMS_API: &str = "apis/progAPI";
+const COURSES_API: &str = "apis/courseAPI";
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BACKOFF_MILLIS: u64 = 500;
+
+impl WebJsonProvider {
+    pub fn init(config: &configs::WebProvider) -> Result<Self, Error> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(
+                config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            ))
+            .build()?;
+
+        if let Some(cache_dir) = &config.cache_dir {
+            std::fs::create_dir_all(cache_dir)?;
+        }
+
+        Ok(Self {
+            client,
+            base_url: config.base_url.trim_end_matches('/').to_owned(),
+            catalog_path: config.catalog_path.clone(),
+            max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            retry_backoff: Duration::from_millis(
+                config
+                    .retry_backoff_millis
+                    .unwrap_or(DEFAULT_RETRY_BACKOFF_MILLIS),
+            ),
+            cache_dir: config.cache_dir.clone(),
+        })
+    }
+
+    /// Builds the API url for the item at `path` in the catalog. Ex: the whole catalog is at
+    /// `/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue`
+    fn api_url(&self, api: &str, path: &str) -> Result<Url, Error> {
+        let endpoint = format!("{}/{}", self.base_url, api);
+
+        Url::parse_with_params(&endpoint, &[("path", path), ("format", "json")])
+            .map_err(|_| Error::InvalidUrl(endpoint))
+    }
+
+    /// GETs `url` and returns the body, retrying with exponential backoff on timeouts, connection
+    /// errors and 5xx responses
+    async fn fetch(&self, url: Url) -> Result<Vec<u8>, Error> {
+        let cached = self.read_cache(&url).await;
+
+        let mut attempt = 0;
+        let err = loop {
+            let mut request = self.client.get(url.clone());
+            if let Some((validators, _)) = &cached {
+                if let Some(etag) = &validators.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &validators.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            let err = match request.send().await {
+                Ok(response) if response.status() == StatusCode::NOT_MODIFIED => {
+                    if let Some((_, body)) = cached {
+                        debug!("Using cached response for {url}");
+                        return Ok(body);
+                    }
+
+                    Error::HttpStatus(StatusCode::NOT_MODIFIED)
+                }
+                Ok(response) if response.status().is_success() => {
+                    let validators = CacheValidators {
+                        etag: header_to_string(response.headers().get(ETAG)),
+                        last_modified: header_to_string(response.headers().get(LAST_MODIFIED)),
+                    };
+                    let body = response.bytes().await?.to_vec();
+
+                    if let Err(err) = self.write_cache(&url, &validators, &body).await {
+                        warn!("Failed to cache response for {url}: {err}");
+                    }
+
+                    return Ok(body);
+                }
+                Ok(response)
+                    if response.status().is_server_error()
+                        || response.status() == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    Error::HttpStatus(response.status())
+                }
+                Ok(response) => return Err(Error::HttpStatus(response.status())),
+                Err(err) if err.is_timeout() || err.is_connect() || err.is_request() => {
+                    Error::Http(err)
+                }
+                Err(err) => return Err(Error::Http(err)),
+            };
+
+            if attempt >= self.max_retries {
+                break err;
+            }
+
+            let backoff = self.retry_backoff * 2u32.pow(attempt);
+            warn!("Request to {url} failed ({err}), retrying in {backoff:?}");
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        };
+
+        // Serve stale data rather than nothing when the API is down
+        match cached {
+            Some((_, body)) => {
+                warn!("Falling back to cached response for {url}: {err}");
+                Ok(body)
+            }
+            None => Err(err),
+        }
+    }
+
+    fn cache_paths(&self, url: &Url) -> Option<(PathBuf, PathBuf)> {
+        let cache_dir = self.cache_dir.as_ref()?;
+        let key = format!("{:x}", Sha256::digest(url.as_str().as_bytes()));
+
+        Some((
+            cache_dir.join(format!("{key}.json")),
+            cache_dir.join(format!("{key}.validators.json")),
+        ))
+    }
+
+    async fn read_cache(&self, url: &Url) -> Option<(CacheValidators, Vec<u8>)> {
+        let (body_path, validators_path) = self.cache_paths(url)?;
+
+        let body = tokio::fs::read(body_path).await.ok()?;
+        let validators = tokio::fs::read(validators_path)
+            .await
+            .ok()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default();
+
+        Some((validators, body))
+    }
+
+    async fn write_cache(
+        &self,
+        url: &Url,
+        validators: &CacheValidators,
+        body: &[u8],
+    ) -> Result<(), Error> {
+        let Some((body_path, validators_path)) = self.cache_paths(url) else {
+            return Ok(());
+        };
+
+        tokio::fs::write(body_path, body).await?;
+        tokio::fs::write(validators_path, serde_json::to_vec(validators)?).await?;
+
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct WebJsonProvider;
+fn header_to_string(value: Option<&HeaderValue>) -> Option<String> {
+    value.and_then(|v| v.to_str().ok()).map(|v| v.to_owned())
+}
+
+impl WebJsonProvider {
+    /// Catalog paths can be asked for directly while URLs and GUIDs are looked up in the whole
+    /// catalog, which is usually a cheap conditional request thanks to the response cache
+    async fn get_item_json(&self, api: &str, layout: Layout, id: &str) -> Result<Value, Error> {
+        let path = if id.starts_with('/') {
+            id
+        } else {
+            &self.catalog_path
+        };
 
+        let url = self.api_url(api, path)?;
+        let stream = JsonStream::from_reader(Cursor::new(self.fetch(url).await?), layout);
+
+        json_index::find(stream, id).await
+    }
+}
+
+#[async_trait]
 impl JsonProvider for WebJsonProvider {
-    fn get_all_program_jsons(&self) -> Result<Vec<Value>, Error> {
-        todo!()
+    async fn stream_program_jsons(&self) -> Result<JsonStream, Error> {
+        let url = self.api_url(PROGRAMS_API, &self.catalog_path)?;
+        Ok(JsonStream::programs(Cursor::new(self.fetch(url).await?)))
     }
 
-    fn get_program_json(&self, _url: &str) -> Result<Value, Error> {
-        todo!()
+    async fn get_program_json(&self, id: &str) -> Result<Value, Error> {
+        self.get_item_json(PROGRAMS_API, PROGRAMS_LAYOUT, id).await
     }
 
-    fn get_all_course_jsons(&self) -> Result<Vec<Value>, Error> {
-        todo!()
+    async fn stream_course_jsons(&self) -> Result<JsonStream, Error> {
+        let url = self.api_url(COURSES_API, &self.catalog_path)?;
+        Ok(JsonStream::courses(Cursor::new(self.fetch(url).await?)))
     }
 
-    fn get_course_json(&self, _url: &str) -> Result<Value, Error> {
-        todo!()
+    async fn get_course_json(&self, id: &str) -> Result<Value, Error> {
+        self.get_item_json(COURSES_API, COURSES_LAYOUT, id).await
     }
 }
 
@@ -54,6 +326,15 @@ impl JsonProvider for WebJsonProvider {
 pub struct FileJsonProvider {
     data_root: PathBuf,
     all_jsons_file: PathBuf,
+    index: Arc<RwLock<Option<LoadedIndex>>>,
+}
+
+/// Index of `all_jsons_file` as it was when it was last modified at `modified`
+#[derive(Debug)]
+struct LoadedIndex {
+    layout: Layout,
+    modified: SystemTime,
+    index: JsonIndex,
 }
 
 impl FileJsonProvider {
@@ -65,116 +346,364 @@ impl FileJsonProvider {
         path.push(&all_programs_file);
 
         // Check if the file exists
-        if path.try_exists()? == false {
+        if !path.try_exists()? {
             return Err(Error::FileNotFound(path));
         }
 
         Ok(Self {
             data_root,
             all_jsons_file: all_programs_file,
+            index: Arc::new(RwLock::new(None)),
         })
     }
 }
 
-impl JsonProvider for FileJsonProvider {
-    fn get_all_program_jsons(&self) -> Result<Vec<Value>, Error> {
+impl FileJsonProvider {
+    /// Opens `all_jsons_file` for reading on a blocking thread
+    async fn open_all_jsons_file(&self) -> Result<std::io::BufReader<std::fs::File>, Error> {
+        let file = tokio::fs::File::open(self.all_jsons_path())
+            .await?
+            .into_std()
+            .await;
+
+        Ok(std::io::BufReader::new(file))
+    }
+
+    fn all_jsons_path(&self) -> PathBuf {
         let mut path = self.data_root.clone();
         path.push(&self.all_jsons_file);
+        path
+    }
 
-        let json_str = std::fs::read_to_string(path)?;
+    /// Looks up `id` in the index of `all_jsons_file`, (re)building the index when it's missing or
+    /// the file has been replaced since it was built
+    async fn get_item_json(&self, layout: Layout, id: &str) -> Result<Value, Error> {
+        let modified = tokio::fs::metadata(self.all_jsons_path())
+            .await?
+            .modified()?;
+
+        {
+            let index = self.index.read().await;
+            if let Some(loaded) = index.as_ref() {
+                if loaded.layout == layout && loaded.modified == modified {
+                    return loaded
+                        .index
+                        .get(&self.all_jsons_path(), id)
+                        .await?
+                        .ok_or_else(|| Error::NotFound(id.to_owned()));
+                }
+            }
+        }
 
-        let json: Value = serde_json::from_str(&json_str)?;
+        let mut index = self.index.write().await;
+        let stream = JsonStream::from_reader(self.open_all_jsons_file().await?, layout);
+        let loaded = index.insert(LoadedIndex {
+            layout,
+            modified,
+            index: JsonIndex::build(stream).await?,
+        });
+        debug!(
+            "Indexed {} items in {:?}",
+            loaded.index.len(),
+            self.all_jsons_file
+        );
+
+        loaded
+            .index
+            .get(&self.all_jsons_path(), id)
+            .await?
+            .ok_or_else(|| Error::NotFound(id.to_owned()))
+    }
+}
 
-        // Index into API response to grab the actual JSON array containing the
-        // Program Objects which is nested in the format: `obj.programs.program`
-        let program_jsons = {
-            let Value::Object(json) = json else {
-                return Err(Error::Format("expected a JSON object"));
-            };
+#[async_trait]
+impl JsonProvider for FileJsonProvider {
+    async fn stream_program_jsons(&self) -> Result<JsonStream, Error> {
+        Ok(JsonStream::programs(self.open_all_jsons_file().await?))
+    }
 
-            let (_, programs_json) = json
-                .into_iter()
-                .filter(|(key, _)| key == "programs")
-                .next()
-                .ok_or(Error::Format("missing field `programs`"))?;
+    async fn get_program_json(&self, id: &str) -> Result<Value, Error> {
+        self.get_item_json(PROGRAMS_LAYOUT, id).await
+    }
 
-            let Value::Object(program_json) = programs_json else {
-                return Err(Error::Format(
-                    "expected field `programs` to be a JSON object",
-                ));
-            };
+    async fn stream_course_jsons(&self) -> Result<JsonStream, Error> {
+        Ok(JsonStream::courses(self.open_all_jsons_file().await?))
+    }
 
-            let (_, programs_json) = program_json
-                .into_iter()
-                .filter(|(key, _)| key == "program")
-                .next()
-                .ok_or(Error::Format("missing field `program`"))?;
+    async fn get_course_json(&self, id: &str) -> Result<Value, Error> {
+        self.get_item_json(COURSES_LAYOUT, id).await
+    }
+}
 
-            let Value::Array(program_jsons) = programs_json else {
-                return Err(Error::Format("expected field `program` to be a JSON array"));
-            };
+#[cfg(test)]
+mod test {
+    use std::path::Path;
 
-            program_jsons
-        };
+    use serde_json::json;
+    use wiremock::{
+        matchers::{header, header_exists, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
 
-        Ok(program_jsons)
+    use super::*;
+
+    async fn all_programs(provider: &dyn JsonProvider) -> Result<Vec<Value>, Error> {
+        provider.stream_program_jsons().await?.collect().await
     }
 
-    fn get_program_json(&self, url: &str) -> Result<Value, Error> {
-        let mut path = self.data_root.clone();
-        path.push(url);
+    async fn all_courses(provider: &dyn JsonProvider) -> Result<Vec<Value>, Error> {
+        provider.stream_course_jsons().await?.collect().await
+    }
 
-        let json_str = std::fs::read_to_string(path)?;
+    const CATALOG_PATH: &str = "/sitecore/content/Catalogs/Test-Catalog";
 
-        let program_json: Value = serde_json::from_str(&json_str)?;
+    fn web_provider(server: &MockServer, cache_dir: Option<PathBuf>) -> WebJsonProvider {
+        WebJsonProvider::init(&configs::WebProvider {
+            base_url: server.uri(),
+            catalog_path: CATALOG_PATH.to_owned(),
+            timeout_secs: Some(5),
+            max_retries: Some(2),
+            retry_backoff_millis: Some(1),
+            cache_dir,
+        })
+        .unwrap()
+    }
 
-        Ok(program_json)
+    fn programs_body() -> Value {
+        json!({
+            "programs": {
+                "program": [
+                    { "title": "Major in Computer Science" },
+                    { "title": "Minor in Computer Science" }
+                ]
+            }
+        })
     }
 
-    fn get_all_course_jsons(&self) -> Result<Vec<Value>, Error> {
-        let mut path = self.data_root.clone();
-        path.push(&self.all_jsons_file);
+    #[tokio::test]
+    async fn fetches_all_programs_from_catalog_api() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/apis/progAPI"))
+            .and(query_param("path", CATALOG_PATH))
+            .and(query_param("format", "json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(programs_body()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let programs = all_programs(&web_provider(&server, None)).await.unwrap();
+
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0]["title"], "Major in Computer Science");
+    }
 
-        let json_str = std::fs::read_to_string(path)?;
+    #[tokio::test]
+    async fn fetches_single_course_by_path() {
+        let server = MockServer::start().await;
+        let course_path = format!("{CATALOG_PATH}/Courses/CSC-Computer-Science/200/CSC-215");
+        Mock::given(method("GET"))
+            .and(path("/apis/courseAPI"))
+            .and(query_param("path", course_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "courses": { "course": { "path": course_path, "number": "215" } }
+            })))
+            .mount(&server)
+            .await;
+
+        let course = web_provider(&server, None)
+            .get_course_json(&course_path)
+            .await
+            .unwrap();
+
+        assert_eq!(course["number"], "215");
+    }
 
-        let json: Value = serde_json::from_str(&json_str)?;
+    #[tokio::test]
+    async fn looks_up_program_by_guid_in_catalog() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/apis/progAPI"))
+            .and(query_param("path", CATALOG_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "programs": {
+                    "program": [
+                        { "GUID": "{0821AE73-3C32-45BB-824F-22B76A7602B6}", "title": "A" },
+                        { "GUID": "{D3561BF9-40FA-4890-B4C4-F7EB0E9981CC}", "title": "B" }
+                    ]
+                }
+            })))
+            .mount(&server)
+            .await;
+        let provider = web_provider(&server, None);
+
+        let program = provider
+            .get_program_json("d3561bf9-40fa-4890-b4c4-f7eb0e9981cc")
+            .await
+            .unwrap();
+        let missing = provider
+            .get_program_json("{00000000-0000-0000-0000-000000000000}")
+            .await;
+
+        assert_eq!(program["title"], "B");
+        assert!(matches!(missing, Err(Error::NotFound(_))));
+    }
 
-        // Index into API response to grab the actual JSON array containing the
-        // Program Objects which is nested in the format: `obj.programs.program`
-        let course_jsons = {
-            let Value::Object(json) = json else {
-                return Err(Error::Format("expected a JSON object"));
-            };
+    #[tokio::test]
+    async fn looks_up_course_in_file() {
+        let provider = FileJsonProvider::init("../data", "courses.json").unwrap();
+
+        let by_guid = provider
+            .get_course_json("{D3561BF9-40FA-4890-B4C4-F7EB0E9981CC}")
+            .await
+            .unwrap();
+        let by_path = provider
+            .get_course_json("/sitecore/content/Catalogs/Union-University/2023/Academic-Catalogue-Undergraduate-Catalogue/Courses/ACC-Accounting/100/ACC-180")
+            .await
+            .unwrap();
+        let missing = provider.get_course_json("ACC-999").await;
+
+        assert_eq!(by_guid["number"], "180");
+        assert_eq!(by_guid, by_path);
+        assert!(matches!(missing, Err(Error::NotFound(id)) if id == "ACC-999"));
+    }
 
-            let (_, courses_json) = json
-                .into_iter()
-                .filter(|(key, _)| key == "courses")
-                .next()
-                .ok_or(Error::Format("missing field `courses`"))?;
+    #[tokio::test]
+    async fn reindexes_file_after_it_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("programs.json");
+        let write_programs = |title: &str| {
+            let json = json!({
+                "programs": { "program": [{ "url": "https://catalog/a", "title": title }] }
+            });
+            std::fs::write(&file, serde_json::to_vec(&json).unwrap()).unwrap();
+        };
 
-            let Value::Object(course_json) = courses_json else {
-                return Err(Error::Format(
-                    "expected field `courses` to be a JSON object",
-                ));
-            };
+        write_programs("Old");
+        let provider = FileJsonProvider::init(dir.path(), Path::new("programs.json")).unwrap();
+        let old = provider
+            .get_program_json("https://catalog/a")
+            .await
+            .unwrap();
+
+        write_programs("New");
+        // Make sure the modified time changes even on filesystems with coarse timestamps
+        let f = std::fs::File::options().append(true).open(&file).unwrap();
+        f.set_modified(SystemTime::now() + Duration::from_secs(1))
+            .unwrap();
+        let new = provider
+            .get_program_json("https://catalog/a")
+            .await
+            .unwrap();
+
+        assert_eq!(old["title"], "Old");
+        assert_eq!(new["title"], "New");
+    }
 
-            let (_, courses_json) = course_json
-                .into_iter()
-                .filter(|(key, _)| key == "course")
-                .next()
-                .ok_or(Error::Format("missing field `course`"))?;
+    #[tokio::test]
+    async fn retries_on_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(programs_body()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let programs = all_programs(&web_provider(&server, None)).await.unwrap();
+
+        assert_eq!(programs.len(), 2);
+    }
 
-            let Value::Array(course_jsons) = courses_json else {
-                return Err(Error::Format("expected field `course` to be a JSON array"));
-            };
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let result = all_programs(&web_provider(&server, None)).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::HttpStatus(StatusCode::INTERNAL_SERVER_ERROR))
+        ));
+    }
 
-            course_jsons
-        };
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = all_courses(&web_provider(&server, None)).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::HttpStatus(StatusCode::NOT_FOUND))
+        ));
+    }
 
-        Ok(course_jsons)
+    #[tokio::test]
+    async fn uses_cached_response_when_not_modified() {
+        let server = MockServer::start().await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let provider = web_provider(&server, Some(cache_dir.path().to_owned()));
+
+        Mock::given(method("GET"))
+            .and(header("if-none-match", "\"v1\""))
+            .and(header_exists("if-modified-since"))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .insert_header("Last-Modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+                    .set_body_json(programs_body()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let first = all_programs(&provider).await.unwrap();
+        let second = all_programs(&provider).await.unwrap();
+
+        assert_eq!(first, second);
     }
 
-    fn get_course_json(&self, _url: &str) -> Result<Value, Error> {
-        todo!()
+    #[tokio::test]
+    async fn falls_back_to_cache_when_api_is_down() {
+        let server = MockServer::start().await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let provider = web_provider(&server, Some(cache_dir.path().to_owned()));
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(programs_body()))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(502))
+            .mount(&server)
+            .await;
+
+        let first = all_programs(&provider).await.unwrap();
+        let second = all_programs(&provider).await.unwrap();
+
+        assert_eq!(first, second);
     }
 }
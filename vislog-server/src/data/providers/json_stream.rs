@@ -0,0 +1,525 @@
+use std::{cell::Cell, fmt, io::Read, ops::Range, rc::Rc};
+
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserialize,
+};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::json_providers::Error;
+
+/// How many parsed items may be buffered before the deserializer waits for the consumer
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Where the items are nested in an API response. Ex: `obj.programs.program`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    outer: &'static str,
+    inner: &'static str,
+    missing_outer: &'static str,
+    missing_inner: &'static str,
+    outer_not_object: &'static str,
+    inner_not_array: &'static str,
+}
+
+pub const PROGRAMS_LAYOUT: Layout = Layout {
+    outer: "programs",
+    inner: "program",
+    missing_outer: "missing field `programs`",
+    missing_inner: "missing field `program`",
+    outer_not_object: "expected field `programs` to be a JSON object",
+    inner_not_array: "expected field `program` to be a JSON array",
+};
+
+pub const COURSES_LAYOUT: Layout = Layout {
+    outer: "courses",
+    inner: "course",
+    missing_outer: "missing field `courses`",
+    missing_inner: "missing field `course`",
+    outer_not_object: "expected field `courses` to be a JSON object",
+    inner_not_array: "expected field `course` to be a JSON array",
+};
+
+/// Item JSONs yielded one at a time as they are deserialized from an API response.
+///
+/// The response is deserialized on a blocking thread so that only the items that haven't been
+/// consumed yet are held in memory instead of the whole document. Dropping the stream stops the
+/// deserializer.
+#[derive(Debug)]
+pub struct JsonStream {
+    rx: mpsc::Receiver<Result<Item, Error>>,
+}
+
+/// An item along with where it was found
+#[derive(Debug)]
+pub struct Item {
+    pub json: Value,
+    /// Bytes of the document the item was read from, possibly starting with the whitespace and
+    /// comma that separate it from the previous item. `None` when the items don't come from an
+    /// array of a document
+    pub span: Option<Range<u64>>,
+}
+
+impl JsonStream {
+    /// Starts deserializing the items nested in `reader` according to `layout`
+    pub fn from_reader<R>(reader: R, layout: Layout) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        tokio::task::spawn_blocking(move || {
+            let read = Rc::new(Cell::new(0));
+            let walk = Walk {
+                layout,
+                tx,
+                expected: Cell::new(EXPECTED_OBJECT),
+                read: read.clone(),
+            };
+            let mut deserializer =
+                serde_json::Deserializer::from_reader(CountingReader { reader, read });
+            let result = RootSeed(&walk)
+                .deserialize(&mut deserializer)
+                .and_then(|_| deserializer.end());
+
+            match result {
+                Ok(()) => {}
+                // The consumer is gone so there is nobody left to report to
+                Err(_) if walk.tx.is_closed() => {}
+                // Items are deserialized into `Value`s which accept any JSON, so data errors can
+                // only come from the layout not matching
+                Err(err) if err.is_data() => {
+                    let _ = walk
+                        .tx
+                        .blocking_send(Err(Error::Format(walk.expected.get())));
+                }
+                Err(err) => {
+                    let _ = walk.tx.blocking_send(Err(err.into()));
+                }
+            }
+        });
+
+        Self { rx }
+    }
+
+    /// Yields the items produced by `produce` on a blocking thread. `produce` is given a callback
+    /// to hand over each item which returns `false` once the stream has been dropped.
+    pub fn from_blocking<F>(produce: F) -> Self
+    where
+        F: FnOnce(&mut dyn FnMut(Value) -> bool) -> Result<(), Error> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+        tokio::task::spawn_blocking(move || {
+            let mut send = |json| tx.blocking_send(Ok(Item { json, span: None })).is_ok();
+            if let Err(err) = produce(&mut send) {
+                let _ = tx.blocking_send(Err(err));
+            }
+        });
+
+        Self { rx }
+    }
+
+    /// Starts deserializing the program JSONs nested in `reader` at `obj.programs.program`
+    pub fn programs<R: Read + Send + 'static>(reader: R) -> Self {
+        Self::from_reader(reader, PROGRAMS_LAYOUT)
+    }
+
+    /// Starts deserializing the course JSONs nested in `reader` at `obj.courses.course`
+    pub fn courses<R: Read + Send + 'static>(reader: R) -> Self {
+        Self::from_reader(reader, COURSES_LAYOUT)
+    }
+
+    /// Waits for the next item. Returns `None` once all items have been yielded.
+    pub async fn next(&mut self) -> Option<Result<Value, Error>> {
+        Some(self.next_item().await?.map(|item| item.json))
+    }
+
+    /// Waits for the next item along with where it was found in the document
+    pub async fn next_item(&mut self) -> Option<Result<Item, Error>> {
+        self.rx.recv().await
+    }
+
+    /// Waits for all remaining items, stopping at the first error. Only meant for tests, everything
+    /// else parses the items one at a time with `parse_each`
+    #[cfg(test)]
+    pub async fn collect(mut self) -> Result<Vec<Value>, Error> {
+        let mut items = Vec::new();
+        while let Some(item) = self.next().await {
+            items.push(item?);
+        }
+
+        Ok(items)
+    }
+
+    /// Waits for the next item while blocking the current thread, for consumers running on a
+    /// blocking thread themselves. Panics when called from an async context
+    pub fn blocking_next(&mut self) -> Option<Result<Value, Error>> {
+        Some(self.rx.blocking_recv()?.map(|item| item.json))
+    }
+
+    /// Parses every remaining item as soon as it is yielded so that the raw JSON of only one item
+    /// is held at a time. Stops at the first error of the stream, not at the ones of `parse`.
+    ///
+    /// The items are parsed on a blocking thread to keep the async workers free.
+    pub async fn parse_each<T, E, P>(mut self, parse: P) -> Result<(Vec<T>, Vec<E>), Error>
+    where
+        T: Send + 'static,
+        E: Send + 'static,
+        P: Fn(Value) -> Result<T, E> + Send + 'static,
+    {
+        tokio::task::spawn_blocking(move || {
+            let (mut parsed, mut errors) = (Vec::new(), Vec::new());
+            while let Some(item) = self.blocking_next() {
+                match parse(item?) {
+                    Ok(item) => parsed.push(item),
+                    Err(err) => errors.push(err),
+                }
+            }
+
+            Ok((parsed, errors))
+        })
+        .await?
+    }
+}
+
+const EXPECTED_OBJECT: &str = "expected a JSON object";
+const STREAM_CLOSED: &str = "stream closed";
+
+/// Keeps count of the bytes read out of `reader`
+struct CountingReader<R> {
+    reader: R,
+    read: Rc<Cell<u64>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.read.set(self.read.get() + n as u64);
+        Ok(n)
+    }
+}
+
+/// State shared by the visitors while walking down to the items
+struct Walk {
+    layout: Layout,
+    tx: mpsc::Sender<Result<Item, Error>>,
+    /// What the layout is reported to be missing if deserializing fails with a data error at the
+    /// current point of the walk
+    expected: Cell<&'static str>,
+    /// Bytes of the document read so far. The deserializer reads one byte at a time and only
+    /// looks ahead when it has to, so right after an object or array has been deserialized this is
+    /// where it ends
+    read: Rc<Cell<u64>>,
+}
+
+impl Walk {
+    /// Raises a data error reported as `Error::Format(expected)`
+    fn mismatch<E: de::Error>(&self, expected: &'static str) -> E {
+        self.expected.set(expected);
+        de::Error::custom(expected)
+    }
+}
+
+/// Visits the top level object looking for `layout.outer`
+struct RootSeed<'a>(&'a Walk);
+
+impl<'de> DeserializeSeed<'de> for RootSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.0.expected.set(EXPECTED_OBJECT);
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RootSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(EXPECTED_OBJECT)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let walk = self.0;
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if !found && key == walk.layout.outer {
+                map.next_value_seed(OuterSeed(walk))?;
+                found = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        if !found {
+            return Err(walk.mismatch(walk.layout.missing_outer));
+        }
+
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, _seq: A) -> Result<(), A::Error> {
+        Err(self.0.mismatch(EXPECTED_OBJECT))
+    }
+}
+
+/// Visits the object at `layout.outer` looking for `layout.inner`
+struct OuterSeed<'a>(&'a Walk);
+
+impl<'de> DeserializeSeed<'de> for OuterSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.0.expected.set(self.0.layout.outer_not_object);
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for OuterSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0.layout.outer_not_object)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let walk = self.0;
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            if !found && key == walk.layout.inner {
+                map.next_value_seed(ItemsSeed(walk))?;
+                found = true;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        if !found {
+            return Err(walk.mismatch(walk.layout.missing_inner));
+        }
+
+        Ok(())
+    }
+}
+
+/// Sends every item in the array at `layout.inner` through the channel as soon as it is parsed
+struct ItemsSeed<'a>(&'a Walk);
+
+impl ItemsSeed<'_> {
+    fn send<E: de::Error>(&self, item: Item) -> Result<(), E> {
+        self.0
+            .tx
+            .blocking_send(Ok(item))
+            .map_err(|_| de::Error::custom(STREAM_CLOSED))
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for ItemsSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.0.expected.set(self.0.layout.inner_not_array);
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ItemsSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0.layout.inner_not_array)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut start = self.0.read.get();
+        while let Some(json) = seq.next_element::<Value>()? {
+            let end = self.0.read.get();
+            // Scalars are only known to have ended once the byte after them was read
+            let span = (json.is_object() || json.is_array()).then_some(start..end);
+            self.send(Item { json, span })?;
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    // The API returns a single object instead of an array when asked for a single item
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<(), A::Error> {
+        let json = Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        self.send(Item { json, span: None })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{io::Cursor, time::Duration};
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    fn stream_programs(json: &str) -> JsonStream {
+        JsonStream::programs(Cursor::new(json.to_owned().into_bytes()))
+    }
+
+    /// Reader which closes its channel once it gets dropped
+    struct DropNotifier<R> {
+        reader: R,
+        _dropped: oneshot::Sender<()>,
+    }
+
+    impl<R> DropNotifier<R> {
+        fn new(reader: R) -> (Self, oneshot::Receiver<()>) {
+            let (tx, rx) = oneshot::channel();
+            (
+                Self {
+                    reader,
+                    _dropped: tx,
+                },
+                rx,
+            )
+        }
+    }
+
+    impl<R: Read> Read for DropNotifier<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reader.read(buf)
+        }
+    }
+
+    /// Reader repeating `item` forever
+    struct Endless {
+        item: &'static [u8],
+        pos: usize,
+    }
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            for byte in buf.iter_mut() {
+                *byte = self.item[self.pos];
+                self.pos = (self.pos + 1) % self.item.len();
+            }
+
+            Ok(buf.len())
+        }
+    }
+
+    #[tokio::test]
+    async fn yields_items_in_order() {
+        let json = r#"{
+            "info": { "ignored": [1, 2, 3] },
+            "programs": { "count": 2, "program": [{ "title": "A" }, { "title": "B" }] }
+        }"#;
+
+        let programs = stream_programs(json).collect().await.unwrap();
+
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0]["title"], "A");
+        assert_eq!(programs[1]["title"], "B");
+    }
+
+    #[tokio::test]
+    async fn yields_single_object_as_one_item() {
+        let json = r#"{ "courses": { "course": { "number": "215" } } }"#;
+        let mut stream = JsonStream::courses(Cursor::new(json.as_bytes().to_vec()));
+
+        assert_eq!(stream.next().await.unwrap().unwrap()["number"], "215");
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_missing_fields() {
+        let missing_outer = stream_programs(r#"{ "courses": {} }"#).collect().await;
+        let missing_inner = stream_programs(r#"{ "programs": {} }"#).collect().await;
+
+        assert!(matches!(
+            missing_outer,
+            Err(Error::Format("missing field `programs`"))
+        ));
+        assert!(matches!(
+            missing_inner,
+            Err(Error::Format("missing field `program`"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reports_unexpected_layout() {
+        let not_object = stream_programs("[]").collect().await;
+        let not_array = stream_programs(r#"{ "programs": { "program": 1 } }"#)
+            .collect()
+            .await;
+
+        assert!(matches!(
+            not_object,
+            Err(Error::Format("expected a JSON object"))
+        ));
+        assert!(matches!(
+            not_array,
+            Err(Error::Format("expected field `program` to be a JSON array"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn yields_items_before_reporting_syntax_errors() {
+        let mut stream = stream_programs(r#"{ "programs": { "program": [{ "title": "A" }, {"#);
+
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(Error::DeserializeFromStr(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn parses_each_item() {
+        let json =
+            r#"{ "programs": { "program": [{ "title": "A" }, { "id": 1 }, { "title": "B" }] } }"#;
+
+        let (titles, errors) = stream_programs(json)
+            .parse_each(|item| match &item["title"] {
+                Value::String(title) => Ok(title.clone()),
+                _ => Err(item),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(titles, ["A", "B"]);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stops_when_dropped() {
+        // Never runs out of items so the deserializer can only finish by stopping
+        let endless_items = Cursor::new(r#"{ "programs": { "program": ["#).chain(Endless {
+            item: br#"{ "title": "A" },"#,
+            pos: 0,
+        });
+        let (reader, dropped) = DropNotifier::new(endless_items);
+        let mut stream = JsonStream::programs(reader);
+
+        assert!(stream.next().await.unwrap().is_ok());
+        drop(stream);
+
+        let stopped = tokio::time::timeout(Duration::from_secs(5), dropped).await;
+        assert!(
+            stopped.is_ok(),
+            "Deserializer kept running after the stream was dropped"
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_the_same_items_as_parsing_the_whole_file() {
+        let json = std::fs::read_to_string("../data/courses.json").unwrap();
+        let whole: Value = serde_json::from_str(&json).unwrap();
+
+        let file = std::fs::File::open("../data/courses.json").unwrap();
+        let streamed = JsonStream::courses(std::io::BufReader::new(file))
+            .collect()
+            .await
+            .unwrap();
+
+        assert_eq!(&Value::Array(streamed), &whole["courses"]["course"]);
+    }
+}
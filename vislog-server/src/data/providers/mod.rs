@@ -1,14 +1,384 @@
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use self::json_stream::JsonStream;
 
 pub mod courses;
+pub mod json_index;
 pub mod json_providers;
+pub mod json_stream;
 pub mod programs;
+pub mod sqlite_provider;
 
+/// SHA-256 of the raw JSON an item was parsed from
+pub type JsonHash = [u8; 32];
+
+#[derive(Clone)]
 struct ProviderCache<K, T, E>
 where
     K: Hash,
     E: std::error::Error,
 {
+    /// Whether the cache has been filled at least once
+    loaded: bool,
     items: HashMap<K, T>,
     errors: Vec<E>,
+    /// Parse result of every raw JSON seen in the last refresh so unchanged items aren't reparsed
+    parsed: HashMap<JsonHash, Result<K, E>>,
+    /// Hash of the raw JSON each item was parsed from
+    hashes: HashMap<K, JsonHash>,
+    version: CacheVersion,
+}
+
+/// Identifies the contents of a cache, used to answer conditional requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheVersion {
+    /// SHA-256 of the hashes of every raw JSON in the cache
+    pub hash: JsonHash,
+    /// When the contents of the cache last changed
+    pub modified_at: DateTime<Utc>,
+}
+
+impl Default for CacheVersion {
+    fn default() -> Self {
+        Self {
+            hash: [0; 32],
+            modified_at: DateTime::UNIX_EPOCH,
+        }
+    }
+}
+
+impl CacheVersion {
+    /// The version after a single item with the raw JSON hash `item_hash` got replaced
+    fn bumped(&self, item_hash: &JsonHash) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(self.hash);
+        hasher.update(item_hash);
+
+        Self {
+            hash: hasher.finalize().into(),
+            modified_at: Utc::now(),
+        }
+    }
+}
+
+/// Hash of a raw JSON, used to tell whether an item changed between refreshes
+fn json_hash(json: &serde_json::Value) -> Result<JsonHash, serde_json::Error> {
+    Ok(Sha256::digest(serde_json::to_vec(json)?).into())
+}
+
+/// What changed in a cache after it was refreshed
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RefreshReport<K> {
+    pub added: Vec<K>,
+    pub changed: Vec<K>,
+    pub removed: Vec<K>,
+    /// Number of items whose raw JSON changed and had to be parsed again
+    pub reparsed: usize,
+    /// Number of items whose raw JSON didn't change and were reused from the previous cache
+    pub reused: usize,
+    pub errors: usize,
+}
+
+impl<K> RefreshReport<K> {
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Size of a cache as reported by the admin API
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CacheStats {
+    pub loaded: bool,
+    pub items: usize,
+    pub errors: usize,
+}
+
+impl<K, T, E> Default for ProviderCache<K, T, E>
+where
+    K: Hash,
+    E: std::error::Error,
+{
+    fn default() -> Self {
+        Self {
+            loaded: false,
+            items: HashMap::new(),
+            errors: Vec::new(),
+            parsed: HashMap::new(),
+            hashes: HashMap::new(),
+            version: CacheVersion::default(),
+        }
+    }
+}
+
+impl<K, T, E> ProviderCache<K, T, E>
+where
+    K: Hash + Eq + Clone,
+    T: Clone,
+    E: std::error::Error + Clone,
+{
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            loaded: self.loaded,
+            items: self.items.len(),
+            errors: self.errors.len(),
+        }
+    }
+
+    /// An unloaded cache holding parse results kept from an earlier run, so that the first refresh
+    /// only parses the items whose raw JSON isn't among them. `hashes` is left empty for the first
+    /// refresh to still report every item as added.
+    fn seeded<F>(stored: Vec<(JsonHash, Result<T, E>)>, key: F) -> Self
+    where
+        F: Fn(&T) -> K,
+    {
+        let mut cache = Self::default();
+        for (hash, item) in stored {
+            match item {
+                Ok(item) => {
+                    let k = key(&item);
+                    cache.parsed.insert(hash, Ok(k.clone()));
+                    cache.items.insert(k, item);
+                }
+                Err(err) => {
+                    cache.parsed.insert(hash, Err(err));
+                }
+            }
+        }
+
+        cache
+    }
+
+    /// Puts `item`, parsed out of the raw JSON hashing to `hash`, in place of the item cached under
+    /// `k` so that the next refresh sees it as already parsed
+    fn replace(&mut self, k: K, item: T, hash: JsonHash) {
+        let old_hash = self.hashes.insert(k.clone(), hash);
+        if let Some(old_hash) = old_hash {
+            self.parsed.remove(&old_hash);
+        }
+        self.parsed.insert(hash, Ok(k.clone()));
+        self.items.insert(k, item);
+
+        if old_hash != Some(hash) {
+            self.version = self.version.bumped(&hash);
+        }
+    }
+
+    /// Builds the next version of the cache out of the items in `stream`, only parsing the items
+    /// whose raw JSON isn't in this cache.
+    ///
+    /// The current cache is left untouched so that it can keep serving readers until it gets
+    /// swapped out for the new one. Blocks while parsing, so it must run on a blocking thread.
+    fn refreshed<P, F>(
+        &self,
+        mut stream: JsonStream,
+        parse: P,
+        key: F,
+    ) -> Result<(Self, RefreshReport<K>), json_providers::Error>
+    where
+        P: Fn(serde_json::Value) -> Result<T, E>,
+        F: Fn(&T) -> K,
+    {
+        let mut next = Self {
+            loaded: true,
+            ..Self::default()
+        };
+        let (mut reparsed, mut reused) = (0, 0);
+        let mut version_hasher = Sha256::new();
+
+        while let Some(json) = stream.blocking_next() {
+            let json = json?;
+            let hash = json_hash(&json)?;
+            version_hasher.update(hash);
+
+            let item = match self.parsed.get(&hash) {
+                Some(Ok(k)) if self.items.contains_key(k) => {
+                    reused += 1;
+                    Ok(self.items[k].clone())
+                }
+                Some(Err(err)) => {
+                    reused += 1;
+                    Err(err.clone())
+                }
+                _ => {
+                    reparsed += 1;
+                    parse(json)
+                }
+            };
+
+            match item {
+                Ok(item) => {
+                    let k = key(&item);
+                    next.parsed.insert(hash, Ok(k.clone()));
+                    next.hashes.insert(k.clone(), hash);
+                    next.items.insert(k, item);
+                }
+                Err(err) => {
+                    next.parsed.insert(hash, Err(err.clone()));
+                    next.errors.push(err);
+                }
+            }
+        }
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for (k, hash) in &next.hashes {
+            match self.hashes.get(k) {
+                None => added.push(k.clone()),
+                Some(old_hash) if old_hash != hash => changed.push(k.clone()),
+                Some(_) => {}
+            }
+        }
+        let current: HashSet<&K> = next.hashes.keys().collect();
+        let removed = self
+            .hashes
+            .keys()
+            .filter(|k| !current.contains(k))
+            .cloned()
+            .collect();
+
+        let hash = version_hasher.finalize().into();
+        next.version = if self.loaded && hash == self.version.hash {
+            self.version
+        } else {
+            CacheVersion {
+                hash,
+                modified_at: Utc::now(),
+            }
+        };
+
+        let report = RefreshReport {
+            added,
+            changed,
+            removed,
+            reparsed,
+            reused,
+            errors: next.errors.len(),
+        };
+
+        Ok((next, report))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    #[derive(Debug, Clone, thiserror::Error)]
+    #[error("missing id")]
+    struct MissingId;
+
+    type Cache = ProviderCache<u64, String, MissingId>;
+
+    fn stream(items: Value) -> JsonStream {
+        let json = json!({ "programs": { "program": items } });
+        JsonStream::programs(Cursor::new(serde_json::to_vec(&json).unwrap()))
+    }
+
+    async fn refresh(cache: &Cache, items: Value) -> (Cache, RefreshReport<u64>) {
+        let parse = |json: Value| {
+            let id = json["id"].as_u64().ok_or(MissingId)?;
+            Ok(format!(
+                "{id}: {}",
+                json["title"].as_str().unwrap_or_default()
+            ))
+        };
+        let key = |item: &String| item.split(':').next().unwrap().parse().unwrap();
+
+        let (cache, stream) = (cache.clone(), stream(items));
+        tokio::task::spawn_blocking(move || cache.refreshed(stream, parse, key))
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_added_changed_and_removed_items() {
+        let (cache, first) = refresh(
+            &Cache::default(),
+            json!([{ "id": 1, "title": "A" }, { "id": 2, "title": "B" }, { "title": "?" }]),
+        )
+        .await;
+        let (cache, second) = refresh(
+            &cache,
+            json!([{ "id": 2, "title": "B2" }, { "id": 3, "title": "C" }, { "title": "?" }]),
+        )
+        .await;
+
+        assert!(cache.loaded);
+        assert_eq!(first.added.len(), 2);
+        assert_eq!((first.reparsed, first.errors), (3, 1));
+        assert_eq!(second.added, vec![3]);
+        assert_eq!(second.changed, vec![2]);
+        assert_eq!(second.removed, vec![1]);
+        assert_eq!(cache.items[&2], "2: B2");
+        assert_ne!(cache.version.hash, CacheVersion::default().hash);
+        assert_eq!(cache.errors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reuses_items_with_unchanged_json() {
+        let items = json!([{ "id": 1, "title": "A" }, { "id": 2, "title": "B" }, {}]);
+        let (cache, _) = refresh(&Cache::default(), items.clone()).await;
+        let first_version = cache.version;
+        let (cache, report) = refresh(&cache, items).await;
+
+        assert!(report.is_unchanged());
+        assert_eq!(cache.version, first_version);
+        assert_eq!((report.reparsed, report.reused), (0, 3));
+        assert_eq!(cache.items.len(), 2);
+        assert_eq!(cache.errors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn seeded_cache_skips_parsing_stored_items() {
+        let (a, b, missing) = (
+            json!({ "id": 1, "title": "A" }),
+            json!({ "id": 2, "title": "B" }),
+            json!({}),
+        );
+        // Nothing was kept for `b`
+        let stored = vec![
+            (json_hash(&a).unwrap(), Ok("1: A".to_owned())),
+            (json_hash(&missing).unwrap(), Err(MissingId)),
+        ];
+        let seeded = Cache::seeded(stored, |item| {
+            item.split(':').next().unwrap().parse().unwrap()
+        });
+        let (cache, report) = refresh(&seeded, json!([a, b, missing])).await;
+
+        assert!(!seeded.loaded);
+        assert_eq!(report.added.len(), 2);
+        assert_eq!((report.reparsed, report.reused), (1, 2));
+        assert_eq!(cache.items[&1], "1: A");
+        assert_eq!(cache.items[&2], "2: B");
+        assert_eq!(cache.errors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_replaced_items() {
+        let (mut cache, _) = refresh(&Cache::default(), json!([{ "id": 1, "title": "A" }])).await;
+        let first_version = cache.version;
+
+        let replacement = json!({ "id": 1, "title": "A2" });
+        cache.replace(1, "1: A2".to_owned(), json_hash(&replacement).unwrap());
+        let replaced_version = cache.version;
+        let (cache, report) = refresh(&cache, json!([replacement])).await;
+
+        assert_ne!(replaced_version, first_version);
+        assert!(report.is_unchanged());
+        assert_eq!((report.reparsed, report.reused), (0, 1));
+        assert_eq!(cache.items[&1], "1: A2");
+    }
 }
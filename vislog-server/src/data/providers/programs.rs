@@ -1,14 +1,20 @@
-use std::{collections::HashMap, fmt::Display, sync::Arc};
+use std::sync::Arc;
 
+use arc_swap::ArcSwap;
+
+use serde_json::Value;
 use thiserror::Error;
-use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use tracing::{field::debug, instrument, Level};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, instrument, Level};
 use vislog_core::{parsing::guid::Guid, Program};
-use vislog_parser::{parse_programs, ParsingError};
+use vislog_parser::{parse_program, ParsingError};
+
+use crate::data::snapshots::CatalogKind;
 
 use super::{
+    json_hash,
     json_providers::{self, JsonProvider},
-    ProviderCache,
+    CacheStats, CacheVersion, ProviderCache, RefreshReport,
 };
 
 /// Provides program struct parsing
@@ -44,49 +50,30 @@ use super::{
 /// # use self::json_providers::JsonProviderError;
 /// # let json_provider = FileJsonProvider::init("../data".into(), "programs.json".into());
 /// # let program_provider = ProgramsProvider::with(Box::new(json_provider.clone()));
-/// let cs_major_json = json_provider.get_program_json("cs_major.json")?;
+/// let cs_major_json = json_provider.get_program_json("{5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5}")?;
 /// let cs_major: Program = serde_json::from_str(&(serde_json::to_string(&cs_major_json)?))?;
 /// dbg!(cs_major.title);
 /// ```
 #[derive(Clone)]
 pub struct ProgramsProvider {
     json_provider: Arc<RwLock<Box<dyn JsonProvider>>>,
-    cache: Arc<RwLock<ProviderCache<Guid, Program, ParsingError>>>,
+    cache: Arc<ArcSwap<ProviderCache<Guid, Program, ParsingError>>>,
+    /// Makes sure that only one refresh builds a new cache at a time
+    refresh_lock: Arc<Mutex<()>>,
 }
 
 impl ProgramsProvider {
     pub fn with(json_provider: Box<dyn JsonProvider>) -> Self {
-        let json_provider = Arc::new(RwLock::new(json_provider));
-        let cache = ProviderCache {
-            items: HashMap::new(),
-            errors: Vec::new(),
-        };
-        let cache = Arc::new(RwLock::new(cache));
         Self {
-            json_provider,
-            cache,
+            json_provider: Arc::new(RwLock::new(json_provider)),
+            cache: Arc::new(ArcSwap::from_pointee(ProviderCache::default())),
+            refresh_lock: Arc::new(Mutex::new(())),
         }
     }
 
     #[instrument(skip(self))]
     pub async fn get_all_programs(&self) -> Result<(Vec<Program>, Vec<ParsingError>)> {
-        let cache = {
-            let read_cache_guard = self.cache.read().await;
-
-            if read_cache_guard.items.is_empty() && read_cache_guard.errors.is_empty() {
-                debug("cache empty");
-                drop(read_cache_guard);
-                let json_provider_read_guard = self.json_provider.read().await;
-                let write_cache_guard = self.cache.write().await;
-                Self::_refresh_cache(json_provider_read_guard, write_cache_guard).await?;
-
-                // Reacquire read lock
-                self.cache.read().await
-            } else {
-                debug("cache populated");
-                read_cache_guard
-            }
-        };
+        let cache = self.loaded_cache().await?;
 
         let mut programs: Vec<Program> = cache.items.values().cloned().collect();
         programs.sort();
@@ -97,68 +84,129 @@ impl ProgramsProvider {
 
     #[instrument(level = Level::DEBUG, skip(self))]
     pub async fn get_program(&self, guid: &Guid) -> Result<Option<Program>> {
-        let cache = {
-            let read_cache_guard = self.cache.read().await;
-
-            if read_cache_guard.items.is_empty() && read_cache_guard.errors.is_empty() {
-                debug("cache empty");
-
-                drop(read_cache_guard);
-                let json_provider_read_guard = self.json_provider.read().await;
-                let write_cache_guard = self.cache.write().await;
-                Self::_refresh_cache(json_provider_read_guard, write_cache_guard).await?;
-
-                // Reacquire read lock
-                self.cache.read().await
-            } else {
-                debug("cache populated");
-                read_cache_guard
-            }
-        };
+        let cache = self.loaded_cache().await?;
 
-        Ok(cache.items.get(guid).map(|p| p.clone()))
+        Ok(cache.items.get(guid).cloned())
     }
 
-    pub async fn refresh_cache(&self) -> Result<()> {
+    /// Gets the unparsed JSON of a single program by its catalog URL, catalog path or GUID
+    pub async fn get_program_json(&self, id: &str) -> Result<Value> {
         let json_provider_read_guard = self.json_provider.read().await;
-        let cache_write_guard = self.cache.write().await;
 
-        Self::_refresh_cache(json_provider_read_guard, cache_write_guard).await
+        Ok(json_provider_read_guard.get_program_json(id).await?)
+    }
+
+    /// Reparses a single program and replaces it in the cache without reparsing all the others
+    #[instrument(level = Level::DEBUG, skip(self))]
+    pub async fn refresh_program(&self, id: &str) -> Result<Program> {
+        let program_json = self.get_program_json(id).await?;
+        let hash = json_hash(&program_json).map_err(json_providers::Error::from)?;
+        let program = parse_program(program_json).map_err(|err| Error::Parsing(vec![err]))?;
+
+        let _refresh_guard = self.refresh_lock.lock().await;
+        let mut cache = ProviderCache::clone(&self.cache.load());
+        cache.replace(program.guid, program.clone(), hash);
+        self.cache.store(Arc::new(cache));
+
+        Ok(program)
     }
 
-    /// SAFETY: There must not be a another read guard for `RwLockReadGuard<'a, ProviderCache>` in
-    /// the same execution "thread" to avoid deadlocks
-    async fn _refresh_cache<'a>(
-        json_provider_read_guard: RwLockReadGuard<'a, Box<dyn JsonProvider>>,
-        mut cache_write_guard: RwLockWriteGuard<'a, ProviderCache<Guid, Program, ParsingError>>,
-    ) -> Result<()> {
-        let program_jsons = json_provider_read_guard.get_all_program_jsons()?;
-        let (programs, errors) = parse_programs(program_jsons);
+    /// Version of the cached programs, filling the cache if it hasn't been loaded yet
+    pub async fn cache_version(&self) -> Result<CacheVersion> {
+        Ok(self.loaded_cache().await?.version)
+    }
 
-        let programs = programs
-            .into_iter()
-            .map(|p| (p.guid.clone(), p))
-            .collect::<Vec<(Guid, Program)>>();
+    /// Size of the cache without filling it if it hasn't been loaded yet
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.load().stats()
+    }
 
-        cache_write_guard.items.clear();
-        cache_write_guard.errors.clear();
+    /// Reloads the data from the `JsonProvider` and reparses the programs that changed
+    pub async fn refresh_cache(&self) -> Result<RefreshReport<Guid>> {
+        let _refresh_guard = self.refresh_lock.lock().await;
 
-        cache_write_guard.items.extend(programs);
-        cache_write_guard.errors.extend(errors);
+        self.json_provider
+            .read()
+            .await
+            .reload(CatalogKind::Programs)
+            .await?;
 
-        Ok(())
+        self._refresh_cache().await
+    }
+
+    /// Fills the cache the first time it's needed
+    async fn loaded_cache(&self) -> Result<Arc<ProviderCache<Guid, Program, ParsingError>>> {
+        let cache = self.cache.load_full();
+        if cache.loaded {
+            debug!("cache populated");
+            return Ok(cache);
+        }
+
+        debug!("cache empty");
+        // Someone else is already filling the cache, so let the caller retry instead of piling up
+        // behind the lock
+        let Ok(_refresh_guard) = self.refresh_lock.try_lock() else {
+            return Err(Error::CacheLoading);
+        };
+
+        // Another task might have filled the cache right before the lock was taken
+        if !self.cache.load().loaded {
+            self._refresh_cache().await?;
+        }
+
+        Ok(self.cache.load_full())
+    }
+
+    /// Builds a new cache while readers keep using the current one, then swaps it in.
+    ///
+    /// NOTE: `refresh_lock` must be held by the caller
+    async fn _refresh_cache(&self) -> Result<RefreshReport<Guid>> {
+        let json_provider = self.json_provider.read().await;
+        let mut current = self.cache.load_full();
+        if !current.loaded {
+            // Start from the programs the provider kept parsed from an earlier run
+            let stored = json_provider.stored_programs().await?;
+            current = Arc::new(ProviderCache::seeded(stored, |p| p.guid));
+        }
+        let program_jsons = json_provider.stream_program_jsons().await?;
+
+        let (next, report) = tokio::task::spawn_blocking(move || {
+            current.refreshed(program_jsons, parse_program, |p| p.guid)
+        })
+        .await
+        .map_err(json_providers::Error::from)??;
+        self.cache.store(Arc::new(next));
+
+        if report.is_unchanged() {
+            debug!("Programs unchanged after refresh");
+            return Ok(report);
+        }
+
+        info!(
+            "Refreshed programs: {} added, {} changed, {} removed, {} reparsed, {} errors",
+            report.added.len(),
+            report.changed.len(),
+            report.removed.len(),
+            report.reparsed,
+            report.errors
+        );
+
+        Ok(report)
     }
 }
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("Failed to get the program JSON: {0}")]
     JsonProvider(#[from] json_providers::Error),
-}
 
-pub type Result<T> = std::result::Result<T, Error>;
+    /// The JSON was found but could not be parsed
+    #[error("Failed to parse the program JSON ({} errors)", .0.len())]
+    Parsing(Vec<ParsingError>),
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
-    }
+    /// The cache is being filled for the first time and can't serve requests yet
+    #[error("The programs are still being loaded")]
+    CacheLoading,
 }
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,703 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info};
+use vislog_core::{stored::Stored, CourseDetails, Program};
+use vislog_parser::{parse_course, parse_program, ParsingError};
+
+use crate::{configs, data::snapshots::CatalogKind};
+
+use super::{
+    json_hash,
+    json_index::lookup_key,
+    json_providers::{Error, JsonProvider},
+    json_stream::{JsonStream, COURSES_LAYOUT, PROGRAMS_LAYOUT},
+    JsonHash,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS programs (
+    catalog_year TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    guid TEXT,
+    url_key TEXT,
+    path_key TEXT,
+    title TEXT,
+    raw_json TEXT NOT NULL,
+    PRIMARY KEY (catalog_year, position)
+);
+CREATE INDEX IF NOT EXISTS programs_guid ON programs (catalog_year, guid);
+CREATE INDEX IF NOT EXISTS programs_url_key ON programs (catalog_year, url_key);
+CREATE INDEX IF NOT EXISTS programs_path_key ON programs (catalog_year, path_key);
+
+CREATE TABLE IF NOT EXISTS courses (
+    catalog_year TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    guid TEXT,
+    url_key TEXT,
+    path_key TEXT,
+    subject_code TEXT,
+    number TEXT,
+    name TEXT,
+    raw_json TEXT NOT NULL,
+    PRIMARY KEY (catalog_year, position)
+);
+CREATE INDEX IF NOT EXISTS courses_guid ON courses (catalog_year, guid);
+CREATE INDEX IF NOT EXISTS courses_url_key ON courses (catalog_year, url_key);
+CREATE INDEX IF NOT EXISTS courses_path_key ON courses (catalog_year, path_key);
+CREATE INDEX IF NOT EXISTS courses_subject_number ON courses (catalog_year, subject_code, number);
+
+-- Replaced by `parse_results`
+DROP TABLE IF EXISTS parse_errors;
+
+-- The parsed item or the error it failed to parse with, only loaded while `checksum` matches the
+-- last import of its kind
+CREATE TABLE IF NOT EXISTS parse_results (
+    catalog_year TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    checksum TEXT NOT NULL,
+    json_hash BLOB NOT NULL,
+    parsed_json TEXT,
+    error_stage TEXT,
+    error_title TEXT,
+    error_message TEXT,
+    PRIMARY KEY (catalog_year, kind, position)
+);
+
+CREATE TABLE IF NOT EXISTS imports (
+    catalog_year TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    imported_at TEXT NOT NULL,
+    PRIMARY KEY (catalog_year, kind)
+);
+";
+
+/// Serves catalog JSON out of a SQLite database holding the raw JSON of every item of each catalog
+/// year, along with the GUID, URL, path and the title or subject code, number and name of the
+/// item in their own columns, and the parsed `Program`s/`CourseDetails` and parse errors.
+///
+/// The data files in `data.storage` are imported whenever their contents change so the database
+/// can be queried with SQL for reports, and neither it nor the parsed items need to be rebuilt
+/// after restarts.
+#[derive(Debug, Clone)]
+pub struct SqliteProvider {
+    db_path: PathBuf,
+    catalog_year: String,
+    data_root: PathBuf,
+    all_programs_file: PathBuf,
+    all_courses_file: PathBuf,
+}
+
+/// Outcome of importing a data file into the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub kind: CatalogKind,
+    pub items: usize,
+    pub errors: usize,
+    /// Whether the import was skipped because the data file didn't change since the last import
+    pub unchanged: bool,
+}
+
+/// An item ready to be inserted into the database
+struct ItemRow {
+    guid: Option<String>,
+    url_key: Option<String>,
+    path_key: Option<String>,
+    /// `title` of programs or `subject_code`, `number` and `name` of courses
+    columns: Vec<Option<String>>,
+    raw_json: String,
+    json_hash: JsonHash,
+    /// The item serialized after being parsed
+    parsed_json: Option<String>,
+    error: Option<ParsingError>,
+}
+
+impl SqliteProvider {
+    /// Opens (or creates) the database and makes sure the tables exist
+    pub fn init(config: &configs::SqliteProvider, data: &configs::Data) -> Result<Self, Error> {
+        if let Some(parent) = config.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let provider = Self {
+            db_path: config.path.clone(),
+            catalog_year: config.catalog_year.clone(),
+            data_root: data.storage.clone(),
+            all_programs_file: data.all_programs_file.clone(),
+            all_courses_file: data.all_courses_file.clone(),
+        };
+        provider.connect()?.execute_batch(SCHEMA)?;
+
+        Ok(provider)
+    }
+
+    fn connect(&self) -> Result<Connection, Error> {
+        let conn = Connection::open(&self.db_path)?;
+        // Let readers keep going while an import is being written
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        Ok(conn)
+    }
+
+    fn data_file(&self, kind: CatalogKind) -> PathBuf {
+        match kind {
+            CatalogKind::Programs => self.data_root.join(&self.all_programs_file),
+            CatalogKind::Courses => self.data_root.join(&self.all_courses_file),
+        }
+    }
+
+    /// Replaces the stored items of `kind` for the configured catalog year with the contents of
+    /// the matching data file. Nothing is done if the file hasn't changed since the last import.
+    pub async fn import(&self, kind: CatalogKind) -> Result<ImportSummary, Error> {
+        let path = self.data_file(kind);
+        let contents = tokio::fs::read(&path).await?;
+        let checksum = format!("{:x}", Sha256::digest(&contents));
+
+        let provider = self.clone();
+        let imported_checksum =
+            tokio::task::spawn_blocking(move || provider.imported_checksum(kind)).await??;
+
+        if imported_checksum.as_deref() == Some(checksum.as_str()) {
+            debug!("{path:?} is unchanged since the last import");
+            return Ok(ImportSummary {
+                kind,
+                items: 0,
+                errors: 0,
+                unchanged: true,
+            });
+        }
+
+        let layout = match kind {
+            CatalogKind::Programs => PROGRAMS_LAYOUT,
+            CatalogKind::Courses => COURSES_LAYOUT,
+        };
+        let mut stream = JsonStream::from_reader(std::io::Cursor::new(contents), layout);
+        let mut items = Vec::new();
+        while let Some(item) = stream.next().await {
+            items.push(item?);
+        }
+
+        let provider = self.clone();
+        let summary = tokio::task::spawn_blocking(move || {
+            let rows = items
+                .into_iter()
+                .map(|item| item_row(kind, item))
+                .collect::<Result<Vec<_>, _>>()?;
+            let summary = ImportSummary {
+                kind,
+                items: rows.len(),
+                errors: rows.iter().filter(|row| row.error.is_some()).count(),
+                unchanged: false,
+            };
+            provider.replace_items(kind, &checksum, rows)?;
+
+            Ok::<_, Error>(summary)
+        })
+        .await??;
+
+        info!(
+            "Imported {} {} ({} errors) for catalog year {} into {:?}",
+            summary.items,
+            kind.as_str(),
+            summary.errors,
+            self.catalog_year,
+            self.db_path
+        );
+
+        Ok(summary)
+    }
+
+    /// Checksum of the last import of `kind`, ignoring imports made before parse results were
+    /// stored along with the items so that they get imported again
+    fn imported_checksum(&self, kind: CatalogKind) -> Result<Option<String>, Error> {
+        let checksum = self
+            .connect()?
+            .query_row(
+                "SELECT checksum FROM imports i WHERE catalog_year = ?1 AND kind = ?2
+                    AND EXISTS (SELECT 1 FROM parse_results r WHERE r.catalog_year = i.catalog_year
+                        AND r.kind = i.kind AND r.checksum = i.checksum)",
+                params![self.catalog_year, kind.as_str()],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(checksum)
+    }
+
+    fn replace_items(
+        &self,
+        kind: CatalogKind,
+        checksum: &str,
+        rows: Vec<ItemRow>,
+    ) -> Result<(), Error> {
+        let mut conn = self.connect()?;
+        let tx = conn.transaction()?;
+        let year = &self.catalog_year;
+
+        tx.execute(
+            &format!("DELETE FROM {} WHERE catalog_year = ?1", kind.as_str()),
+            params![year],
+        )?;
+        tx.execute(
+            "DELETE FROM parse_results WHERE catalog_year = ?1 AND kind = ?2",
+            params![year, kind.as_str()],
+        )?;
+
+        {
+            let mut insert_item = match kind {
+                CatalogKind::Programs => tx.prepare(
+                    "INSERT INTO programs (catalog_year, position, guid, url_key, path_key, title,
+                        raw_json)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?,
+                CatalogKind::Courses => tx.prepare(
+                    "INSERT INTO courses (catalog_year, position, guid, url_key, path_key,
+                        subject_code, number, name, raw_json)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )?,
+            };
+            let mut insert_result = tx.prepare(
+                "INSERT INTO parse_results (catalog_year, kind, position, checksum, json_hash,
+                    parsed_json, error_stage, error_title, error_message)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+
+            for (position, row) in rows.into_iter().enumerate() {
+                let mut values: Vec<&dyn rusqlite::ToSql> =
+                    vec![year, &position, &row.guid, &row.url_key, &row.path_key];
+                values.extend(row.columns.iter().map(|c| c as &dyn rusqlite::ToSql));
+                values.push(&row.raw_json);
+                insert_item.execute(values.as_slice())?;
+
+                let (stage, title, message) = match &row.error {
+                    Some(ParsingError::Serialization { title, err_msg }) => {
+                        (Some("serialization"), title.as_ref(), Some(err_msg))
+                    }
+                    Some(ParsingError::Deserialization { title, err_msg }) => {
+                        (Some("deserialization"), title.as_ref(), Some(err_msg))
+                    }
+                    None => (None, None, None),
+                };
+                insert_result.execute(params![
+                    year,
+                    kind.as_str(),
+                    position,
+                    checksum,
+                    &row.json_hash[..],
+                    row.parsed_json,
+                    stage,
+                    title,
+                    message
+                ])?;
+            }
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO imports (catalog_year, kind, checksum, imported_at)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![
+                year,
+                kind.as_str(),
+                checksum,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Parse results stored by the last import of `kind`, leaving out the ones stored by an older
+    /// import or that can't be read back so that their items get parsed again
+    async fn parse_results<T>(
+        &self,
+        kind: CatalogKind,
+    ) -> Result<Vec<(JsonHash, Result<T, ParsingError>)>, Error>
+    where
+        T: Send + 'static,
+        Stored<T>: DeserializeOwned,
+    {
+        let provider = self.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = provider.connect()?;
+            let mut stmt = conn.prepare(
+                "SELECT r.json_hash, r.parsed_json, r.error_stage, r.error_title, r.error_message
+                FROM parse_results r
+                JOIN imports i ON i.catalog_year = r.catalog_year AND i.kind = r.kind
+                    AND i.checksum = r.checksum
+                WHERE r.catalog_year = ?1 AND r.kind = ?2
+                ORDER BY r.position",
+            )?;
+            let mut rows = stmt.query(params![provider.catalog_year, kind.as_str()])?;
+
+            let mut results = Vec::new();
+            while let Some(row) = rows.next()? {
+                let Ok(hash) = JsonHash::try_from(row.get::<_, Vec<u8>>(0)?) else {
+                    continue;
+                };
+                let parsed_json: Option<String> = row.get(1)?;
+                let (stage, title, message): (Option<String>, _, Option<String>) =
+                    (row.get(2)?, row.get(3)?, row.get(4)?);
+
+                let result = match (parsed_json, stage.as_deref(), message) {
+                    (Some(parsed_json), _, _) => {
+                        match serde_json::from_str::<Stored<T>>(&parsed_json) {
+                            Ok(Stored(item)) => Ok(item),
+                            Err(err) => {
+                                debug!(
+                                    "Ignoring stored {} that can't be read: {err}",
+                                    kind.as_str()
+                                );
+                                continue;
+                            }
+                        }
+                    }
+                    (None, Some("serialization"), Some(err_msg)) => {
+                        Err(ParsingError::Serialization { title, err_msg })
+                    }
+                    (None, Some("deserialization"), Some(err_msg)) => {
+                        Err(ParsingError::Deserialization { title, err_msg })
+                    }
+                    _ => continue,
+                };
+                results.push((hash, result));
+            }
+
+            debug!(
+                "Loaded {} stored {} parse results",
+                results.len(),
+                kind.as_str()
+            );
+
+            Ok(results)
+        })
+        .await?
+    }
+
+    /// Streams the raw JSON of every stored item of `kind` in catalog order
+    fn stream_items(&self, kind: CatalogKind) -> JsonStream {
+        let provider = self.clone();
+
+        JsonStream::from_blocking(move |send| {
+            let conn = provider.connect()?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT raw_json FROM {} WHERE catalog_year = ?1 ORDER BY position",
+                kind.as_str()
+            ))?;
+            let mut rows = stmt.query(params![provider.catalog_year])?;
+
+            while let Some(row) = rows.next()? {
+                let raw_json: String = row.get(0)?;
+                if !send(serde_json::from_str(&raw_json)?) {
+                    break;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    async fn get_item_json(&self, kind: CatalogKind, id: &str) -> Result<Value, Error> {
+        let provider = self.clone();
+        let key = lookup_key(id);
+
+        let raw_json: Option<String> = tokio::task::spawn_blocking(move || {
+            provider
+                .connect()?
+                .query_row(
+                    &format!(
+                        "SELECT raw_json FROM {} WHERE catalog_year = ?1
+                            AND (guid = ?2 OR url_key = ?2 OR path_key = ?2)
+                        ORDER BY position LIMIT 1",
+                        kind.as_str()
+                    ),
+                    params![provider.catalog_year, key],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(Error::from)
+        })
+        .await??;
+
+        match raw_json {
+            Some(raw_json) => Ok(serde_json::from_str(&raw_json)?),
+            None => Err(Error::NotFound(id.to_owned())),
+        }
+    }
+}
+
+/// Pulls the columns that items can be looked up and queried by out of `item`, and parses it on
+/// its own so that its parse result can be stored next to it
+fn item_row(kind: CatalogKind, item: Value) -> Result<ItemRow, Error> {
+    fn parse_result<T: Serialize>(
+        parsed: Result<T, ParsingError>,
+    ) -> Result<(Option<String>, Option<ParsingError>), Error> {
+        Ok(match parsed {
+            Ok(parsed) => (Some(serde_json::to_string(&parsed)?), None),
+            Err(err) => (None, Some(err)),
+        })
+    }
+
+    let string_field = |field: &str| item.get(field).and_then(Value::as_str).map(str::to_owned);
+    let key_field = |field: &str| item.get(field).and_then(Value::as_str).map(lookup_key);
+
+    let (columns, (parsed_json, error)) = match kind {
+        CatalogKind::Programs => (
+            vec![string_field("title")],
+            parse_result(parse_program(item.clone()))?,
+        ),
+        CatalogKind::Courses => (
+            vec![
+                string_field("subject_code"),
+                string_field("number"),
+                string_field("name"),
+            ],
+            parse_result(parse_course(item.clone()))?,
+        ),
+    };
+
+    Ok(ItemRow {
+        guid: key_field("GUID"),
+        url_key: key_field("url"),
+        path_key: key_field("path"),
+        columns,
+        raw_json: serde_json::to_string(&item)?,
+        json_hash: json_hash(&item)?,
+        parsed_json,
+        error,
+    })
+}
+
+#[async_trait]
+impl JsonProvider for SqliteProvider {
+    async fn stream_program_jsons(&self) -> Result<JsonStream, Error> {
+        Ok(self.stream_items(CatalogKind::Programs))
+    }
+
+    async fn get_program_json(&self, id: &str) -> Result<Value, Error> {
+        self.get_item_json(CatalogKind::Programs, id).await
+    }
+
+    async fn stream_course_jsons(&self) -> Result<JsonStream, Error> {
+        Ok(self.stream_items(CatalogKind::Courses))
+    }
+
+    async fn get_course_json(&self, id: &str) -> Result<Value, Error> {
+        self.get_item_json(CatalogKind::Courses, id).await
+    }
+
+    async fn reload(&self, kind: CatalogKind) -> Result<(), Error> {
+        self.import(kind).await?;
+        Ok(())
+    }
+
+    async fn stored_programs(
+        &self,
+    ) -> Result<Vec<(JsonHash, Result<Program, ParsingError>)>, Error> {
+        self.parse_results(CatalogKind::Programs).await
+    }
+
+    async fn stored_courses(
+        &self,
+    ) -> Result<Vec<(JsonHash, Result<CourseDetails, ParsingError>)>, Error> {
+        self.parse_results(CatalogKind::Courses).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::data::providers::programs::ProgramsProvider;
+
+    async fn all_programs(provider: &dyn JsonProvider) -> Result<Vec<Value>, Error> {
+        provider.stream_program_jsons().await?.collect().await
+    }
+
+    async fn all_courses(provider: &dyn JsonProvider) -> Result<Vec<Value>, Error> {
+        provider.stream_course_jsons().await?.collect().await
+    }
+
+    struct Fixture {
+        // Removes the directory once dropped
+        _dir: tempfile::TempDir,
+        provider: SqliteProvider,
+        programs_file: PathBuf,
+    }
+
+    fn fixture() -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let programs_file = dir.path().join("programs.json");
+        std::fs::copy("../data/programs.json", &programs_file).unwrap();
+        std::fs::copy("../data/courses.json", dir.path().join("courses.json")).unwrap();
+
+        let provider = SqliteProvider::init(
+            &configs::SqliteProvider {
+                path: dir.path().join("db").join("catalog.sqlite3"),
+                catalog_year: "2023".to_owned(),
+            },
+            &configs::Data {
+                storage: dir.path().to_owned(),
+                all_programs_file: "programs.json".into(),
+                all_courses_file: "courses.json".into(),
+                max_snapshots: None,
+                provider: Some(configs::JsonProviderKind::Sqlite),
+                web: None,
+                sqlite: None,
+            },
+        )
+        .unwrap();
+
+        Fixture {
+            _dir: dir,
+            provider,
+            programs_file,
+        }
+    }
+
+    #[tokio::test]
+    async fn imports_and_streams_items_in_order() {
+        let fixture = fixture();
+        let provider = &fixture.provider;
+        let summary = provider.import(CatalogKind::Courses).await.unwrap();
+
+        let file = std::fs::File::open("../data/courses.json").unwrap();
+        let expected = JsonStream::courses(file).collect().await.unwrap();
+        let stored = all_courses(provider).await.unwrap();
+
+        assert_eq!(summary.items, expected.len());
+        assert!(!summary.unchanged);
+        assert_eq!(stored, expected);
+    }
+
+    #[tokio::test]
+    async fn skips_import_of_unchanged_file() {
+        let fixture = fixture();
+        let (provider, programs_file) = (&fixture.provider, &fixture.programs_file);
+
+        assert!(
+            !provider
+                .import(CatalogKind::Programs)
+                .await
+                .unwrap()
+                .unchanged
+        );
+        assert!(
+            provider
+                .import(CatalogKind::Programs)
+                .await
+                .unwrap()
+                .unchanged
+        );
+
+        let json = json!({ "programs": { "program": [{ "title": "Only program" }] } });
+        std::fs::write(programs_file, serde_json::to_vec(&json).unwrap()).unwrap();
+        let summary = provider.import(CatalogKind::Programs).await.unwrap();
+
+        assert_eq!(summary.items, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(all_programs(provider).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn looks_up_items_by_key() {
+        let fixture = fixture();
+        let provider = &fixture.provider;
+        provider.import(CatalogKind::Courses).await.unwrap();
+
+        let course = provider
+            .get_course_json("d3561bf9-40fa-4890-b4c4-f7eb0e9981cc")
+            .await
+            .unwrap();
+        let missing = provider.get_course_json("ACC-999").await;
+
+        assert_eq!(course["number"], "180");
+        assert!(matches!(missing, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn stores_items_for_queries() {
+        let fixture = fixture();
+        let provider = &fixture.provider;
+        provider.import(CatalogKind::Programs).await.unwrap();
+        provider.import(CatalogKind::Courses).await.unwrap();
+
+        let conn = provider.connect().unwrap();
+        let csc_courses: usize = conn
+            .query_row(
+                "SELECT COUNT(*) FROM courses WHERE subject_code = 'CSC'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let cs_major_title: String = conn
+            .query_row(
+                "SELECT json_extract(raw_json, '$.title') FROM programs
+                WHERE guid = '5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert_eq!(csc_courses, 48);
+        assert_eq!(cs_major_title, "Major in Computer Science—42 hours");
+    }
+
+    #[tokio::test]
+    async fn loads_parse_results_of_the_last_import() {
+        let fixture = fixture();
+        let provider = &fixture.provider;
+        provider.import(CatalogKind::Programs).await.unwrap();
+
+        let stored = provider.stored_programs().await.unwrap();
+        let hashes: Vec<JsonHash> = all_programs(provider)
+            .await
+            .unwrap()
+            .iter()
+            .map(|json| json_hash(json).unwrap())
+            .collect();
+
+        assert_eq!(
+            stored.iter().map(|(hash, _)| *hash).collect::<Vec<_>>(),
+            hashes
+        );
+        assert!(stored.iter().any(|(_, program)| matches!(
+            program,
+            Ok(program) if program.title == "Major in Computer Science—42 hours"
+        )));
+
+        // Parse results of an older import are left out
+        provider
+            .connect()
+            .unwrap()
+            .execute("UPDATE imports SET checksum = 'older'", [])
+            .unwrap();
+
+        assert!(provider.stored_programs().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn first_refresh_reuses_stored_parse_results() {
+        let fixture = fixture();
+        fixture
+            .provider
+            .import(CatalogKind::Programs)
+            .await
+            .unwrap();
+
+        let programs = ProgramsProvider::with(Box::new(fixture.provider.clone()));
+        let report = programs.refresh_cache().await.unwrap();
+
+        assert_eq!(report.reparsed, 0);
+        assert_eq!(report.added.len(), report.reused - report.errors);
+    }
+}
@@ -0,0 +1,313 @@
+use std::{fmt::Display, future::Future, str::FromStr, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::{sync::RwLock, task::JoinHandle};
+use tracing::{error, info, instrument, warn};
+use utoipa::ToSchema;
+
+use crate::configs;
+
+use self::error::{Error, Result};
+
+pub mod error {
+    use thiserror::Error;
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        /// Neither `interval_secs` nor `cron` were given
+        #[error("Either `interval_secs` or `cron` must be given")]
+        MissingSchedule,
+
+        /// Only one of `interval_secs` and `cron` can be given
+        #[error("Only one of `interval_secs` and `cron` can be given")]
+        ConflictingSchedules,
+
+        /// `interval_secs` must be greater than 0
+        #[error("`interval_secs` must be greater than 0")]
+        ZeroInterval,
+
+        #[error("Invalid cron expression: {0}")]
+        InvalidCron(String),
+    }
+}
+
+/// An error a scheduled job can fail with
+pub trait JobError: Display {
+    /// What the status endpoint shows, which anyone can read. Unlike the [Display] message, which
+    /// only gets logged, it shouldn't hold urls, paths or upstream responses
+    fn public_message(&self) -> String;
+}
+
+/// When the catalog gets refreshed
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    Interval(Duration),
+    Cron(Box<cron::Schedule>),
+}
+
+impl Schedule {
+    pub fn from_config(config: &configs::Scheduler) -> Result<Self> {
+        match (config.interval_secs, &config.cron) {
+            (Some(_), Some(_)) => Err(Error::ConflictingSchedules),
+            (None, None) => Err(Error::MissingSchedule),
+            (Some(0), None) => Err(Error::ZeroInterval),
+            (Some(secs), None) => Ok(Self::Interval(Duration::from_secs(secs))),
+            (None, Some(expression)) => Self::cron(expression),
+        }
+    }
+
+    /// Accepts the standard 5 field expressions (Ex: "0 3 * * *") along with the 6 and 7 field
+    /// ones that start with seconds and optionally end with years
+    pub fn cron(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        let expression = if expression.split_whitespace().count() == 5 {
+            format!("0 {expression}")
+        } else {
+            expression.to_owned()
+        };
+
+        cron::Schedule::from_str(&expression)
+            .map(|schedule| Self::Cron(Box::new(schedule)))
+            .map_err(|err| Error::InvalidCron(err.to_string()))
+    }
+
+    /// When the next refresh should happen after `now`. `None` if the schedule never fires again
+    pub fn next_run(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Interval(interval) => Some(now + *interval),
+            Schedule::Cron(schedule) => schedule.after(&now).next(),
+        }
+    }
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schedule::Interval(interval) => write!(f, "every {}s", interval.as_secs()),
+            Schedule::Cron(schedule) => write!(f, "cron \"{schedule}\""),
+        }
+    }
+}
+
+/// What the scheduler has been up to, exposed by the status endpoint
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct SchedulerStatus {
+    pub enabled: bool,
+    pub schedule: Option<String>,
+    pub running: bool,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_failure: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+}
+
+/// Runs a job, usually the refresh of the whole catalog, on a [Schedule] in the background
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    schedule: Option<Schedule>,
+    status: Arc<RwLock<SchedulerStatus>>,
+}
+
+impl Scheduler {
+    pub fn new(schedule: Schedule) -> Self {
+        let status = SchedulerStatus {
+            enabled: true,
+            schedule: Some(schedule.to_string()),
+            ..Default::default()
+        };
+
+        Self {
+            schedule: Some(schedule),
+            status: Arc::new(RwLock::new(status)),
+        }
+    }
+
+    /// A scheduler that never runs, only there to report that it's disabled
+    pub fn disabled() -> Self {
+        Self {
+            schedule: None,
+            status: Arc::new(RwLock::new(SchedulerStatus::default())),
+        }
+    }
+
+    pub async fn status(&self) -> SchedulerStatus {
+        self.status.read().await.clone()
+    }
+
+    /// Runs `job` every time the schedule fires until the returned task is aborted.
+    /// Returns `None` if the scheduler is disabled
+    pub fn spawn<F, Fut, E>(&self, job: F) -> Option<JoinHandle<()>>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), E>> + Send,
+        E: JobError + Send,
+    {
+        let schedule = self.schedule.clone()?;
+        let scheduler = self.clone();
+
+        info!("Scheduling catalog refresh {schedule}");
+
+        Some(tokio::spawn(async move {
+            loop {
+                let Some(next_run) = schedule.next_run(Utc::now()) else {
+                    warn!("Schedule {schedule} won't fire again, stopping scheduler");
+                    scheduler.status.write().await.next_run = None;
+                    return;
+                };
+                scheduler.status.write().await.next_run = Some(next_run);
+
+                let delay = (next_run - Utc::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(delay).await;
+
+                scheduler.run_once(&job).await;
+            }
+        }))
+    }
+
+    /// Runs `job` right away and records how it went
+    #[instrument(skip_all)]
+    pub async fn run_once<F, Fut, E>(&self, job: &F) -> bool
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = std::result::Result<(), E>>,
+        E: JobError,
+    {
+        {
+            let mut status = self.status.write().await;
+            status.running = true;
+            status.last_run = Some(Utc::now());
+        }
+
+        info!("Running scheduled catalog refresh");
+        let result = job().await;
+
+        let mut status = self.status.write().await;
+        status.running = false;
+        status.runs += 1;
+
+        match result {
+            Ok(()) => {
+                info!("Scheduled catalog refresh succeeded");
+                status.last_success = Some(Utc::now());
+                true
+            }
+            Err(err) => {
+                error!("Scheduled catalog refresh failed: {err}");
+                status.failures += 1;
+                status.last_failure = Some(Utc::now());
+                status.last_error = Some(err.public_message());
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct UpstreamDown;
+
+    impl Display for UpstreamDown {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "upstream down: GET https://catalog.internal/api?token=secret"
+            )
+        }
+    }
+
+    impl JobError for UpstreamDown {
+        fn public_message(&self) -> String {
+            "upstream down".to_owned()
+        }
+    }
+
+    fn config(interval_secs: Option<u64>, cron: Option<&str>) -> configs::Scheduler {
+        configs::Scheduler {
+            enabled: Some(true),
+            interval_secs,
+            cron: cron.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn parses_schedule_from_config() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+
+        let interval = Schedule::from_config(&config(Some(3600), None)).unwrap();
+        let daily = Schedule::from_config(&config(None, Some("0 3 * * *"))).unwrap();
+        let with_seconds = Schedule::from_config(&config(None, Some("30 0 3 * * *"))).unwrap();
+
+        assert_eq!(
+            interval.next_run(now),
+            Some(Utc.with_ymd_and_hms(2024, 5, 1, 13, 30, 0).unwrap())
+        );
+        assert_eq!(
+            daily.next_run(now),
+            Some(Utc.with_ymd_and_hms(2024, 5, 2, 3, 0, 0).unwrap())
+        );
+        assert_eq!(
+            with_seconds.next_run(now),
+            Some(Utc.with_ymd_and_hms(2024, 5, 2, 3, 0, 30).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        assert!(matches!(
+            Schedule::from_config(&config(None, None)),
+            Err(Error::MissingSchedule)
+        ));
+        assert!(matches!(
+            Schedule::from_config(&config(Some(60), Some("0 3 * * *"))),
+            Err(Error::ConflictingSchedules)
+        ));
+        assert!(matches!(
+            Schedule::from_config(&config(Some(0), None)),
+            Err(Error::ZeroInterval)
+        ));
+        assert!(matches!(
+            Schedule::from_config(&config(None, Some("every day"))),
+            Err(Error::InvalidCron(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn records_successes_and_failures() {
+        let scheduler = Scheduler::new(Schedule::Interval(Duration::from_secs(60)));
+
+        assert!(
+            scheduler
+                .run_once(&|| async { Ok::<_, UpstreamDown>(()) })
+                .await
+        );
+        assert!(!scheduler.run_once(&|| async { Err(UpstreamDown) }).await);
+
+        let status = scheduler.status().await;
+        assert!(status.enabled && !status.running);
+        assert_eq!((status.runs, status.failures), (2, 1));
+        assert!(status.last_success.is_some() && status.last_failure.is_some());
+        assert_eq!(status.last_error.as_deref(), Some("upstream down"));
+        assert_eq!(status.schedule.as_deref(), Some("every 60s"));
+    }
+
+    #[tokio::test]
+    async fn disabled_scheduler_never_runs() {
+        let scheduler = Scheduler::disabled();
+
+        assert!(scheduler
+            .spawn(|| async { Ok::<_, UpstreamDown>(()) })
+            .is_none());
+        assert!(!scheduler.status().await.enabled);
+    }
+}
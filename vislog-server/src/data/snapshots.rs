@@ -0,0 +1,482 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+};
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+use vislog_parser::{parse_course, parse_program};
+
+use crate::configs;
+
+use self::error::{Error, Result};
+
+use super::providers::json_providers::{FileJsonProvider, JsonProvider};
+
+pub mod error {
+    use thiserror::Error;
+
+    use crate::data::providers::json_providers;
+
+    pub type Result<T> = std::result::Result<T, Error>;
+
+    #[derive(Debug, Error)]
+    pub enum Error {
+        #[error("Failed to read or write a snapshot: {0}")]
+        Io(#[from] std::io::Error),
+
+        #[error("Failed to read the snapshot data: {0}")]
+        JsonProvider(#[from] json_providers::Error),
+
+        /// No snapshot with the given id exists
+        #[error("No snapshot with the id {0:?}")]
+        NotFound(String),
+
+        /// The contents of the snapshot no longer match the checksum it was saved with
+        #[error("Snapshot {id:?} no longer matches its checksum, its contents hash to {actual}")]
+        ChecksumMismatch { id: String, actual: String },
+
+        /// The snapshot could be read but none of its items could be parsed
+        #[error("None of the items in snapshot {id:?} could be parsed ({errors} errors)")]
+        Invalid { id: String, errors: usize },
+    }
+}
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+/// Has microseconds so that snapshots saved within the same second get different ids
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+/// Also reads the ids of snapshots saved before microseconds were added
+const TIMESTAMP_PARSE_FORMAT: &str = "%Y%m%dT%H%M%S%.fZ";
+const DEFAULT_MAX_SNAPSHOTS: usize = 5;
+
+/// The kind of catalog data a snapshot holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogKind {
+    Programs,
+    Courses,
+}
+
+impl CatalogKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogKind::Programs => "programs",
+            CatalogKind::Courses => "courses",
+        }
+    }
+}
+
+impl TryFrom<&str> for CatalogKind {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s {
+            "programs" => Ok(CatalogKind::Programs),
+            "courses" => Ok(CatalogKind::Courses),
+            _ => Err(Error::NotFound(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SnapshotInfo {
+    /// Formatted as `<timestamp>-<sha256 checksum>`
+    pub id: String,
+    pub kind: CatalogKind,
+    pub fetched_at: DateTime<Utc>,
+    pub checksum: String,
+    pub size: u64,
+    /// Whether this snapshot is the one currently being served
+    pub current: bool,
+}
+
+/// Saves a freshly fetched catalog as a new snapshot, validates it by parsing it, then promotes it
+/// to be the current data file and prunes old snapshots.
+///
+/// The current data file is left untouched if the snapshot fails validation.
+pub async fn save_and_promote(
+    data: &configs::Data,
+    kind: CatalogKind,
+    contents: &[u8],
+) -> Result<SnapshotInfo> {
+    let dir = snapshots_dir(data, kind);
+    fs::create_dir_all(&dir).await?;
+
+    let checksum = sha256_hex(contents);
+    let fetched_at = Utc::now();
+    let id = format!("{}-{}", fetched_at.format(TIMESTAMP_FORMAT), checksum);
+
+    // Never overwrite an existing snapshot
+    let mut f = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(snapshot_path(data, kind, &id))
+        .await?;
+    f.write_all(contents).await?;
+    f.flush().await?;
+    debug!("Saved {} snapshot {id}", kind.as_str());
+
+    // Invalid snapshots are discarded so that they don't push valid ones out during pruning
+    if let Err(err) = validate(data, kind, &id).await {
+        warn!("Discarding invalid {} snapshot {id}: {err}", kind.as_str());
+        fs::remove_file(snapshot_path(data, kind, &id)).await?;
+        return Err(err);
+    }
+    promote(data, kind, &id).await?;
+    prune(data, kind).await?;
+
+    Ok(SnapshotInfo {
+        id,
+        kind,
+        fetched_at,
+        checksum,
+        size: contents.len() as u64,
+        current: true,
+    })
+}
+
+/// Lists the snapshots of `kind` from newest to oldest
+pub async fn list(data: &configs::Data, kind: CatalogKind) -> Result<Vec<SnapshotInfo>> {
+    let dir = snapshots_dir(data, kind);
+    if !fs::try_exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let current_checksum = match fs::read(current_path(data, kind)).await {
+        Ok(contents) => Some(sha256_hex(&contents)),
+        Err(_) => None,
+    };
+
+    let mut snapshots = Vec::new();
+    let mut entries = fs::read_dir(&dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name();
+        let Some(id) = file_name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        let Some((fetched_at, checksum)) = parse_id(id) else {
+            warn!("Ignoring unrecognized file in snapshots directory: {file_name:?}");
+            continue;
+        };
+
+        snapshots.push(SnapshotInfo {
+            id: id.to_owned(),
+            kind,
+            fetched_at,
+            current: current_checksum.as_deref() == Some(checksum),
+            checksum: checksum.to_owned(),
+            size: entry.metadata().await?.len(),
+        });
+    }
+
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.fetched_at));
+
+    Ok(snapshots)
+}
+
+/// Verifies the checksum of a previous snapshot and makes it the current data file again
+pub async fn rollback(data: &configs::Data, kind: CatalogKind, id: &str) -> Result<SnapshotInfo> {
+    let snapshot = list(data, kind)
+        .await?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| Error::NotFound(id.to_owned()))?;
+
+    let contents = fs::read(snapshot_path(data, kind, id)).await?;
+    let actual = sha256_hex(&contents);
+    if actual != snapshot.checksum {
+        return Err(Error::ChecksumMismatch {
+            id: id.to_owned(),
+            actual,
+        });
+    }
+
+    validate(data, kind, id).await?;
+    promote(data, kind, id).await?;
+    info!("Rolled back {} to snapshot {id}", kind.as_str());
+
+    Ok(SnapshotInfo {
+        current: true,
+        ..snapshot
+    })
+}
+
+/// Makes sure that the snapshot is in the layout returned by the API and that at least some of its
+/// items can be parsed
+async fn validate(data: &configs::Data, kind: CatalogKind, id: &str) -> Result<()> {
+    let provider = FileJsonProvider::init(
+        snapshots_dir(data, kind),
+        PathBuf::from(format!("{id}.json")),
+    )?;
+
+    let (parsed, errors) = match kind {
+        CatalogKind::Programs => {
+            let (programs, errors) = provider
+                .stream_program_jsons()
+                .await?
+                .parse_each(parse_program)
+                .await?;
+            (programs.len(), errors.len())
+        }
+        CatalogKind::Courses => {
+            let (courses, errors) = provider
+                .stream_course_jsons()
+                .await?
+                .parse_each(parse_course)
+                .await?;
+            (courses.len(), errors.len())
+        }
+    };
+
+    debug!(
+        "Validated {} snapshot {id}: {parsed} parsed, {errors} errors",
+        kind.as_str()
+    );
+
+    if parsed == 0 {
+        return Err(Error::Invalid {
+            id: id.to_owned(),
+            errors,
+        });
+    }
+
+    Ok(())
+}
+
+/// Copies the snapshot next to the current data file and atomically renames it over the current
+/// data file so that readers never see a partially written file
+async fn promote(data: &configs::Data, kind: CatalogKind, id: &str) -> Result<()> {
+    let current = current_path(data, kind);
+    let mut tmp = current.clone().into_os_string();
+    tmp.push(".tmp");
+
+    fs::copy(snapshot_path(data, kind, id), &tmp).await?;
+    fs::rename(&tmp, &current).await?;
+
+    Ok(())
+}
+
+/// Removes the oldest snapshots so that at most `data.max_snapshots` are kept. The snapshot
+/// currently being served is never removed.
+async fn prune(data: &configs::Data, kind: CatalogKind) -> Result<()> {
+    let max_snapshots = data.max_snapshots.unwrap_or(DEFAULT_MAX_SNAPSHOTS);
+
+    for snapshot in list(data, kind).await?.into_iter().skip(max_snapshots) {
+        if snapshot.current {
+            continue;
+        }
+
+        debug!("Pruning {} snapshot {}", kind.as_str(), snapshot.id);
+        fs::remove_file(snapshot_path(data, kind, &snapshot.id)).await?;
+    }
+
+    Ok(())
+}
+
+fn snapshots_dir(data: &configs::Data, kind: CatalogKind) -> PathBuf {
+    data.storage.join(SNAPSHOTS_DIR).join(kind.as_str())
+}
+
+fn snapshot_path(data: &configs::Data, kind: CatalogKind, id: &str) -> PathBuf {
+    snapshots_dir(data, kind).join(format!("{id}.json"))
+}
+
+/// The file in `data.storage` holding the data currently being served
+fn current_path(data: &configs::Data, kind: CatalogKind) -> PathBuf {
+    match kind {
+        CatalogKind::Programs => data.storage.join(&data.all_programs_file),
+        CatalogKind::Courses => data.storage.join(&data.all_courses_file),
+    }
+}
+
+fn parse_id(id: &str) -> Option<(DateTime<Utc>, &str)> {
+    let (timestamp, checksum) = id.split_once('-')?;
+    let fetched_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_PARSE_FORMAT)
+        .ok()?
+        .and_utc();
+
+    Some((fetched_at, checksum))
+}
+
+fn sha256_hex(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn data(dir: &tempfile::TempDir, max_snapshots: usize) -> configs::Data {
+        configs::Data {
+            storage: dir.path().to_owned(),
+            all_programs_file: "programs.json".into(),
+            all_courses_file: "courses.json".into(),
+            max_snapshots: Some(max_snapshots),
+            provider: None,
+            web: None,
+            sqlite: None,
+        }
+    }
+
+    /// A programs data file holding the CS major under `title`
+    fn programs(title: &str) -> Vec<u8> {
+        let mut program: Value =
+            serde_json::from_str(&std::fs::read_to_string("../data/cs_major.json").unwrap())
+                .unwrap();
+        program["title"] = json!(title);
+
+        serde_json::to_vec(&json!({ "programs": { "program": [program] } })).unwrap()
+    }
+
+    fn current(data: &configs::Data) -> Vec<u8> {
+        std::fs::read(current_path(data, CatalogKind::Programs)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn promotes_saved_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = data(&dir, 5);
+        let contents = programs("First");
+
+        let saved = save_and_promote(&data, CatalogKind::Programs, &contents)
+            .await
+            .unwrap();
+        let listed = list(&data, CatalogKind::Programs).await.unwrap();
+
+        assert_eq!(current(&data), contents);
+        assert_eq!(saved.checksum, sha256_hex(&contents));
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, saved.id);
+        assert!(listed[0].current);
+    }
+
+    #[tokio::test]
+    async fn snapshots_saved_in_the_same_second_get_different_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = data(&dir, 5);
+        let contents = programs("Same");
+
+        let first = save_and_promote(&data, CatalogKind::Programs, &contents)
+            .await
+            .unwrap();
+        let second = save_and_promote(&data, CatalogKind::Programs, &contents)
+            .await
+            .unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(list(&data, CatalogKind::Programs).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn discards_invalid_snapshot_without_promoting_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = data(&dir, 5);
+        let contents = programs("Valid");
+        save_and_promote(&data, CatalogKind::Programs, &contents)
+            .await
+            .unwrap();
+
+        let invalid = json!({ "programs": { "program": [{ "title": "Unparsable" }] } });
+        let result = save_and_promote(
+            &data,
+            CatalogKind::Programs,
+            &serde_json::to_vec(&invalid).unwrap(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::Invalid { errors: 1, .. })));
+        assert_eq!(current(&data), contents);
+        assert_eq!(list(&data, CatalogKind::Programs).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rolls_back_only_to_snapshots_matching_their_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = data(&dir, 5);
+        let (first, second, third) = (programs("First"), programs("Second"), programs("Third"));
+        let first_id = save_and_promote(&data, CatalogKind::Programs, &first)
+            .await
+            .unwrap()
+            .id;
+        let second_id = save_and_promote(&data, CatalogKind::Programs, &second)
+            .await
+            .unwrap()
+            .id;
+        save_and_promote(&data, CatalogKind::Programs, &third)
+            .await
+            .unwrap();
+
+        let tampered = snapshot_path(&data, CatalogKind::Programs, &second_id);
+        std::fs::write(&tampered, programs("Tampered")).unwrap();
+        let result = rollback(&data, CatalogKind::Programs, &second_id).await;
+
+        assert!(matches!(result, Err(Error::ChecksumMismatch { .. })));
+        assert_eq!(current(&data), third);
+
+        let rolled_back = rollback(&data, CatalogKind::Programs, &first_id)
+            .await
+            .unwrap();
+
+        assert!(rolled_back.current);
+        assert_eq!(current(&data), first);
+        assert!(matches!(
+            rollback(&data, CatalogKind::Programs, "missing").await,
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn prunes_oldest_snapshots_but_keeps_the_current_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = data(&dir, 2);
+        let oldest = save_and_promote(&data, CatalogKind::Programs, &programs("1"))
+            .await
+            .unwrap();
+        let middle = save_and_promote(&data, CatalogKind::Programs, &programs("2"))
+            .await
+            .unwrap();
+        // Same contents as the oldest snapshot, which makes it current again
+        let newest = save_and_promote(&data, CatalogKind::Programs, &programs("1"))
+            .await
+            .unwrap();
+
+        let ids: Vec<String> = list(&data, CatalogKind::Programs)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+
+        assert_eq!(ids, vec![newest.id.clone(), middle.id, oldest.id.clone()]);
+
+        save_and_promote(&data, CatalogKind::Programs, &programs("3"))
+            .await
+            .unwrap();
+        let ids: Vec<String> = list(&data, CatalogKind::Programs)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[1], newest.id);
+    }
+
+    #[test]
+    fn reads_ids_without_microseconds() {
+        let (fetched_at, checksum) = parse_id("20261018T184725Z-abc").unwrap();
+
+        assert_eq!(fetched_at.to_rfc3339(), "2026-10-18T18:47:25+00:00");
+        assert_eq!(checksum, "abc");
+    }
+}
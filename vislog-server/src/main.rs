@@ -1,7 +1,10 @@
 use std::net::SocketAddr;
 
-use data::fetching;
-use data::providers::json_providers::FileJsonProvider;
+use data::providers::json_providers::{FileJsonProvider, WebJsonProvider};
+use data::providers::sqlite_provider::SqliteProvider;
+use data::scheduler::{Schedule, Scheduler};
+use data::snapshots::CatalogKind;
+use data::{fetching, maps};
 use lazy_static::lazy_static;
 use tokio::net::TcpListener;
 use tracing::{error, info, warn};
@@ -11,7 +14,7 @@ use tracing_subscriber::{fmt, EnvFilter};
 
 use web::init_server;
 
-use crate::configs::ServerConfig;
+use crate::configs::{JsonProviderKind, ServerConfig};
 use crate::data::providers::courses::CoursesProvider;
 use crate::data::providers::json_providers;
 use crate::data::providers::programs::ProgramsProvider;
@@ -21,10 +24,8 @@ mod data;
 mod web;
 
 lazy_static! {
-    pub static ref CONFIGS: ServerConfig = ServerConfig::new().expect(&format!(
-        "Failed to load config file '{}'",
-        configs::CONFIG_FILE_PATH
-    ));
+    pub static ref CONFIGS: ServerConfig = ServerConfig::new()
+        .unwrap_or_else(|_| panic!("Failed to load config file '{}'", configs::CONFIG_FILE_PATH));
 }
 
 #[tokio::main]
@@ -51,19 +52,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
 
     let (programs_provider, courses_provider) = init_programs_and_courses_providers().await?;
+    maps::spawn_generate(&programs_provider, &courses_provider);
+
+    let scheduler = init_scheduler()?;
+    scheduler.spawn({
+        let (programs_provider, courses_provider) =
+            (programs_provider.clone(), courses_provider.clone());
+
+        move || {
+            let (programs_provider, courses_provider) =
+                (programs_provider.clone(), courses_provider.clone());
+            async move { fetching::refresh_catalog(&programs_provider, &courses_provider).await }
+        }
+    });
 
     let addr = format!("{}:{}", CONFIGS.server.host, CONFIGS.server.port);
     let listener = TcpListener::bind(&addr).await?;
     let server = init_server(
         programs_provider,
         courses_provider,
+        scheduler,
         CONFIGS.static_assets.as_ref().map(|c| c.dir.clone()),
     );
 
     info!("Listening at {addr}");
 
     if let Some(cors) = &CONFIGS.cors {
-        if cors.origins.len() >= 1 {
+        if !cors.origins.is_empty() {
             info!(
                 "Allowing requests from origins: \"{}\"",
                 cors.origins_to_string()
@@ -80,8 +95,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn init_scheduler() -> Result<Scheduler, Box<dyn std::error::Error>> {
+    let Some(config) = CONFIGS
+        .scheduler
+        .as_ref()
+        .filter(|config| config.enabled.unwrap_or(true))
+    else {
+        info!("Scheduled catalog refresh is disabled");
+        return Ok(Scheduler::disabled());
+    };
+
+    Ok(Scheduler::new(Schedule::from_config(config)?))
+}
+
 async fn init_programs_and_courses_providers(
 ) -> Result<(ProgramsProvider, CoursesProvider), Box<dyn std::error::Error>> {
+    match CONFIGS.data.provider.unwrap_or_default() {
+        JsonProviderKind::File => {}
+        JsonProviderKind::Web => return init_web_programs_and_courses_providers(),
+        JsonProviderKind::Sqlite => return init_sqlite_programs_and_courses_providers().await,
+    }
+
     // TODO: Figure out why logs in this code block doesn't work
     let programs_provider = {
         let (json_provider, need_refetch) = {
@@ -93,7 +127,7 @@ async fn init_programs_and_courses_providers(
 
                     tokio::fs::File::create(&path)
                         .await
-                        .expect(&format!("Should be able to create file at {path:?}"));
+                        .unwrap_or_else(|_| panic!("Should be able to create file at {path:?}"));
 
                     // Try to initialize file provider again. Hard fail if creating data file doesn't
                     // fix the issue
@@ -134,7 +168,7 @@ async fn init_programs_and_courses_providers(
 
                     tokio::fs::File::create(&path)
                         .await
-                        .expect(&format!("Should be able to create file at {path:?}"));
+                        .unwrap_or_else(|_| panic!("Should be able to create file at {path:?}"));
 
                     // Try to initialize file provider again. Hard fail if creating data file doesn't
                     // fix the issue
@@ -167,3 +201,44 @@ async fn init_programs_and_courses_providers(
 
     Ok((programs_provider, courses_provider))
 }
+
+fn init_web_programs_and_courses_providers(
+) -> Result<(ProgramsProvider, CoursesProvider), Box<dyn std::error::Error>> {
+    let config = CONFIGS
+        .data
+        .web
+        .as_ref()
+        .ok_or("`data.provider` is \"web\" but the `[data.web]` section is missing")?;
+
+    info!("Reading catalog data from {}", config.base_url);
+    let json_provider = WebJsonProvider::init(config)?;
+
+    Ok((
+        ProgramsProvider::with(Box::new(json_provider.clone())),
+        CoursesProvider::with(Box::new(json_provider)),
+    ))
+}
+
+async fn init_sqlite_programs_and_courses_providers(
+) -> Result<(ProgramsProvider, CoursesProvider), Box<dyn std::error::Error>> {
+    let config = CONFIGS
+        .data
+        .sqlite
+        .as_ref()
+        .ok_or("`data.provider` is \"sqlite\" but the `[data.sqlite]` section is missing")?;
+
+    info!("Reading catalog data from {:?}", config.path);
+    let json_provider = SqliteProvider::init(config, &CONFIGS.data)?;
+
+    // Keep serving what was imported before if the data files can't be imported
+    for kind in [CatalogKind::Programs, CatalogKind::Courses] {
+        if let Err(err) = json_provider.import(kind).await {
+            warn!("Failed to import {}: {err}", kind.as_str());
+        }
+    }
+
+    Ok((
+        ProgramsProvider::with(Box::new(json_provider.clone())),
+        CoursesProvider::with(Box::new(json_provider)),
+    ))
+}
@@ -0,0 +1,312 @@
+use axum::{
+    extract::State,
+    middleware::from_fn_with_state,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, instrument};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use vislog_core::{parsing::guid::Guid, CourseDetails, Program};
+
+use crate::data::{
+    fetching, maps,
+    providers::{courses::CoursesProvider, programs::ProgramsProvider, CacheStats, RefreshReport},
+    snapshots::{self, CatalogKind, SnapshotInfo},
+};
+use crate::web::{
+    error::{ErrorBody, Result},
+    extract::{Path, Query},
+    middleware::admin::{mw_require_admin_token, AdminAuth, AdminIdentity},
+};
+use crate::CONFIGS;
+
+#[derive(Clone)]
+struct AdminState {
+    programs_provider: ProgramsProvider,
+    courses_provider: CoursesProvider,
+}
+
+/// Routes that change the data being served. Every request needs an admin token
+pub fn routes(
+    programs_provider: ProgramsProvider,
+    courses_provider: CoursesProvider,
+    auth: AdminAuth,
+) -> Router {
+    Router::new()
+        .route("/programs/refresh", post(refresh_programs_handler))
+        .route("/courses/refresh", post(refresh_courses_handler))
+        .route("/cache", get(get_cache_stats_handler))
+        .route("/cache/reload", post(reload_cache_handler))
+        .route(
+            "/snapshots/:kind/:id/rollback",
+            post(rollback_snapshot_handler),
+        )
+        .with_state(AdminState {
+            programs_provider,
+            courses_provider,
+        })
+        .layer(from_fn_with_state(auth, mw_require_admin_token))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    refresh_programs_handler,
+    refresh_courses_handler,
+    get_cache_stats_handler,
+    reload_cache_handler,
+    rollback_snapshot_handler,
+))]
+pub struct AdminApi;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RefreshParam {
+    /// Id of the single item to refresh. Everything is refreshed if omitted
+    id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+enum ProgramsRefreshResponse {
+    All(Vec<Program>),
+    One(Program),
+}
+
+/// Fetches programs from the catalog API
+///
+/// Only the cache is refreshed when the programs are read straight from the catalog API
+#[utoipa::path(
+    post,
+    path = "/programs/refresh",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(RefreshParam),
+    responses(
+        (status = 200, body = ProgramsRefreshResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 429, description = "Too many admin requests", body = ErrorBody),
+        (status = 502, description = "The catalog API failed", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, identity), fields(admin = identity.name), err)]
+async fn refresh_programs_handler(
+    Extension(identity): Extension<AdminIdentity>,
+    Query(RefreshParam { id }): Query<RefreshParam>,
+    State(state): State<AdminState>,
+) -> Result<Json<ProgramsRefreshResponse>> {
+    if let Some(id) = id {
+        info!("Refreshing program with id: {id}");
+        let program = state.programs_provider.refresh_program(&id).await?;
+        maps::spawn_generate(&state.programs_provider, &state.courses_provider);
+
+        return Ok(Json(ProgramsRefreshResponse::One(program)));
+    }
+
+    info!("Refreshing all programs");
+    let programs = fetching::refresh_programs(&state.programs_provider).await?;
+    maps::spawn_generate(&state.programs_provider, &state.courses_provider);
+
+    debug!("Programs count after refresh: {}", programs.len());
+
+    Ok(Json(ProgramsRefreshResponse::All(programs)))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+enum CoursesRefreshResponse {
+    All(Vec<CourseDetails>),
+    One(Box<CourseDetails>),
+}
+
+/// Fetches courses from the catalog API
+///
+/// Only the cache is refreshed when the courses are read straight from the catalog API
+#[utoipa::path(
+    post,
+    path = "/courses/refresh",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(RefreshParam),
+    responses(
+        (status = 200, body = CoursesRefreshResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 429, description = "Too many admin requests", body = ErrorBody),
+        (status = 502, description = "The catalog API failed", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, identity), fields(admin = identity.name), err)]
+async fn refresh_courses_handler(
+    Extension(identity): Extension<AdminIdentity>,
+    Query(RefreshParam { id }): Query<RefreshParam>,
+    State(state): State<AdminState>,
+) -> Result<Json<CoursesRefreshResponse>> {
+    if let Some(id) = id {
+        info!("Refreshing course with id: {id}");
+        let course = state.courses_provider.refresh_course(&id).await?;
+        maps::spawn_generate(&state.programs_provider, &state.courses_provider);
+
+        return Ok(Json(CoursesRefreshResponse::One(Box::new(course))));
+    }
+
+    info!("Refreshing all courses");
+    let courses = fetching::refresh_courses(&state.courses_provider).await?;
+    maps::spawn_generate(&state.programs_provider, &state.courses_provider);
+
+    debug!("Number of courses after refresh: {}", courses.len());
+
+    Ok(Json(CoursesRefreshResponse::All(courses)))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct CacheStatsResponse {
+    programs: CacheStats,
+    courses: CacheStats,
+}
+
+#[utoipa::path(
+    get,
+    path = "/cache",
+    tag = "admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = CacheStatsResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 429, description = "Too many admin requests", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state))]
+async fn get_cache_stats_handler(State(state): State<AdminState>) -> Json<CacheStatsResponse> {
+    info!("Getting cache stats");
+
+    Json(CacheStatsResponse {
+        programs: state.programs_provider.cache_stats(),
+        courses: state.courses_provider.cache_stats(),
+    })
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ReloadParam {
+    /// Both caches are reloaded if omitted
+    kind: Option<CatalogKind>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+struct ReloadResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    programs: Option<RefreshReport<Guid>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    courses: Option<RefreshReport<Guid>>,
+}
+
+/// Reloads the caches from the current data without fetching from the catalog API
+#[utoipa::path(
+    post,
+    path = "/cache/reload",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(ReloadParam),
+    responses(
+        (status = 200, body = ReloadResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 429, description = "Too many admin requests", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, identity), fields(admin = identity.name), err)]
+async fn reload_cache_handler(
+    Extension(identity): Extension<AdminIdentity>,
+    Query(ReloadParam { kind }): Query<ReloadParam>,
+    State(state): State<AdminState>,
+) -> Result<Json<ReloadResponse>> {
+    info!("Reloading cache of {kind:?}");
+
+    let mut response = ReloadResponse::default();
+    if kind.is_none_or(|kind| kind == CatalogKind::Programs) {
+        response.programs = Some(state.programs_provider.refresh_cache().await?);
+    }
+    if kind.is_none_or(|kind| kind == CatalogKind::Courses) {
+        response.courses = Some(state.courses_provider.refresh_cache().await?);
+    }
+    maps::spawn_generate(&state.programs_provider, &state.courses_provider);
+
+    Ok(Json(response))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct RollbackResponse {
+    snapshot: SnapshotInfo,
+    /// What changed in the served data after rolling back
+    refresh: RefreshReport<Guid>,
+}
+
+/// Serves the data of an older snapshot again
+#[utoipa::path(
+    post,
+    path = "/snapshots/{kind}/{id}/rollback",
+    tag = "admin",
+    security(("admin_token" = [])),
+    params(
+        ("kind" = CatalogKind, Path),
+        ("id" = String, Path, description = "Id of the snapshot"),
+    ),
+    responses(
+        (status = 200, body = RollbackResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 404, description = "No snapshot with the id", body = ErrorBody),
+        (status = 409, description = "The snapshot doesn't match its checksum", body = ErrorBody),
+        (status = 422, description = "The snapshot failed to parse", body = ErrorBody),
+        (status = 429, description = "Too many admin requests", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, identity), fields(admin = identity.name), err)]
+async fn rollback_snapshot_handler(
+    Extension(identity): Extension<AdminIdentity>,
+    Path((kind, id)): Path<(String, String)>,
+    State(state): State<AdminState>,
+) -> Result<Json<RollbackResponse>> {
+    info!("Rolling back {kind} to snapshot {id}");
+
+    let kind = CatalogKind::try_from(kind.as_str())?;
+    let snapshot = snapshots::rollback(&CONFIGS.data, kind, &id).await?;
+
+    let refresh = match kind {
+        CatalogKind::Programs => state.programs_provider.refresh_cache().await?,
+        CatalogKind::Courses => state.courses_provider.refresh_cache().await?,
+    };
+    maps::spawn_generate(&state.programs_provider, &state.courses_provider);
+
+    Ok(Json(RollbackResponse { snapshot, refresh }))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use tower::ServiceExt;
+
+    use crate::data::providers::json_providers::FileJsonProvider;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn rollback_requires_admin_token() {
+        let programs = FileJsonProvider::init("../data", "programs.json").unwrap();
+        let courses = FileJsonProvider::init("../data", "courses.json").unwrap();
+        let app = routes(
+            ProgramsProvider::with(Box::new(programs)),
+            CoursesProvider::with(Box::new(courses)),
+            AdminAuth::from_config(None),
+        );
+
+        let res = app
+            .oneshot(
+                Request::post("/snapshots/programs/20240101T000000Z/rollback")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
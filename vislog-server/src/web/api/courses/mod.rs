@@ -1,59 +1,343 @@
-use axum::{
-    extract::{Path, State},
-    routing::get,
-    Json, Router,
-};
-
+use axum::{extract::State, response::Response, routing::get, Json, Router};
+use serde_json::Value;
 use tracing::{debug, info, instrument};
-use vislog_core::{parsing::guid::Guid, CourseDetails};
+use utoipa::OpenApi;
+use vislog_core::{export::course_rows, parsing::guid::Guid, CourseDetails};
+
+use crate::data::providers::courses::CoursesProvider;
+use vislog_parser::ParsingError;
 
-use crate::data::{fetching, providers::courses::CoursesProvider};
-use crate::web::error::{Error, Result};
+use crate::web::{
+    api::{
+        export_response, programs::ItemIdParam, with_parse_error_count, Cached, ExportParams,
+        WithParseErrorCount,
+    },
+    error::{Error, ErrorBody, Result},
+    extract::{Path, Query},
+};
 
 pub fn routes(courses_provider: CoursesProvider) -> Router {
     Router::new()
         .route("/", get(get_all_courses_handler))
         .route("/:guid", get(get_course_handler))
-        .route("/refresh", get(refresh_courses_handler))
+        .route("/:guid/equivalents", get(get_equivalent_courses_handler))
+        .route("/by-code/:subject/:number", get(get_course_by_code_handler))
+        .route("/json", get(get_course_json_handler))
+        .route("/errors", get(get_all_course_errors_handler))
+        .route("/export", get(export_courses_handler))
         .with_state(courses_provider)
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    get_all_courses_handler,
+    get_course_handler,
+    get_equivalent_courses_handler,
+    get_course_by_code_handler,
+    get_course_json_handler,
+    get_all_course_errors_handler,
+    export_courses_handler,
+))]
+pub struct CoursesApi;
+
+/// All courses that could be parsed
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "courses",
+    responses(
+        (status = 200, body = Vec<CourseDetails>, headers(
+            ("x-parse-error-count" = usize, description = "Number of courses that failed to parse")
+        )),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
 #[instrument(skip(courses_provider))]
 async fn get_all_courses_handler(
     State(courses_provider): State<CoursesProvider>,
-) -> Result<Json<Vec<CourseDetails>>> {
+) -> Result<Cached<WithParseErrorCount<Vec<CourseDetails>>>> {
     info!("Getting all courses");
 
+    let version = courses_provider.cache_version().await?;
     let (courses, errors) = courses_provider.get_all_courses().await?;
 
     debug!("courses: {}, errors: {}", courses.len(), errors.len());
 
-    Ok(Json(courses))
+    Ok(Cached(
+        version,
+        with_parse_error_count(errors.len(), courses),
+    ))
+}
+
+/// Every course that could be parsed as a CSV or XLSX file, one row per course sorted by subject
+/// and number
+#[utoipa::path(
+    get,
+    path = "/export",
+    tag = "courses",
+    params(ExportParams),
+    responses(
+        (status = 200, description = "CSV or XLSX file", content(
+            (String = "text/csv"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        )),
+        (status = 400, description = "Invalid format", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(courses_provider), err)]
+async fn export_courses_handler(
+    State(courses_provider): State<CoursesProvider>,
+    Query(ExportParams { format }): Query<ExportParams>,
+) -> Result<Response> {
+    info!("Exporting all courses");
+
+    let (courses, _errors) = courses_provider.get_all_courses().await?;
+
+    debug!("courses: {}", courses.len());
+
+    export_response(
+        format.unwrap_or_default(),
+        "courses",
+        &course_rows(&courses),
+    )
 }
 
+/// Errors of the courses that failed to parse
+#[utoipa::path(
+    get,
+    path = "/errors",
+    tag = "courses",
+    responses(
+        (status = 200, body = Vec<ParsingError>),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(courses_provider))]
+async fn get_all_course_errors_handler(
+    State(courses_provider): State<CoursesProvider>,
+) -> Result<Cached<Json<Vec<ParsingError>>>> {
+    info!("Getting all course parsing errors");
+
+    let version = courses_provider.cache_version().await?;
+    let (_courses, errors) = courses_provider.get_all_courses().await?;
+
+    debug!("errors: {}", errors.len());
+
+    Ok(Cached(version, Json(errors)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{guid}",
+    tag = "courses",
+    params(("guid" = Guid, Path, description = "GUID of the course, without braces")),
+    responses(
+        (status = 200, body = CourseDetails),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 400, description = "Invalid GUID", body = ErrorBody),
+        (status = 404, description = "No course with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
 #[instrument(skip(courses_provider))]
 async fn get_course_handler(
     Path(guid): Path<Guid>,
     State(courses_provider): State<CoursesProvider>,
-) -> Result<Json<CourseDetails>> {
+) -> Result<Cached<Json<CourseDetails>>> {
     info!("Getting course with guid: {}", guid);
 
+    let version = courses_provider.cache_version().await?;
     let course = courses_provider
         .get_course(&guid)
         .await?
         .ok_or(Error::CourseNotFound(guid))?;
 
-    Ok(Json(course))
+    Ok(Cached(version, Json(course)))
 }
 
+/// The courses cross-listed with a course, which count as the same course. Sorted by code and
+/// empty if the course isn't cross-listed
+#[utoipa::path(
+    get,
+    path = "/{guid}/equivalents",
+    tag = "courses",
+    params(("guid" = Guid, Path, description = "GUID of the course, without braces")),
+    responses(
+        (status = 200, body = Vec<CourseDetails>),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 400, description = "Invalid GUID", body = ErrorBody),
+        (status = 404, description = "No course with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
 #[instrument(skip(courses_provider))]
-async fn refresh_courses_handler(
+async fn get_equivalent_courses_handler(
+    Path(guid): Path<Guid>,
     State(courses_provider): State<CoursesProvider>,
-) -> Result<Json<Vec<CourseDetails>>> {
-    info!("Refreshing all courses");
-    let courses = fetching::fetch_all_courses(&courses_provider).await?;
+) -> Result<Cached<Json<Vec<CourseDetails>>>> {
+    info!("Getting courses equivalent to course with guid: {}", guid);
+
+    let version = courses_provider.cache_version().await?;
+    let courses = courses_provider
+        .get_equivalent_courses(&guid)
+        .await?
+        .ok_or(Error::CourseNotFound(guid))?;
+
+    debug!("equivalents: {}", courses.len());
+
+    Ok(Cached(version, Json(courses)))
+}
+
+/// A course by the subject code and number humans refer to it by (Ex: `CSC/215`), ignoring case.
+/// Numbers containing a `/` have to be percent-encoded. If several courses share the code, the
+/// error lists their GUIDs
+#[utoipa::path(
+    get,
+    path = "/by-code/{subject}/{number}",
+    tag = "courses",
+    params(
+        ("subject" = String, Path, description = "Subject code of the course", example = "CSC"),
+        ("number" = String, Path, description = "Number of the course", example = "215"),
+    ),
+    responses(
+        (status = 200, body = CourseDetails),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 404, description = "No course with the subject code and number", body = ErrorBody),
+        (status = 409, description = "Several courses have the subject code and number", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(courses_provider))]
+async fn get_course_by_code_handler(
+    Path((subject_code, number)): Path<(String, String)>,
+    State(courses_provider): State<CoursesProvider>,
+) -> Result<Cached<Json<CourseDetails>>> {
+    info!("Getting course with code: {subject_code} {number}");
+
+    let version = courses_provider.cache_version().await?;
+    let mut courses = courses_provider
+        .get_courses_by_code(&subject_code, &number)
+        .await?;
+
+    match courses.len() {
+        0 => Err(Error::CourseCodeNotFound {
+            subject_code,
+            number,
+        }),
+        1 => Ok(Cached(version, Json(courses.remove(0)))),
+        _ => Err(Error::AmbiguousCourseCode {
+            subject_code,
+            number,
+            guids: courses.iter().map(|course| course.guid).collect(),
+        }),
+    }
+}
+
+/// The unparsed JSON of a single course as returned by the catalog API
+#[utoipa::path(
+    get,
+    path = "/json",
+    tag = "courses",
+    params(ItemIdParam),
+    responses(
+        (status = 200, body = Object),
+        (status = 404, description = "No course matches the id", body = ErrorBody),
+    )
+)]
+#[instrument(skip(courses_provider))]
+async fn get_course_json_handler(
+    Query(ItemIdParam { id }): Query<ItemIdParam>,
+    State(courses_provider): State<CoursesProvider>,
+) -> Result<Json<Value>> {
+    info!("Getting course json with id: {id}");
+
+    let course_json = courses_provider.get_course_json(&id).await?;
+
+    Ok(Json(course_json))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use axum::{
+        body::{to_bytes, Body},
+        http::{Request, StatusCode},
+        middleware::map_response,
+    };
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use crate::{
+        data::providers::json_providers::FileJsonProvider,
+        web::middleware::errors::mw_map_error_response,
+    };
+
+    use super::*;
+
+    fn course(guid: &str) -> Value {
+        json!({
+            "url": format!("https://catalog/{guid}"),
+            "path": format!("/{guid}"),
+            "GUID": format!("{{{guid}}}"),
+            "subject_code": "CSC",
+            "subject_name": "Computer Science",
+            "number": "105",
+            "name": "Computer Science I",
+            "credits_min": "3.0",
+            "credits_max": null,
+            "description": "",
+            "prerequisite_narrative": null,
+            "corequisite_narrative": null,
+        })
+    }
+
+    #[tokio::test]
+    async fn lists_every_course_sharing_a_code() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("courses.json"),
+            json!({ "courses": { "course": [
+                course("00000000-0000-0000-0000-000000000001"),
+                course("00000000-0000-0000-0000-000000000002"),
+            ] } })
+            .to_string(),
+        )
+        .unwrap();
+        let courses_provider = CoursesProvider::with(Box::new(
+            FileJsonProvider::init(dir.path(), Path::new("courses.json")).unwrap(),
+        ));
+        let app = routes(courses_provider).layer(map_response(mw_map_error_response));
 
-    debug!("Number of courses after refresh: {}", courses.len());
+        let res = app
+            .oneshot(
+                Request::get("/by-code/csc/105")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
 
-    Ok(Json(courses))
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "AMBIGUOUS_COURSE_CODE");
+        let mut guids: Vec<&str> = body["details"]["guids"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|guid| guid.as_str().unwrap())
+            .collect();
+        guids.sort();
+        assert_eq!(
+            guids,
+            vec![
+                "00000000-0000-0000-0000-000000000001",
+                "00000000-0000-0000-0000-000000000002"
+            ]
+        );
+    }
 }
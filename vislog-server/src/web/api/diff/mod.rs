@@ -0,0 +1,56 @@
+use axum::{routing::get, Json, Router};
+use serde::Deserialize;
+use tracing::{debug, info, instrument};
+use utoipa::{IntoParams, OpenApi};
+use vislog_core::diff::CatalogDiff;
+
+use crate::data::diffing;
+use crate::web::{
+    error::{ErrorBody, Result},
+    extract::Query,
+};
+
+pub fn routes() -> Router {
+    Router::new().route("/", get(get_diff_handler))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_diff_handler))]
+pub struct DiffApi;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct DiffParams {
+    /// Directory of the old catalog relative to the storage directory. Ex: "2023"
+    from: String,
+    /// Directory of the new catalog relative to the storage directory, "." for the one being
+    /// served
+    to: String,
+}
+
+/// What changed between two catalogs in the storage directory
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "diff",
+    params(DiffParams),
+    responses(
+        (status = 200, body = CatalogDiff),
+        (status = 400, description = "A catalog is not inside of the storage directory", body = ErrorBody),
+        (status = 404, description = "A catalog doesn't exist", body = ErrorBody),
+    )
+)]
+#[instrument(err)]
+async fn get_diff_handler(Query(params): Query<DiffParams>) -> Result<Json<CatalogDiff>> {
+    info!("Diffing catalog {:?} against {:?}", params.from, params.to);
+
+    let diff = diffing::diff_catalogs(&params.from, &params.to).await?;
+
+    debug!(
+        "Programs changed: {}, Courses changed: {}",
+        diff.programs.changed.len(),
+        diff.courses.changed.len()
+    );
+
+    Ok(Json(CatalogDiff::clone(&diff)))
+}
@@ -1,14 +1,224 @@
-use axum::Router;
+use axum::{
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde::Deserialize;
+use utoipa::{
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    IntoParams, Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+use vislog_core::export::{self, ExportFormat, Row};
 
-use crate::data::providers::{courses::CoursesProvider, programs::ProgramsProvider};
+use crate::{
+    data::{
+        providers::{courses::CoursesProvider, programs::ProgramsProvider, CacheVersion},
+        scheduler::Scheduler,
+    },
+    web::{error::Result, middleware::admin::AdminAuth},
+    CONFIGS,
+};
 
-pub mod error;
+/// Number of items that failed to parse, set on the responses listing programs or courses
+pub const PARSE_ERROR_COUNT_HEADER: &str = "x-parse-error-count";
 
+/// A JSON response with the `PARSE_ERROR_COUNT_HEADER` set
+pub type WithParseErrorCount<T> = ([(&'static str, String); 1], Json<T>);
+
+pub fn with_parse_error_count<T>(error_count: usize, body: T) -> WithParseErrorCount<T> {
+    (
+        [(PARSE_ERROR_COUNT_HEADER, error_count.to_string())],
+        Json(body),
+    )
+}
+
+/// A response built from cached data that clients can revalidate with `If-None-Match` or
+/// `If-Modified-Since`, handled by
+/// [mw_conditional_get](super::middleware::caching::mw_conditional_get)
+pub struct Cached<T>(pub CacheVersion, pub T);
+
+impl<T: IntoResponse> IntoResponse for Cached<T> {
+    fn into_response(self) -> Response {
+        let mut response = self.1.into_response();
+        response.extensions_mut().insert(self.0);
+
+        response
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportParams {
+    /// Defaults to `csv`
+    #[param(inline)]
+    pub format: Option<ExportFormat>,
+}
+
+/// Rows exported as a file to download, named `{file_stem}.csv` or `{file_stem}.xlsx`
+pub fn export_response<R: Row>(
+    format: ExportFormat,
+    file_stem: &str,
+    rows: &[R],
+) -> Result<Response> {
+    let file = export::write(format, rows)?;
+    let disposition = format!(
+        "attachment; filename=\"{file_stem}.{}\"",
+        format.extension()
+    );
+
+    Ok((
+        [
+            (CONTENT_TYPE, format.content_type().to_owned()),
+            (CONTENT_DISPOSITION, disposition),
+        ],
+        file,
+    )
+        .into_response())
+}
+
+mod admin;
 mod courses;
+mod diff;
 mod programs;
+mod snapshots;
+mod status;
+mod subjects;
 
-pub fn routes(programs_provider: ProgramsProvider, courses_provider: CoursesProvider) -> Router {
+/// OpenAPI document of every route under `/api`, generated from the handlers and the types they
+/// return. `openapi.json` at the root of this crate is a copy of it kept in sync by a test
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "vislog", description = "Programs and courses of the university catalog"),
+    nest(
+        (path = "/api/status", api = status::StatusApi),
+        (path = "/api/programs", api = programs::ProgramsApi),
+        (path = "/api/courses", api = courses::CoursesApi),
+        (path = "/api/subjects", api = subjects::SubjectsApi),
+        (path = "/api/diff", api = diff::DiffApi),
+        (path = "/api/admin", api = admin::AdminApi),
+        (path = "/api/snapshots", api = snapshots::SnapshotsApi),
+    ),
+    modifiers(&AdminTokenScheme)
+)]
+pub struct ApiDoc;
+
+/// The bearer token required by the admin routes
+struct AdminTokenScheme;
+
+impl Modify for AdminTokenScheme {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_token",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+}
+
+/// Serves [ApiDoc] at `/api/openapi.json` and a Swagger UI page for it at `/api/docs`
+pub fn docs() -> Router {
+    SwaggerUi::new("/api/docs")
+        .url("/api/openapi.json", ApiDoc::openapi())
+        .into()
+}
+
+pub fn routes(
+    programs_provider: ProgramsProvider,
+    courses_provider: CoursesProvider,
+    scheduler: Scheduler,
+) -> Router {
     Router::new()
-        .nest("/programs", programs::routes(programs_provider))
-        .nest("/courses", courses::routes(courses_provider))
+        .nest("/status", status::routes(scheduler))
+        .nest(
+            "/programs",
+            programs::routes(programs_provider.clone(), courses_provider.clone()),
+        )
+        .nest("/courses", courses::routes(courses_provider.clone()))
+        .nest("/subjects", subjects::routes(courses_provider.clone()))
+        .nest("/diff", diff::routes())
+        .nest(
+            "/admin",
+            admin::routes(
+                programs_provider,
+                courses_provider,
+                AdminAuth::from_config(CONFIGS.admin.as_ref()),
+            ),
+        )
+        .nest("/snapshots", snapshots::routes())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Fails when the routes or the types they return change without `openapi.json` being
+    /// regenerated with `UPDATE_OPENAPI=1 cargo test`
+    #[test]
+    fn committed_spec_matches_routes() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("openapi.json");
+        let spec = ApiDoc::openapi()
+            .to_pretty_json()
+            .expect("Should serialize spec")
+            + "\n";
+
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(&path, &spec).unwrap();
+            return;
+        }
+
+        let committed = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            committed == spec,
+            "{path:?} is out of date, regenerate it with `UPDATE_OPENAPI=1 cargo test`"
+        );
+    }
+
+    #[test]
+    fn spec_references_resolve() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+
+        assert!(!refs.is_empty());
+        for reference in refs {
+            let pointer = reference
+                .strip_prefix('#')
+                .expect("Should only have local references");
+            assert!(spec.pointer(pointer).is_some(), "Dangling {reference}");
+        }
+    }
+
+    #[test]
+    fn only_admin_routes_require_token() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        assert!(spec.get("security").is_none());
+        for (path, operations) in spec["paths"].as_object().unwrap() {
+            for (method, operation) in operations.as_object().unwrap() {
+                assert_eq!(
+                    operation.get("security").is_some(),
+                    path.starts_with("/api/admin/"),
+                    "{method} {path}"
+                );
+            }
+        }
+    }
+
+    fn collect_refs(value: &serde_json::Value, refs: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(reference)) = map.get("$ref") {
+                    refs.push(reference.clone());
+                }
+                map.values().for_each(|value| collect_refs(value, refs));
+            }
+            serde_json::Value::Array(values) => {
+                values.iter().for_each(|value| collect_refs(value, refs))
+            }
+            _ => {}
+        }
+    }
 }
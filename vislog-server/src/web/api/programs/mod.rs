@@ -1,32 +1,97 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{FromRef, State},
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{IntoResponse, Response},
     routing::get,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tracing::{debug, info, instrument};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use vislog_core::export::program_rows;
+use vislog_core::graph::{dot::to_dot, mermaid::to_mermaid, ProgramGraph};
 use vislog_core::parsing::guid::Guid;
+use vislog_core::plan::{pdf::to_pdf, Plan};
 use vislog_core::Program;
 
-use crate::web::error::{Error, Result};
+use vislog_parser::ParsingError;
 
-use crate::data::{fetching, providers::programs::ProgramsProvider};
+use crate::web::{
+    api::{export_response, with_parse_error_count, Cached, ExportParams, WithParseErrorCount},
+    error::{Error, ErrorBody, Result},
+    extract::{self, Path, Query},
+};
+
+use crate::data::{
+    maps,
+    providers::{courses::CoursesProvider, programs::ProgramsProvider},
+};
 
-pub fn routes(program_provider: ProgramsProvider) -> Router {
+/// The courses are only needed for the prerequisites drawn in program graphs
+#[derive(Clone, FromRef)]
+struct ProgramsState {
+    programs_provider: ProgramsProvider,
+    courses_provider: CoursesProvider,
+}
+
+pub fn routes(programs_provider: ProgramsProvider, courses_provider: CoursesProvider) -> Router {
     Router::new()
         .route("/", get(get_all_programs_handler))
         .route("/:guid", get(get_program_handler))
+        .route("/:guid/graph", get(get_program_graph_handler))
+        .route("/:guid/graph.json", get(get_program_graph_json_handler))
+        .route("/:guid/map.svg", get(get_program_map_handler))
+        .route("/:guid/export", get(export_program_handler))
+        .route(
+            "/:guid/plan.pdf",
+            get(get_program_plan_handler).post(post_program_plan_handler),
+        )
         .route("/titles", get(get_all_program_titles_handler))
-        .route("/refresh", get(refresh_all_programs_handler))
-        .with_state(program_provider)
+        .route("/errors", get(get_all_program_errors_handler))
+        .route("/json", get(get_program_json_handler))
+        .with_state(ProgramsState {
+            programs_provider,
+            courses_provider,
+        })
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    get_all_programs_handler,
+    get_program_handler,
+    get_program_graph_handler,
+    get_program_graph_json_handler,
+    get_program_map_handler,
+    export_program_handler,
+    get_program_plan_handler,
+    post_program_plan_handler,
+    get_all_program_titles_handler,
+    get_all_program_errors_handler,
+    get_program_json_handler,
+))]
+pub struct ProgramsApi;
+
+/// All programs that could be parsed, sorted by title
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "programs",
+    responses(
+        (status = 200, body = Vec<Program>, headers(
+            ("x-parse-error-count" = usize, description = "Number of programs that failed to parse")
+        )),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
 #[instrument(skip(programs_provider), err)]
 async fn get_all_programs_handler(
     State(programs_provider): State<ProgramsProvider>,
-) -> Result<Json<Vec<Program>>> {
+) -> Result<Cached<WithParseErrorCount<Vec<Program>>>> {
     info!("Getting all programs");
 
+    let version = programs_provider.cache_version().await?;
     let (programs, errors) = programs_provider.get_all_programs().await?;
 
     debug!(
@@ -35,44 +100,409 @@ async fn get_all_programs_handler(
         errors.len()
     );
 
-    Ok(Json(programs))
+    Ok(Cached(
+        version,
+        with_parse_error_count(errors.len(), programs),
+    ))
+}
+
+/// Errors of the programs that failed to parse
+#[utoipa::path(
+    get,
+    path = "/errors",
+    tag = "programs",
+    responses(
+        (status = 200, body = Vec<ParsingError>),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(programs_provider), err)]
+async fn get_all_program_errors_handler(
+    State(programs_provider): State<ProgramsProvider>,
+) -> Result<Cached<Json<Vec<ParsingError>>>> {
+    info!("Getting all program parsing errors");
+
+    let version = programs_provider.cache_version().await?;
+    let (_programs, errors) = programs_provider.get_all_programs().await?;
+
+    debug!("Error count: {}", errors.len());
+
+    Ok(Cached(version, Json(errors)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/{guid}",
+    tag = "programs",
+    params(("guid" = Guid, Path, description = "GUID of the program, without braces")),
+    responses(
+        (status = 200, body = Program),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 400, description = "Invalid GUID", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
 #[instrument(skip(programs_provider, guid), err)]
 async fn get_program_handler(
     State(programs_provider): State<ProgramsProvider>,
     Path(guid): Path<Guid>,
-) -> Result<Json<Program>> {
+) -> Result<Cached<Json<Program>>> {
     info!("Getting program with guid: {}", guid);
 
+    let version = programs_provider.cache_version().await?;
+    let program = programs_provider
+        .get_program(&guid)
+        .await?
+        .ok_or(Error::ProgramNotFound(guid))?;
+
+    Ok(Cached(version, Json(program)))
+}
+
+/// The requirements of a program as a CSV or XLSX file, one row per course or label with the
+/// module, requirement and operator group it's listed in
+#[utoipa::path(
+    get,
+    path = "/{guid}/export",
+    tag = "programs",
+    params(
+        ("guid" = Guid, Path, description = "GUID of the program, without braces"),
+        ExportParams,
+    ),
+    responses(
+        (status = 200, description = "CSV or XLSX file", content(
+            (String = "text/csv"),
+            (Vec<u8> = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+        )),
+        (status = 400, description = "Invalid GUID or format", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(programs_provider, guid), err)]
+async fn export_program_handler(
+    State(programs_provider): State<ProgramsProvider>,
+    Path(guid): Path<Guid>,
+    Query(ExportParams { format }): Query<ExportParams>,
+) -> Result<Response> {
+    info!("Exporting program with guid: {guid}");
+
     let program = programs_provider
         .get_program(&guid)
         .await?
         .ok_or(Error::ProgramNotFound(guid))?;
+    let rows = program_rows(&program);
+
+    debug!("Row count: {}", rows.len());
+
+    export_response(format.unwrap_or_default(), &guid.to_string(), &rows)
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct GraphParams {
+    /// Defaults to `dot`
+    #[param(inline)]
+    format: Option<GraphFormat>,
+}
+
+/// The requirements of a program and the prerequisites of its courses as a graph with a cluster
+/// per requirement module
+#[utoipa::path(
+    get,
+    path = "/{guid}/graph",
+    tag = "programs",
+    params(
+        ("guid" = Guid, Path, description = "GUID of the program, without braces"),
+        GraphParams,
+    ),
+    responses(
+        (status = 200, description = "Graphviz DOT or Mermaid flowchart", content(
+            (String = "text/vnd.graphviz"),
+            (String = "text/plain"),
+        )),
+        (status = 400, description = "Invalid GUID or format", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, guid), err)]
+async fn get_program_graph_handler(
+    State(state): State<ProgramsState>,
+    Path(guid): Path<Guid>,
+    Query(GraphParams { format }): Query<GraphParams>,
+) -> Result<impl IntoResponse> {
+    info!("Getting graph of program with guid: {guid}");
+
+    let graph = program_graph(&state, guid).await?;
+
+    debug!(
+        "Node count: {}, Edge count: {}",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+
+    Ok(match format.unwrap_or_default() {
+        GraphFormat::Dot => (
+            [(CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")],
+            to_dot(&graph),
+        ),
+        GraphFormat::Mermaid => (
+            [(CONTENT_TYPE, "text/plain; charset=utf-8")],
+            to_mermaid(&graph),
+        ),
+    })
+}
+
+/// The requirements of a program and the prerequisites of its courses as a flat list of nodes and
+/// edges, ready to be loaded by graph libraries like Cytoscape, D3 or React Flow
+#[utoipa::path(
+    get,
+    path = "/{guid}/graph.json",
+    tag = "programs",
+    params(("guid" = Guid, Path, description = "GUID of the program, without braces")),
+    responses(
+        (status = 200, body = ProgramGraph),
+        (status = 400, description = "Invalid GUID", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, guid), err)]
+async fn get_program_graph_json_handler(
+    State(state): State<ProgramsState>,
+    Path(guid): Path<Guid>,
+) -> Result<Json<ProgramGraph>> {
+    info!("Getting graph json of program with guid: {guid}");
 
-    Ok(Json(program))
+    let graph = program_graph(&state, guid).await?;
+
+    debug!(
+        "Node count: {}, Edge count: {}",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+
+    Ok(Json(graph))
 }
 
-#[derive(Debug, Deserialize)]
+/// The requirements of a program and the prerequisites of its courses laid out as an SVG image.
+/// Also pre-generated into the static assets as `maps/{guid}.svg` when the catalog is refreshed
+#[utoipa::path(
+    get,
+    path = "/{guid}/map.svg",
+    tag = "programs",
+    params(("guid" = Guid, Path, description = "GUID of the program, without braces")),
+    responses(
+        (status = 200, description = "SVG image", content_type = "image/svg+xml", body = String),
+        (status = 400, description = "Invalid GUID", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, guid), err)]
+async fn get_program_map_handler(
+    State(state): State<ProgramsState>,
+    Path(guid): Path<Guid>,
+) -> Result<impl IntoResponse> {
+    info!("Getting map of program with guid: {guid}");
+
+    let program = state
+        .programs_provider
+        .get_program(&guid)
+        .await?
+        .ok_or(Error::ProgramNotFound(guid))?;
+    let svg = maps::render_map(program, &state.courses_provider).await?;
+
+    debug!("Map size: {} bytes", svg.len());
+
+    Ok(([(CONTENT_TYPE, "image/svg+xml")], svg))
+}
+
+/// A printable PDF of the requirements of a program as a checklist
+#[utoipa::path(
+    get,
+    path = "/{guid}/plan.pdf",
+    tag = "programs",
+    params(("guid" = Guid, Path, description = "GUID of the program, without braces")),
+    responses(
+        (status = 200, description = "PDF document", content_type = "application/pdf"),
+        (status = 400, description = "Invalid GUID", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, guid), err)]
+async fn get_program_plan_handler(
+    State(state): State<ProgramsState>,
+    Path(guid): Path<Guid>,
+) -> Result<impl IntoResponse> {
+    info!("Getting plan of program with guid: {guid}");
+
+    program_plan_pdf(&state, guid, None).await
+}
+
+/// A printable PDF of the requirements of a program as a checklist, preceded by the courses
+/// planned for each term and their credit hours. Planned courses are ticked in the checklist
+#[utoipa::path(
+    post,
+    path = "/{guid}/plan.pdf",
+    tag = "programs",
+    params(("guid" = Guid, Path, description = "GUID of the program, without braces")),
+    request_body = Plan,
+    responses(
+        (status = 200, description = "PDF document", content_type = "application/pdf"),
+        (status = 400, description = "Invalid GUID or plan", body = ErrorBody),
+        (status = 404, description = "No program with the GUID", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(state, guid, plan), err)]
+async fn post_program_plan_handler(
+    State(state): State<ProgramsState>,
+    Path(guid): Path<Guid>,
+    extract::Json(plan): extract::Json<Plan>,
+) -> Result<impl IntoResponse> {
+    info!("Getting plan of program with guid: {guid}");
+
+    debug!("Term count: {}", plan.terms.len());
+
+    program_plan_pdf(&state, guid, Some(plan)).await
+}
+
+async fn program_plan_pdf(
+    state: &ProgramsState,
+    guid: Guid,
+    plan: Option<Plan>,
+) -> Result<impl IntoResponse> {
+    let program = state
+        .programs_provider
+        .get_program(&guid)
+        .await?
+        .ok_or(Error::ProgramNotFound(guid))?;
+
+    let planned = plan.iter().flat_map(Plan::courses);
+    let courses = state
+        .courses_provider
+        .get_courses_with_requisites(
+            program
+                .courses()
+                .into_iter()
+                .map(|course| &course.guid)
+                .chain(planned),
+        )
+        .await?;
+
+    let pdf =
+        tokio::task::spawn_blocking(move || to_pdf(&program, &courses, plan.as_ref())).await?;
+
+    debug!("PDF size: {} bytes", pdf.len());
+
+    Ok((
+        [
+            (CONTENT_TYPE, "application/pdf".to_owned()),
+            (
+                CONTENT_DISPOSITION,
+                format!("inline; filename=\"{guid}.pdf\""),
+            ),
+        ],
+        pdf,
+    ))
+}
+
+async fn program_graph(state: &ProgramsState, guid: Guid) -> Result<ProgramGraph> {
+    let program = state
+        .programs_provider
+        .get_program(&guid)
+        .await?
+        .ok_or(Error::ProgramNotFound(guid))?;
+
+    let courses = state
+        .courses_provider
+        .get_courses_with_requisites(program.courses().iter().map(|course| &course.guid))
+        .await?;
+
+    Ok(ProgramGraph::new(&program, &courses))
+}
+
+/// A program or course identified by its catalog URL, catalog path or GUID
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ItemIdParam {
+    /// Catalog URL, catalog path or GUID (with or without braces)
+    pub id: String,
+}
+
+/// The unparsed JSON of a single program as returned by the catalog API
+#[utoipa::path(
+    get,
+    path = "/json",
+    tag = "programs",
+    params(ItemIdParam),
+    responses(
+        (status = 200, body = Object),
+        (status = 404, description = "No program matches the id", body = ErrorBody),
+    )
+)]
+#[instrument(skip(programs_provider), err)]
+async fn get_program_json_handler(
+    Query(ItemIdParam { id }): Query<ItemIdParam>,
+    State(programs_provider): State<ProgramsProvider>,
+) -> Result<Json<Value>> {
+    info!("Getting program json with id: {id}");
+
+    let program_json = programs_provider.get_program_json(&id).await?;
+
+    Ok(Json(program_json))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct ProgramTitlesParam {
+    /// Return objects with both the GUID and title instead of only the titles
     with_guid: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(untagged)]
 enum ProgramTitlesResponse {
     WithGuid { guid: Guid, title: String },
     WithoutGuid(String),
 }
 
+/// Titles of all programs that could be parsed, sorted
+#[utoipa::path(
+    get,
+    path = "/titles",
+    tag = "programs",
+    params(ProgramTitlesParam),
+    responses(
+        (status = 200, body = Vec<ProgramTitlesResponse>, headers(
+            ("x-parse-error-count" = usize, description = "Number of programs that failed to parse")
+        )),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
 #[instrument(skip(programs_provider), err)]
 async fn get_all_program_titles_handler(
     Query(with_guid): Query<ProgramTitlesParam>,
     State(programs_provider): State<ProgramsProvider>,
-) -> Result<Json<Vec<ProgramTitlesResponse>>> {
+) -> Result<Cached<WithParseErrorCount<Vec<ProgramTitlesResponse>>>> {
     info!("Getting all program titles");
 
-    let (programs, _errors) = programs_provider.get_all_programs().await?;
+    let version = programs_provider.cache_version().await?;
+    let (programs, errors) = programs_provider.get_all_programs().await?;
     let with_guid = with_guid.with_guid.unwrap_or(false);
 
     let responses: Vec<ProgramTitlesResponse> = programs
@@ -91,18 +521,8 @@ async fn get_all_program_titles_handler(
 
     debug!("Title count: {}", responses.len());
 
-    Ok(Json(responses))
-}
-
-// TODO: Update state of ProgramsProvider after fetching the lastest data
-#[instrument(skip(programs_provider), err)]
-async fn refresh_all_programs_handler(
-    State(programs_provider): State<ProgramsProvider>,
-) -> Result<Json<Vec<Program>>> {
-    info!("Refreshing all programs");
-    let programs = fetching::fetch_all_programs(&programs_provider).await?;
-
-    debug!("Programs count after refresh: {}", programs.len());
-
-    Ok(Json(programs))
+    Ok(Cached(
+        version,
+        with_parse_error_count(errors.len(), responses),
+    ))
 }
@@ -0,0 +1,49 @@
+use axum::{routing::get, Json, Router};
+use serde::Serialize;
+use tracing::{debug, info, instrument};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::data::snapshots::{self, CatalogKind, SnapshotInfo};
+use crate::web::error::{ErrorBody, Result};
+use crate::CONFIGS;
+
+/// Rolling back to a snapshot is done through the admin routes
+pub fn routes() -> Router {
+    Router::new().route("/", get(get_all_snapshots_handler))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_all_snapshots_handler))]
+pub struct SnapshotsApi;
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SnapshotsResponse {
+    programs: Vec<SnapshotInfo>,
+    courses: Vec<SnapshotInfo>,
+}
+
+/// Snapshots of the catalog kept in the storage directory, newest first
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "snapshots",
+    responses(
+        (status = 200, body = SnapshotsResponse),
+        (status = 500, description = "Failed to read the snapshots", body = ErrorBody),
+    )
+)]
+#[instrument(err)]
+async fn get_all_snapshots_handler() -> Result<Json<SnapshotsResponse>> {
+    info!("Getting all snapshots");
+
+    let programs = snapshots::list(&CONFIGS.data, CatalogKind::Programs).await?;
+    let courses = snapshots::list(&CONFIGS.data, CatalogKind::Courses).await?;
+
+    debug!(
+        "Program snapshots: {}, Course snapshots: {}",
+        programs.len(),
+        courses.len()
+    );
+
+    Ok(Json(SnapshotsResponse { programs, courses }))
+}
@@ -0,0 +1,38 @@
+use axum::{extract::State, routing::get, Json, Router};
+use serde::Serialize;
+use tracing::{info, instrument};
+use utoipa::{OpenApi, ToSchema};
+
+use crate::data::scheduler::{Scheduler, SchedulerStatus};
+
+pub fn routes(scheduler: Scheduler) -> Router {
+    Router::new()
+        .route("/", get(get_status_handler))
+        .with_state(scheduler)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_status_handler))]
+pub struct StatusApi;
+
+#[derive(Debug, Serialize, ToSchema)]
+struct StatusResponse {
+    /// The scheduled background refresh of the catalog
+    refresh: SchedulerStatus,
+}
+
+/// Health of the background jobs of the server
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "status",
+    responses((status = 200, body = StatusResponse))
+)]
+#[instrument(skip(scheduler))]
+async fn get_status_handler(State(scheduler): State<Scheduler>) -> Json<StatusResponse> {
+    info!("Getting server status");
+
+    Json(StatusResponse {
+        refresh: scheduler.status().await,
+    })
+}
@@ -0,0 +1,80 @@
+use axum::{extract::State, routing::get, Json, Router};
+use tracing::{debug, info, instrument};
+use utoipa::OpenApi;
+use vislog_core::CourseDetails;
+
+use crate::{
+    data::providers::courses::{CoursesProvider, Subject},
+    web::{
+        api::Cached,
+        error::{Error, ErrorBody, Result},
+        extract::Path,
+    },
+};
+
+pub fn routes(courses_provider: CoursesProvider) -> Router {
+    Router::new()
+        .route("/", get(get_all_subjects_handler))
+        .route("/:code/courses", get(get_subject_courses_handler))
+        .with_state(courses_provider)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_all_subjects_handler, get_subject_courses_handler))]
+pub struct SubjectsApi;
+
+/// Every subject with at least one course that could be parsed, sorted by code
+#[utoipa::path(
+    get,
+    path = "",
+    tag = "subjects",
+    responses(
+        (status = 200, body = Vec<Subject>),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(courses_provider))]
+async fn get_all_subjects_handler(
+    State(courses_provider): State<CoursesProvider>,
+) -> Result<Cached<Json<Vec<Subject>>>> {
+    info!("Getting all subjects");
+
+    let version = courses_provider.cache_version().await?;
+    let subjects = courses_provider.get_subjects().await?;
+
+    debug!("subjects: {}", subjects.len());
+
+    Ok(Cached(version, Json(subjects)))
+}
+
+/// The courses of a subject sorted by number
+#[utoipa::path(
+    get,
+    path = "/{code}/courses",
+    tag = "subjects",
+    params(("code" = String, Path, description = "Subject code, ignoring case", example = "CSC")),
+    responses(
+        (status = 200, body = Vec<CourseDetails>),
+        (status = 304, description = "The client already has the latest version"),
+        (status = 404, description = "No course with the subject code", body = ErrorBody),
+        (status = 503, description = "The data is still being loaded", body = ErrorBody),
+    )
+)]
+#[instrument(skip(courses_provider))]
+async fn get_subject_courses_handler(
+    Path(code): Path<String>,
+    State(courses_provider): State<CoursesProvider>,
+) -> Result<Cached<Json<Vec<CourseDetails>>>> {
+    info!("Getting courses of subject: {code}");
+
+    let version = courses_provider.cache_version().await?;
+    let courses = courses_provider
+        .get_subject_courses(&code)
+        .await?
+        .ok_or(Error::SubjectNotFound(code))?;
+
+    debug!("courses: {}", courses.len());
+
+    Ok(Cached(version, Json(courses)))
+}
@@ -1,11 +1,25 @@
 use std::sync::Arc;
 
-use axum::response::IntoResponse;
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    response::IntoResponse,
+};
 use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::{json, Value};
 use thiserror::Error;
-use vislog_core::parsing::guid::Guid;
+use utoipa::ToSchema;
+use vislog_core::{export, parsing::guid::Guid};
+use vislog_parser::ParsingError;
 
-use crate::data::{fetching, providers};
+use crate::data::{
+    diffing, fetching, maps,
+    providers::{self, json_providers},
+    snapshots,
+};
+
+/// Seconds clients are told to wait before retrying while the cache is loading
+pub const CACHE_LOADING_RETRY_AFTER_SECS: u64 = 5;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -14,8 +28,38 @@ pub enum Error {
     ProgramsParsing(#[from] providers::programs::Error),
     CoursesParsing(#[from] providers::courses::Error),
     Fetching(#[from] fetching::error::Error),
+    Diffing(#[from] diffing::error::Error),
+    Snapshot(#[from] snapshots::error::Error),
+    Maps(#[from] maps::error::Error),
+    Export(#[from] export::Error),
+    /// Rendering a document on the blocking thread pool panicked or was cancelled
+    Render(#[from] tokio::task::JoinError),
     ProgramNotFound(Guid),
     CourseNotFound(Guid),
+    /// No course has the subject code and number
+    CourseCodeNotFound {
+        subject_code: String,
+        number: String,
+    },
+    /// Several courses have the subject code and number
+    AmbiguousCourseCode {
+        subject_code: String,
+        number: String,
+        guids: Vec<Guid>,
+    },
+    SubjectNotFound(String),
+    /// A path parameter couldn't be parsed (Ex: a GUID that isn't a valid GUID)
+    InvalidPath(#[from] PathRejection),
+    /// The query string couldn't be parsed
+    InvalidQuery(#[from] QueryRejection),
+    /// The JSON body is missing or couldn't be parsed
+    InvalidBody(#[from] JsonRejection),
+    /// No valid admin token was given
+    Unauthorized,
+    /// The client made too many admin requests
+    RateLimited {
+        retry_after_secs: u64,
+    },
 }
 
 impl std::fmt::Display for Error {
@@ -24,9 +68,12 @@ impl std::fmt::Display for Error {
     }
 }
 
+/// The status code is set here but the body is only written by
+/// [mw_map_error_response](super::middleware::errors::mw_map_error_response), since the request id
+/// is only known by the middleware
 impl IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
-        let mut response = StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        let mut response = self.client_error().status.into_response();
 
         response.extensions_mut().insert(Arc::new(self));
 
@@ -34,15 +81,258 @@ impl IntoResponse for Error {
     }
 }
 
-#[macro_export]
-macro_rules! impl_generic_error_and_display_for_error_type {
-    ($name:ident) => {
-        impl std::error::Error for $name {}
+/// What a client gets to know about an [Error]
+#[derive(Debug)]
+pub struct ClientError {
+    pub status: StatusCode,
+    /// Stable identifier of the kind of error for clients to match on (Ex: "PROGRAM_NOT_FOUND")
+    pub code: &'static str,
+    pub message: String,
+    pub details: Option<Value>,
+    /// How long clients should wait before trying again, sent as the `Retry-After` header
+    pub retry_after_secs: Option<u64>,
+}
+
+/// The JSON body of every error response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Stable identifier of the kind of error (Ex: "PROGRAM_NOT_FOUND")
+    #[schema(value_type = String, example = "PROGRAM_NOT_FOUND")]
+    pub code: &'static str,
+    pub message: String,
+    /// Id of the request, also sent as the `x-request-d` header
+    pub request_id: Option<String>,
+    /// Extra information about the error depending on its code
+    pub details: Option<Value>,
+}
+
+impl ClientError {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+            retry_after_secs: None,
+        }
+    }
+
+    fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    fn with_retry_after(mut self, secs: u64) -> Self {
+        self.retry_after_secs = Some(secs);
+        self
+    }
+
+    /// Internal errors are logged but their details are not exposed to clients
+    fn internal() -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            "Internal server error",
+        )
+    }
+
+    pub fn into_body(self, request_id: Option<String>) -> ErrorBody {
+        ErrorBody {
+            code: self.code,
+            message: self.message,
+            request_id,
+            details: self.details,
+        }
+    }
+}
 
-        impl std::fmt::Display for $name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "{self:?}")
+impl Error {
+    pub fn client_error(&self) -> ClientError {
+        match self {
+            Error::ProgramNotFound(guid) => ClientError::new(
+                StatusCode::NOT_FOUND,
+                "PROGRAM_NOT_FOUND",
+                format!("No program with GUID {guid}"),
+            ),
+            Error::CourseNotFound(guid) => ClientError::new(
+                StatusCode::NOT_FOUND,
+                "COURSE_NOT_FOUND",
+                format!("No course with GUID {guid}"),
+            ),
+            Error::CourseCodeNotFound {
+                subject_code,
+                number,
+            } => ClientError::new(
+                StatusCode::NOT_FOUND,
+                "COURSE_NOT_FOUND",
+                format!("No course {subject_code} {number}"),
+            ),
+            Error::AmbiguousCourseCode {
+                subject_code,
+                number,
+                guids,
+            } => ClientError::new(
+                StatusCode::CONFLICT,
+                "AMBIGUOUS_COURSE_CODE",
+                format!("Several courses are {subject_code} {number}, get one by its GUID"),
+            )
+            .with_details(json!({ "guids": guids })),
+            Error::SubjectNotFound(code) => ClientError::new(
+                StatusCode::NOT_FOUND,
+                "SUBJECT_NOT_FOUND",
+                format!("No course with subject code {code}"),
+            ),
+            Error::InvalidPath(rejection) => ClientError::new(
+                StatusCode::BAD_REQUEST,
+                "INVALID_PATH",
+                rejection.body_text(),
+            ),
+            Error::InvalidQuery(rejection) => ClientError::new(
+                StatusCode::BAD_REQUEST,
+                "INVALID_QUERY",
+                rejection.body_text(),
+            ),
+            Error::InvalidBody(rejection) => {
+                ClientError::new(rejection.status(), "INVALID_BODY", rejection.body_text())
             }
+            Error::Unauthorized => ClientError::new(
+                StatusCode::UNAUTHORIZED,
+                "UNAUTHORIZED",
+                "A valid admin token is required",
+            ),
+            Error::RateLimited { retry_after_secs } => ClientError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "RATE_LIMITED",
+                "Too many requests, try again later",
+            )
+            .with_retry_after(*retry_after_secs),
+            Error::ProgramsParsing(err) => programs_client_error(err),
+            Error::CoursesParsing(err) => courses_client_error(err),
+            Error::Fetching(err) => match err {
+                fetching::error::Error::Reqwest(_) => ClientError::new(
+                    StatusCode::BAD_GATEWAY,
+                    "UPSTREAM_FETCH_FAILED",
+                    "Failed to fetch the latest data from the catalog API",
+                ),
+                // The catalog API answered but with data that can't be served
+                fetching::error::Error::Snapshot(snapshots::error::Error::Invalid {
+                    id,
+                    errors,
+                }) => ClientError::new(
+                    StatusCode::BAD_GATEWAY,
+                    "UPSTREAM_DATA_INVALID",
+                    "The catalog API returned data that could not be parsed",
+                )
+                .with_details(json!({ "snapshot": id, "errors": errors })),
+                fetching::error::Error::Snapshot(err) => snapshot_client_error(err),
+                fetching::error::Error::ParsingProgram(err) => programs_client_error(err),
+                fetching::error::Error::ParsingCourse(err) => courses_client_error(err),
+                fetching::error::Error::Io(_) | fetching::error::Error::SerdeJson(_) => {
+                    ClientError::internal()
+                }
+            },
+            Error::Diffing(err) => match err {
+                diffing::error::Error::InvalidCatalog(catalog) => ClientError::new(
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_CATALOG",
+                    format!("'{catalog}' is not a catalog inside of the storage directory"),
+                ),
+                diffing::error::Error::JsonProvider(err) => json_provider_client_error(err),
+                diffing::error::Error::Join(_) => ClientError::internal(),
+            },
+            Error::Snapshot(err) => snapshot_client_error(err),
+            Error::Export(_) | Error::Render(_) => ClientError::internal(),
+            Error::Maps(err) => match err {
+                maps::error::Error::Programs(err) => programs_client_error(err),
+                maps::error::Error::Courses(err) => courses_client_error(err),
+                maps::error::Error::Io(_) | maps::error::Error::Render(_) => {
+                    ClientError::internal()
+                }
+            },
         }
-    };
+    }
+}
+
+fn programs_client_error(err: &providers::programs::Error) -> ClientError {
+    match err {
+        providers::programs::Error::JsonProvider(err) => json_provider_client_error(err),
+        providers::programs::Error::Parsing(errors) => parsing_client_error(errors),
+        providers::programs::Error::CacheLoading => cache_loading_client_error(),
+    }
+}
+
+fn courses_client_error(err: &providers::courses::Error) -> ClientError {
+    match err {
+        providers::courses::Error::JsonProvider(err) => json_provider_client_error(err),
+        providers::courses::Error::Parsing(errors) => parsing_client_error(errors),
+        providers::courses::Error::CacheLoading => cache_loading_client_error(),
+    }
+}
+
+fn parsing_client_error(errors: &[ParsingError]) -> ClientError {
+    ClientError::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "PARSING_FAILED",
+        "The item was found but could not be parsed",
+    )
+    .with_details(json!(errors))
+}
+
+fn cache_loading_client_error() -> ClientError {
+    ClientError::new(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "CACHE_LOADING",
+        "The data is still being loaded, try again shortly",
+    )
+    .with_retry_after(CACHE_LOADING_RETRY_AFTER_SECS)
+}
+
+fn json_provider_client_error(err: &json_providers::Error) -> ClientError {
+    match err {
+        json_providers::Error::NotFound(id) => ClientError::new(
+            StatusCode::NOT_FOUND,
+            "ITEM_NOT_FOUND",
+            format!("No item matches '{id}'"),
+        ),
+        json_providers::Error::FileNotFound(_) => ClientError::new(
+            StatusCode::NOT_FOUND,
+            "DATA_NOT_FOUND",
+            "The catalog data could not be found",
+        ),
+        json_providers::Error::Http(_) => ClientError::new(
+            StatusCode::BAD_GATEWAY,
+            "UPSTREAM_FETCH_FAILED",
+            "Failed to fetch the data from the catalog API",
+        ),
+        json_providers::Error::HttpStatus(status) => ClientError::new(
+            StatusCode::BAD_GATEWAY,
+            "UPSTREAM_FETCH_FAILED",
+            "The catalog API responded with an error",
+        )
+        .with_details(json!({ "upstream_status": status.as_u16() })),
+        _ => ClientError::internal(),
+    }
+}
+
+fn snapshot_client_error(err: &snapshots::error::Error) -> ClientError {
+    match err {
+        snapshots::error::Error::NotFound(id) => ClientError::new(
+            StatusCode::NOT_FOUND,
+            "SNAPSHOT_NOT_FOUND",
+            format!("No snapshot matches '{id}'"),
+        ),
+        snapshots::error::Error::ChecksumMismatch { id, .. } => ClientError::new(
+            StatusCode::CONFLICT,
+            "SNAPSHOT_CORRUPTED",
+            format!("Snapshot '{id}' no longer matches its checksum"),
+        ),
+        snapshots::error::Error::Invalid { id, errors } => ClientError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "SNAPSHOT_INVALID",
+            format!("None of the items in snapshot '{id}' could be parsed"),
+        )
+        .with_details(json!({ "snapshot": id, "errors": errors })),
+        snapshots::error::Error::JsonProvider(err) => json_provider_client_error(err),
+        snapshots::error::Error::Io(_) => ClientError::internal(),
+    }
 }
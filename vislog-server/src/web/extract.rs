@@ -0,0 +1,20 @@
+//! Extractors that reject bad requests with the same JSON error body as every other error
+
+use axum::extract::{FromRequest, FromRequestParts};
+
+use super::error::Error;
+
+/// Same as [axum::extract::Path] but rejects with [Error::InvalidPath]
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(Error))]
+pub struct Path<T>(pub T);
+
+/// Same as [axum::extract::Query] but rejects with [Error::InvalidQuery]
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(Error))]
+pub struct Query<T>(pub T);
+
+/// Same as [axum::Json] but rejects with [Error::InvalidBody]
+#[derive(Debug, FromRequest)]
+#[from_request(via(axum::Json), rejection(Error))]
+pub struct Json<T>(pub T);
@@ -0,0 +1,384 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, OriginalUri, Request, State},
+    http::{header::AUTHORIZATION, Response},
+    middleware::Next,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    configs,
+    web::{error::Error, REQUEST_ID_HEADER},
+};
+
+const DEFAULT_RATE_LIMIT: u32 = 30;
+const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
+
+/// Everything needed to guard the admin API
+#[derive(Debug, Clone)]
+pub struct AdminAuth {
+    tokens: Arc<Vec<TokenHash>>,
+    rate_limiter: RateLimiter,
+    audit_log: AuditLog,
+}
+
+#[derive(Debug)]
+struct TokenHash {
+    name: String,
+    sha256: [u8; 32],
+}
+
+/// Who made an admin request, available to admin handlers as an extension
+#[derive(Debug, Clone)]
+pub struct AdminIdentity {
+    pub name: String,
+}
+
+impl AdminAuth {
+    /// Tokens with an invalid hash are skipped. No requests are allowed if `config` is `None`
+    pub fn from_config(config: Option<&configs::Admin>) -> Self {
+        let Some(config) = config else {
+            return Self::new(
+                Vec::new(),
+                DEFAULT_RATE_LIMIT,
+                DEFAULT_RATE_LIMIT_WINDOW_SECS,
+                None,
+            );
+        };
+
+        let tokens = config
+            .tokens
+            .iter()
+            .filter_map(|token| match parse_sha256(&token.sha256) {
+                Some(sha256) => Some((token.name.clone(), sha256)),
+                None => {
+                    warn!("Skipping admin token '{}' with invalid sha256", token.name);
+                    None
+                }
+            })
+            .collect();
+
+        Self::new(
+            tokens,
+            config.rate_limit.unwrap_or(DEFAULT_RATE_LIMIT),
+            config
+                .rate_limit_window_secs
+                .unwrap_or(DEFAULT_RATE_LIMIT_WINDOW_SECS),
+            config.audit_log.clone(),
+        )
+    }
+
+    fn new(
+        tokens: Vec<(String, [u8; 32])>,
+        rate_limit: u32,
+        rate_limit_window_secs: u64,
+        audit_log: Option<PathBuf>,
+    ) -> Self {
+        let tokens = tokens
+            .into_iter()
+            .map(|(name, sha256)| TokenHash { name, sha256 })
+            .collect();
+
+        Self {
+            tokens: Arc::new(tokens),
+            rate_limiter: RateLimiter::new(rate_limit, Duration::from_secs(rate_limit_window_secs)),
+            audit_log: AuditLog { path: audit_log },
+        }
+    }
+
+    /// Finds the owner of the bearer `token`
+    fn authenticate(&self, token: &str) -> Option<AdminIdentity> {
+        let sha256: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+
+        // Check every token so that the time taken doesn't tell which one matched
+        self.tokens
+            .iter()
+            .fold(None, |found, token| {
+                if constant_time_eq(&token.sha256, &sha256) {
+                    Some(token)
+                } else {
+                    found
+                }
+            })
+            .map(|token| AdminIdentity {
+                name: token.name.clone(),
+            })
+    }
+}
+
+/// Allows each client a fixed number of requests per window
+#[derive(Debug, Clone)]
+struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    windows: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+}
+
+impl RateLimiter {
+    fn new(max_requests: u32, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            windows: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Counts a request from `client` and returns how long it has to wait if it's over the limit
+    fn check(&self, client: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("Should not be poisoned");
+
+        // Forget clients whose window is over so the map doesn't grow forever
+        windows.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+
+        let (start, count) = windows.entry(client.to_owned()).or_insert((now, 0));
+        if *count >= self.max_requests {
+            return Err(self.window - now.duration_since(*start));
+        }
+        *count += 1;
+
+        Ok(())
+    }
+}
+
+/// One line of the audit log
+#[derive(Debug, Serialize)]
+struct AuditEntry<'a> {
+    timestamp: DateTime<Utc>,
+    request_id: Option<&'a str>,
+    client: &'a str,
+    /// Name of the token used, `None` if the request wasn't authenticated
+    token: Option<&'a str>,
+    method: &'a str,
+    uri: &'a str,
+    status: u16,
+}
+
+#[derive(Debug, Clone)]
+struct AuditLog {
+    path: Option<PathBuf>,
+}
+
+impl AuditLog {
+    async fn record(&self, entry: AuditEntry<'_>) {
+        info!(
+            request_id = entry.request_id,
+            client = entry.client,
+            token = entry.token,
+            status = entry.status,
+            "Admin request: {} {}",
+            entry.method,
+            entry.uri
+        );
+
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if let Err(err) = append_line(path, &entry).await {
+            error!("Failed to write to audit log {path:?}: {err}");
+        }
+    }
+}
+
+async fn append_line(path: &PathBuf, entry: &AuditEntry<'_>) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+
+    file.write_all(&line).await?;
+    // Tokio files write in the background, make sure the line is written before moving on
+    file.flush().await
+}
+
+/// Rate limits admin requests by client address, rejects the ones without a valid bearer token
+/// and records all of them in the audit log
+#[instrument(skip_all)]
+pub async fn mw_require_admin_token(
+    State(auth): State<AdminAuth>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    // The full URI since the admin routes are nested
+    OriginalUri(uri): OriginalUri,
+    mut req: Request,
+    next: Next,
+) -> Response<Body> {
+    let client = connect_info
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_owned());
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .map(ToOwned::to_owned);
+    let (method, uri) = (req.method().to_string(), uri.to_string());
+
+    let identity = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| auth.authenticate(token.trim()));
+
+    let res = match (auth.rate_limiter.check(&client), &identity) {
+        (Err(retry_after), _) => Error::RateLimited {
+            retry_after_secs: retry_after.as_secs().max(1),
+        }
+        .into_response(),
+        (Ok(()), None) => Error::Unauthorized.into_response(),
+        (Ok(()), Some(identity)) => {
+            req.extensions_mut().insert(identity.clone());
+            next.run(req).await
+        }
+    };
+
+    auth.audit_log
+        .record(AuditEntry {
+            timestamp: Utc::now(),
+            request_id: request_id.as_deref(),
+            client: &client,
+            token: identity.as_ref().map(|identity| identity.name.as_str()),
+            method: &method,
+            uri: &uri,
+            status: res.status().as_u16(),
+        })
+        .await;
+
+    res
+}
+
+fn parse_sha256(hex: &str) -> Option<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut sha256 = [0; 32];
+    for (i, byte) in sha256.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(sha256)
+}
+
+fn constant_time_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        extract::connect_info::MockConnectInfo, http::StatusCode, middleware::from_fn_with_state,
+        routing::post, Extension, Router,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const TOKEN: &str = "secret-token";
+
+    fn auth(rate_limit: u32, audit_log: Option<PathBuf>) -> AdminAuth {
+        let sha256 = Sha256::digest(TOKEN.as_bytes()).into();
+        AdminAuth::new(vec![("ci".to_owned(), sha256)], rate_limit, 60, audit_log)
+    }
+
+    async fn whoami_handler(Extension(identity): Extension<AdminIdentity>) -> String {
+        identity.name
+    }
+
+    async fn request(auth: &AdminAuth, token: Option<&str>) -> (StatusCode, String) {
+        let app = Router::new()
+            .route("/whoami", post(whoami_handler))
+            .layer(from_fn_with_state(auth.clone(), mw_require_admin_token))
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 1234))));
+
+        let mut req = Request::post("/whoami");
+        if let Some(token) = token {
+            req = req.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn parses_hex_sha256() {
+        let hex = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+        assert_eq!(parse_sha256(hex), Some(Sha256::digest(b"").into()));
+        assert_eq!(parse_sha256(&hex[1..]), None);
+        assert_eq!(parse_sha256(&hex.replace('e', "g")), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_without_valid_token() {
+        let auth = auth(10, None);
+
+        assert_eq!(request(&auth, None).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            request(&auth, Some("nope")).await.0,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            request(&auth, Some(TOKEN)).await,
+            (StatusCode::OK, "ci".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limits_clients() {
+        let auth = auth(2, None);
+
+        assert_eq!(request(&auth, Some(TOKEN)).await.0, StatusCode::OK);
+        assert_eq!(request(&auth, None).await.0, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            request(&auth, Some(TOKEN)).await.0,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn records_requests_in_audit_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let auth = auth(10, Some(path.clone()));
+
+        request(&auth, Some(TOKEN)).await;
+        request(&auth, Some("nope")).await;
+
+        let log = std::fs::read_to_string(path).unwrap();
+        let entries: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["token"], "ci");
+        assert_eq!(entries[0]["uri"], "/whoami");
+        assert_eq!(entries[0]["client"], "127.0.0.1");
+        assert_eq!(entries[1]["token"], serde_json::Value::Null);
+        assert_eq!(entries[1]["status"], 401);
+    }
+}
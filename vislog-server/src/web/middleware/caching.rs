@@ -0,0 +1,315 @@
+use axum::{
+    body::Body,
+    http::{
+        header::{
+            InvalidHeaderValue, CACHE_CONTROL, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+            LAST_MODIFIED,
+        },
+        HeaderMap, HeaderValue, Method, Response, StatusCode, Uri,
+    },
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tracing::{debug, instrument, warn};
+
+use crate::{configs, data::providers::CacheVersion, CONFIGS};
+
+const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+/// Format of the `Last-Modified` and `If-Modified-Since` headers (Ex: "Sun, 06 Nov 1994 08:49:37 GMT")
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Sets the `ETag`, `Last-Modified` and `Cache-Control` headers of responses built from cached data
+/// (see [Cached](crate::web::api::Cached)) and replaces them with a `304 Not Modified` if the
+/// client already has the same version.
+///
+/// Other successful GET responses only get the `Cache-Control` configured for their route, if
+/// any.
+#[instrument(skip_all)]
+pub async fn mw_conditional_get(
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    res: Response<Body>,
+) -> Response<Body> {
+    conditional_get(CONFIGS.http_cache.as_ref(), &method, &uri, &headers, res)
+}
+
+fn conditional_get(
+    config: Option<&configs::HttpCache>,
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    mut res: Response<Body>,
+) -> Response<Body> {
+    if !(method == Method::GET || method == Method::HEAD) || !res.status().is_success() {
+        return res;
+    }
+
+    let version = res.extensions().get::<CacheVersion>().copied();
+    let Some(cache_control) = cache_control(config, uri.path(), version.is_some()) else {
+        return res;
+    };
+
+    let Some(version) = version else {
+        res.headers_mut().insert(CACHE_CONTROL, cache_control);
+        return res;
+    };
+
+    let (etag, last_modified) = match validators(&version, uri) {
+        Ok(validators) => validators,
+        Err(err) => {
+            warn!("Failed to build the validators of {uri}: {err}");
+            res.headers_mut().insert(CACHE_CONTROL, cache_control);
+            return res;
+        }
+    };
+
+    if is_not_modified(headers, &etag, version.modified_at) {
+        debug!("Not modified: {uri}");
+        return (
+            StatusCode::NOT_MODIFIED,
+            [
+                (ETAG, etag),
+                (LAST_MODIFIED, last_modified),
+                (CACHE_CONTROL, cache_control),
+            ],
+        )
+            .into_response();
+    }
+
+    let response_headers = res.headers_mut();
+    response_headers.insert(ETAG, etag);
+    response_headers.insert(LAST_MODIFIED, last_modified);
+    response_headers.insert(CACHE_CONTROL, cache_control);
+
+    res
+}
+
+/// The `Cache-Control` of the longest configured route prefix of `path`, falling back to the
+/// default one for responses built from cached data
+fn cache_control(
+    config: Option<&configs::HttpCache>,
+    path: &str,
+    is_cached: bool,
+) -> Option<HeaderValue> {
+    let route = config
+        .and_then(|config| config.routes.as_ref())
+        .and_then(|routes| {
+            routes
+                .iter()
+                .filter(|(prefix, _)| matches_prefix(path, prefix))
+                .max_by_key(|(prefix, _)| prefix.len())
+        })
+        .map(|(_, cache_control)| cache_control.header_value().clone());
+
+    if route.is_some() || !is_cached {
+        return route;
+    }
+
+    Some(
+        config
+            .and_then(|config| config.cache_control.as_ref())
+            .map_or_else(
+                || HeaderValue::from_static(DEFAULT_CACHE_CONTROL),
+                |cache_control| cache_control.header_value().clone(),
+            ),
+    )
+}
+
+/// Whether `prefix` is `path` or one of its parent paths
+fn matches_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The `ETag` and `Last-Modified` of the response to `uri` built from the cache at `version`.
+///
+/// Different URIs get different ETags since they are different representations of the same
+/// cache. The ETag is weak since the body still goes through the compression layer, so the bytes
+/// sent for the same tag depend on the `Accept-Encoding` of the request.
+fn validators(
+    version: &CacheVersion,
+    uri: &Uri,
+) -> Result<(HeaderValue, HeaderValue), InvalidHeaderValue> {
+    let mut hasher = Sha256::new();
+    hasher.update(version.hash);
+    hasher.update(uri.path_and_query().map_or("", |p| p.as_str()));
+
+    let hash = hasher.finalize();
+    let hex: String = hash[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    let etag = HeaderValue::try_from(format!("W/\"{hex}\""))?;
+    let last_modified =
+        HeaderValue::try_from(version.modified_at.format(HTTP_DATE_FORMAT).to_string())?;
+
+    Ok((etag, last_modified))
+}
+
+/// `If-None-Match` takes precedence over `If-Modified-Since` when both are given. ETags are
+/// compared with the weak comparison, ignoring their `W/` prefix
+fn is_not_modified(headers: &HeaderMap, etag: &HeaderValue, modified_at: DateTime<Utc>) -> bool {
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    let etag = etag.trim_start_matches("W/");
+
+    if let Some(if_none_match) = headers.get(IF_NONE_MATCH) {
+        return if_none_match.to_str().is_ok_and(|tags| {
+            tags.split(',')
+                .map(|tag| tag.trim())
+                .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+        });
+    }
+
+    headers
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|since| since.to_str().ok())
+        .and_then(|since| DateTime::parse_from_rfc2822(since).ok())
+        // HTTP dates only have a precision of seconds
+        .is_some_and(|since| modified_at.timestamp() <= since.timestamp())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn config() -> configs::HttpCache {
+        configs::HttpCache {
+            cache_control: Some("no-cache".to_owned().try_into().unwrap()),
+            routes: Some(HashMap::from([
+                ("/api".to_owned(), "no-store".to_owned().try_into().unwrap()),
+                (
+                    "/api/programs".to_owned(),
+                    "max-age=60".to_owned().try_into().unwrap(),
+                ),
+            ])),
+        }
+    }
+
+    fn version() -> CacheVersion {
+        CacheVersion {
+            hash: [7; 32],
+            modified_at: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+        }
+    }
+
+    fn request(
+        uri: &str,
+        headers: &[(&str, &str)],
+        version: Option<CacheVersion>,
+    ) -> Response<Body> {
+        let mut res = "[]".into_response();
+        if let Some(version) = version {
+            res.extensions_mut().insert(version);
+        }
+
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect();
+
+        conditional_get(
+            Some(&config()),
+            &Method::GET,
+            &uri.parse().unwrap(),
+            &headers,
+            res,
+        )
+    }
+
+    #[test]
+    fn sets_validators_on_cached_responses() {
+        let res = request("/api/programs", &[], Some(version()));
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()[LAST_MODIFIED],
+            "Wed, 01 May 2024 12:00:00 GMT"
+        );
+        assert_eq!(res.headers()[CACHE_CONTROL], "max-age=60");
+        assert!(res.headers()[ETAG].to_str().unwrap().starts_with("W/\""));
+
+        let other = request("/api/programs/titles", &[], Some(version()));
+        assert_ne!(res.headers()[ETAG], other.headers()[ETAG]);
+    }
+
+    #[test]
+    fn answers_not_modified_for_matching_etag() {
+        let etag = request("/api/courses", &[], Some(version())).headers()[ETAG].clone();
+        let etag = etag.to_str().unwrap();
+
+        let res = request(
+            "/api/courses",
+            &[("if-none-match", &format!("\"other\", {etag}"))],
+            Some(version()),
+        );
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()[ETAG], etag);
+        assert_eq!(res.headers()[CACHE_CONTROL], "no-store");
+
+        let strong = etag.trim_start_matches("W/");
+        let res = request(
+            "/api/courses",
+            &[("if-none-match", strong)],
+            Some(version()),
+        );
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let changed = CacheVersion {
+            hash: [8; 32],
+            ..version()
+        };
+        let res = request("/api/courses", &[("if-none-match", etag)], Some(changed));
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn answers_not_modified_since_last_modified() {
+        let res = request(
+            "/api/programs",
+            &[("if-modified-since", "Wed, 01 May 2024 12:00:00 GMT")],
+            Some(version()),
+        );
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let res = request(
+            "/api/programs",
+            &[("if-modified-since", "Wed, 01 May 2024 11:59:59 GMT")],
+            Some(version()),
+        );
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn only_sets_configured_cache_control_on_other_responses() {
+        let res = request("/api/diff", &[], None);
+        assert_eq!(res.headers()[CACHE_CONTROL], "no-store");
+        assert!(res.headers().get(ETAG).is_none());
+
+        let res = request("/check_health", &[], None);
+        assert!(res.headers().get(CACHE_CONTROL).is_none());
+
+        let res = request("/apis", &[], Some(version()));
+        assert_eq!(res.headers()[CACHE_CONTROL], "no-cache");
+    }
+
+    #[test]
+    fn rejects_invalid_cache_control_in_config() {
+        let config = serde_json::json!({ "routes": { "/api": "no-store\nX-Injected: 1" } });
+        assert!(serde_json::from_value::<configs::HttpCache>(config).is_err());
+
+        let config = serde_json::json!({ "cache_control": "max-age=60" });
+        let config = serde_json::from_value::<configs::HttpCache>(config).unwrap();
+        assert_eq!(config.cache_control.unwrap().header_value(), "max-age=60");
+    }
+}
@@ -1,28 +1,282 @@
-use axum::{body::Body, http::Response};
-use tracing::{debug, instrument};
-
-use crate::CONFIGS;
-
-#[instrument(skip(res))]
-pub async fn mw_set_access_control_allow_origin(mut res: Response<Body>) -> Response<Body> {
-    let cors_header_key = "Access-Control-Allow-Origin";
-
-    if let Some(cors) = &CONFIGS.cors {
-        if cors.origins.len() >= 1 {
-            res.headers_mut().insert(
-                cors_header_key,
-                cors.origins_to_string()
-                    .parse()
-                    .expect("Should be valid header value"),
-            );
+use std::time::Duration;
+
+use axum::http::{request::Parts, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{debug, warn};
+
+use crate::{
+    configs,
+    web::{api::PARSE_ERROR_COUNT_HEADER, REQUEST_ID_HEADER},
+};
+
+const DEFAULT_METHODS: [Method; 3] = [Method::GET, Method::POST, Method::OPTIONS];
+const DEFAULT_HEADERS: [&str; 2] = ["authorization", "content-type"];
+const DEFAULT_MAX_AGE_SECS: u64 = 600;
+
+/// Builds the layer answering preflight requests and setting the CORS headers of every response.
+///
+/// The origin of a request is echoed back in `Access-Control-Allow-Origin` if it matches one of
+/// the configured origins (see [origin_matches]), and no CORS headers are set otherwise. Cross
+/// origin requests are rejected by browsers if `config` is `None` or has no origins.
+///
+/// Credentials are never allowed along with the `*` origin, that would let every site make
+/// requests with the credentials of the user.
+pub fn cors_layer(config: Option<&configs::Cors>) -> CorsLayer {
+    let Some(config) = config.filter(|config| !config.origins.is_empty()) else {
+        return CorsLayer::new();
+    };
+
+    let allows_any_origin = config
+        .origins
+        .iter()
+        .any(|pattern| pattern.trim_end_matches('/') == "*");
+    let allow_credentials = match config.allow_credentials.unwrap_or(false) {
+        true if allows_any_origin => {
+            warn!("Ignoring cors allow_credentials since every origin is allowed with '*'");
+            false
         }
+        allow_credentials => allow_credentials,
+    };
+
+    let origins = config.origins.clone();
+    let allow_origin = AllowOrigin::predicate(move |origin: &HeaderValue, _: &Parts| {
+        let allowed = origin.to_str().is_ok_and(|origin| {
+            origins
+                .iter()
+                .any(|pattern| origin_matches(pattern, origin))
+        });
+        debug!("Cors origin {origin:?} allowed: {allowed}");
+
+        allowed
+    });
+
+    let methods = match &config.methods {
+        Some(methods) => methods
+            .iter()
+            .filter_map(|method| match method.to_uppercase().parse::<Method>() {
+                Ok(method) => Some(method),
+                Err(_) => {
+                    warn!("Skipping invalid cors method '{method}'");
+                    None
+                }
+            })
+            .collect(),
+        None => DEFAULT_METHODS.to_vec(),
+    };
+
+    let headers = config
+        .headers
+        .as_ref()
+        .map(|headers| headers.iter().map(String::as_str).collect())
+        .unwrap_or_else(|| DEFAULT_HEADERS.to_vec())
+        .into_iter()
+        .filter_map(|header| match HeaderName::try_from(header) {
+            Ok(header) => Some(header),
+            Err(_) => {
+                warn!("Skipping invalid cors header '{header}'");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(allow_credentials)
+        .max_age(Duration::from_secs(
+            config.max_age_secs.unwrap_or(DEFAULT_MAX_AGE_SECS),
+        ))
+        // Let the frontend read the custom headers of cross origin responses
+        .expose_headers([
+            HeaderName::from_static(PARSE_ERROR_COUNT_HEADER),
+            HeaderName::from_static(REQUEST_ID_HEADER),
+        ])
+}
+
+/// Whether `origin` is allowed by `pattern`.
+///
+/// - `*` allows every origin
+/// - `https://*.example.com` allows every subdomain of `example.com` over https, but not
+///   `example.com` itself
+/// - Anything else has to match the origin exactly, ignoring case
+pub fn origin_matches(pattern: &str, origin: &str) -> bool {
+    let (pattern, origin) = (pattern.to_lowercase(), origin.to_lowercase());
+    let pattern = pattern.trim_end_matches('/');
+
+    if pattern == "*" {
+        return true;
+    }
+
+    let Some((scheme, host)) = pattern.split_once("://*.") else {
+        return pattern == origin;
+    };
+
+    origin
+        .strip_prefix(scheme)
+        .and_then(|origin| origin.strip_prefix("://"))
+        .and_then(|origin| origin.strip_suffix(host))
+        .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.'))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[test]
+    fn matches_exact_and_wildcard_origins() {
+        assert!(origin_matches("*", "https://anything.com"));
+        assert!(origin_matches(
+            "https://Example.com/",
+            "https://example.com"
+        ));
+        assert!(!origin_matches("https://example.com", "http://example.com"));
 
-        debug!(
-            "Cors header: \"{}: {:?}\"",
-            cors_header_key,
-            res.headers().get("Access-Control-Allow-Origin")
+        assert!(origin_matches(
+            "https://*.example.com",
+            "https://app.example.com"
+        ));
+        assert!(origin_matches(
+            "https://*.example.com",
+            "https://a.b.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://evilexample.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "http://app.example.com"
+        ));
+        assert!(!origin_matches(
+            "https://*.example.com",
+            "https://app.example.com.evil.com"
+        ));
+    }
+
+    fn app_allowing(origins: &[&str]) -> Router {
+        let config = configs::Cors {
+            origins: origins.iter().map(|origin| origin.to_string()).collect(),
+            methods: None,
+            headers: None,
+            max_age_secs: Some(60),
+            allow_credentials: Some(true),
+        };
+
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(cors_layer(Some(&config)))
+    }
+
+    fn app() -> Router {
+        app_allowing(&["https://vislog.example.com", "http://*.localhost:5173"])
+    }
+
+    #[tokio::test]
+    async fn echoes_matching_origin() {
+        let res = app()
+            .oneshot(
+                Request::get("/")
+                    .header(header::ORIGIN, "http://app.localhost:5173")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let headers = res.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "http://app.localhost:5173"
         );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert!(headers
+            .get_all(header::VARY)
+            .iter()
+            .any(|vary| vary.to_str().unwrap().contains("origin")));
     }
 
-    res
+    #[tokio::test]
+    async fn ignores_other_origins() {
+        let res = app()
+            .oneshot(
+                Request::get("/")
+                    .header(header::ORIGIN, "https://evil.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn answers_preflight_requests() {
+        let res = app()
+            .oneshot(
+                Request::options("/")
+                    .header(header::ORIGIN, "https://vislog.example.com")
+                    .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                    .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let headers = res.headers();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://vislog.example.com"
+        );
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap()
+            .contains("POST"));
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+            "authorization,content-type"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "60");
+    }
+
+    #[tokio::test]
+    async fn never_allows_credentials_for_every_origin() {
+        let res = app_allowing(&["https://vislog.example.com", "*"])
+            .oneshot(
+                Request::get("/")
+                    .header(header::ORIGIN, "https://evil.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let headers = res.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://evil.com"
+        );
+        assert!(headers
+            .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .is_none());
+    }
 }
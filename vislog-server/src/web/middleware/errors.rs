@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{
+        header::{RETRY_AFTER, WWW_AUTHENTICATE},
+        HeaderMap, HeaderValue, Response, StatusCode,
+    },
+    response::IntoResponse,
+    Json,
+};
+use tracing::{error, instrument, warn};
+
+use crate::web::{error::Error, REQUEST_ID_HEADER};
+
+/// Replaces the body of responses created from an [Error] with an
+/// [ErrorBody](crate::web::error::ErrorBody) and logs the error along with the id of the request
+#[instrument(skip_all)]
+pub async fn mw_map_error_response(headers: HeaderMap, res: Response<Body>) -> Response<Body> {
+    let Some(err) = res.extensions().get::<Arc<Error>>().cloned() else {
+        return res;
+    };
+
+    let request_id = headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|id| id.to_str().ok())
+        .map(ToOwned::to_owned);
+    let client_error = err.client_error();
+    let status = client_error.status;
+    let retry_after_secs = client_error.retry_after_secs;
+
+    if status.is_server_error() {
+        error!(
+            request_id,
+            status = status.as_u16(),
+            code = client_error.code,
+            "{err}"
+        );
+    } else {
+        warn!(
+            request_id,
+            status = status.as_u16(),
+            code = client_error.code,
+            "{err}"
+        );
+    }
+
+    let mut mapped = (status, Json(client_error.into_body(request_id))).into_response();
+    if let Some(secs) = retry_after_secs {
+        mapped
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(secs));
+    }
+    if status == StatusCode::UNAUTHORIZED {
+        mapped
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+
+    mapped
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::to_bytes, http::Request, middleware::map_response, routing::get, Router};
+    use serde_json::Value;
+    use tower::ServiceExt;
+    use vislog_core::parsing::guid::Guid;
+
+    use crate::{
+        data::providers::programs,
+        web::{
+            error::{Result, CACHE_LOADING_RETRY_AFTER_SECS},
+            extract::Path,
+        },
+    };
+
+    use super::*;
+
+    async fn get_program_handler(Path(guid): Path<Guid>) -> Result<()> {
+        Err(Error::ProgramNotFound(guid))
+    }
+
+    async fn loading_handler() -> Result<()> {
+        Err(programs::Error::CacheLoading.into())
+    }
+
+    async fn request(uri: &str) -> (StatusCode, HeaderMap, Value) {
+        let app = Router::new()
+            .route("/programs/:guid", get(get_program_handler))
+            .route("/loading", get(loading_handler))
+            .layer(map_response(mw_map_error_response));
+        let request = Request::get(uri)
+            .header(REQUEST_ID_HEADER, "42")
+            .body(Body::empty())
+            .unwrap();
+
+        let res = app.oneshot(request).await.unwrap();
+        let (status, headers) = (res.status(), res.headers().clone());
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+
+        (status, headers, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn maps_not_found_errors_to_json_body() {
+        let (status, _, body) = request("/programs/5B72AC3A-9A84-4CF5-B1BE-B3E0B48163A5").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "PROGRAM_NOT_FOUND");
+        assert_eq!(body["request_id"], "42");
+        assert!(body["message"].as_str().unwrap().contains("5B72AC3A"));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_guids_with_bad_request() {
+        let (status, _, body) = request("/programs/not-a-guid").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_PATH");
+    }
+
+    #[tokio::test]
+    async fn asks_clients_to_retry_while_cache_is_loading() {
+        let (status, headers, body) = request("/loading").await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            headers[RETRY_AFTER],
+            CACHE_LOADING_RETRY_AFTER_SECS.to_string()
+        );
+        assert_eq!(body["code"], "CACHE_LOADING");
+    }
+}
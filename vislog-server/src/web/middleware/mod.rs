@@ -1 +1,4 @@
+pub mod admin;
+pub mod caching;
 pub mod cors;
+pub mod errors;
@@ -18,13 +18,20 @@ use axum::{
 };
 use tower::ServiceBuilder;
 use tower_http::{
+    compression::CompressionLayer,
     request_id::{MakeRequestId, PropagateRequestIdLayer, RequestId, SetRequestIdLayer},
     services::ServeDir,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
 };
 use tracing::{info, instrument};
 
-use crate::data::providers::{courses::CoursesProvider, programs::ProgramsProvider};
+use crate::{
+    data::{
+        providers::{courses::CoursesProvider, programs::ProgramsProvider},
+        scheduler::Scheduler,
+    },
+    CONFIGS,
+};
 
 #[instrument(skip(addr))]
 async fn check_health_handler(ConnectInfo(addr): ConnectInfo<SocketAddr>) -> Response<Body> {
@@ -32,8 +39,12 @@ async fn check_health_handler(ConnectInfo(addr): ConnectInfo<SocketAddr>) -> Res
     StatusCode::OK.into_response()
 }
 
+/// Header holding the id given to every request, also used to identify requests in error responses
+const REQUEST_ID_HEADER: &str = "x-request-d";
+
 mod api;
 mod error;
+mod extract;
 mod middleware;
 
 #[derive(Debug, Clone, Default)]
@@ -62,13 +73,18 @@ impl MakeRequestId for VislogMakeRequestId {
 pub fn init_server(
     programs_provider: ProgramsProvider,
     courses_provider: CoursesProvider,
+    scheduler: Scheduler,
     static_dir_path: Option<PathBuf>,
 ) -> Router {
-    let x_request_id = HeaderName::from_static("x-request-d");
+    let x_request_id = HeaderName::from_static(REQUEST_ID_HEADER);
 
     let server = Router::new()
         .route("/check_health", get(check_health_handler))
-        .nest("/api", api::routes(programs_provider, courses_provider));
+        .nest(
+            "/api",
+            api::routes(programs_provider, courses_provider, scheduler),
+        )
+        .merge(api::docs());
 
     let server = if let Some(path) = static_dir_path {
         server.nest_service("/", ServeDir::new(path))
@@ -88,8 +104,9 @@ pub fn init_server(
                     .make_span_with(DefaultMakeSpan::new().include_headers(true))
                     .on_response(DefaultOnResponse::new().include_headers(true)),
             )
-            .layer(map_response(
-                middleware::cors::mw_set_access_control_allow_origin,
-            )),
+            .layer(middleware::cors::cors_layer(CONFIGS.cors.as_ref()))
+            .layer(CompressionLayer::new())
+            .layer(map_response(middleware::errors::mw_map_error_response))
+            .layer(map_response(middleware::caching::mw_conditional_get)),
     )
 }